@@ -0,0 +1,436 @@
+use std::{
+  path::PathBuf,
+  time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+
+use crate::chat_stream::ChatStreamEvent;
+use crate::core::{new_id, DirLock, EventSink};
+use crate::chats::{load_thread_in, save_thread_in, ChatMessage, ChatRole, ChatThread};
+use crate::ollama::{OllamaChatReq, OllamaMessage, OllamaOptions, OllamaRole};
+use crate::secrets::RunSecrets;
+use crate::settings::ProfileSettings;
+use crate::tools::ToolCall;
+use crate::trace::{AgentTrace, LoopBudget, LoopPause, TraceStep};
+
+// The chat tool loop. `chat_send` (blocking) and `chat_send_stream` (streaming) both run
+// through `run`; the only difference is whether model output is streamed to the sink.
+// Nothing in here touches the Tauri runtime directly, so it can be driven from tests.
+
+// Typed view of an event sink for chat_stream events. Any `EventSink` (the AppHandle in the
+// GUI) is an AgentSink; tests can implement it directly to collect events.
+pub trait AgentSink {
+  fn chat_event(&self, ev: ChatStreamEvent);
+}
+
+impl<S: EventSink + ?Sized> AgentSink for S {
+  fn chat_event(&self, ev: ChatStreamEvent) {
+    crate::core::emit(self, "chat_stream", &ev);
+  }
+}
+
+pub struct AgentRun {
+  pub profile_id: String,
+  pub chat_id: String,
+  // Placeholder assistant message (already in the thread) that receives the reply.
+  pub assistant_message_id: String,
+  // Holds chat_{id}.json; also the working directory for exec.
+  pub profile_dir: PathBuf,
+  pub memory_path: PathBuf,
+  pub settings: ProfileSettings,
+  pub system_prompt: String,
+  // Overrides settings.agent_limits.max_steps (used by `chat_continue`).
+  pub max_steps: Option<u32>,
+  pub stream: bool,
+  // Env for exec, the Ollama key and the values to redact from tool output.
+  pub secrets: RunSecrets,
+}
+
+pub enum LoopEnd {
+  Reply(String),
+  Paused(LoopPause),
+  Failed(String),
+}
+
+pub struct AgentOutcome {
+  pub end: LoopEnd,
+  pub trace: AgentTrace,
+}
+
+pub fn strip_ollama_prefix(model_id: &str) -> String {
+  model_id.strip_prefix("ollama/").unwrap_or(model_id).to_string()
+}
+
+pub fn extract_first_json_object(s: &str) -> Option<(String, String)> {
+  // Extract the first {...} JSON object from a string, ignoring braces inside strings.
+  let mut in_str = false;
+  let mut escape = false;
+  let mut depth: i32 = 0;
+  let mut start: Option<usize> = None;
+
+  for (i, ch) in s.char_indices() {
+    if in_str {
+      if escape {
+        escape = false;
+      } else if ch == '\\' {
+        escape = true;
+      } else if ch == '"' {
+        in_str = false;
+      }
+      continue;
+    }
+
+    if ch == '"' {
+      in_str = true;
+      continue;
+    }
+
+    if ch == '{' {
+      if depth == 0 {
+        start = Some(i);
+      }
+      depth += 1;
+      continue;
+    }
+
+    if ch == '}' && depth > 0 {
+      depth -= 1;
+      if depth == 0 {
+        if let Some(st) = start {
+          let json = s[st..=i].to_string();
+          let rest = s[i + 1..].to_string();
+          return Some((json, rest));
+        }
+      }
+    }
+  }
+
+  None
+}
+
+pub fn parse_tool_call(content: &str) -> Option<ToolCall> {
+  serde_json::from_str::<ToolCall>(content)
+    .ok()
+    .or_else(|| extract_first_json_object(content).and_then(|(j, _rest)| serde_json::from_str::<ToolCall>(&j).ok()))
+}
+
+fn is_http_url(u: &str) -> bool {
+  u.starts_with("http://") || u.starts_with("https://")
+}
+
+// Classifies the last user message once (auto-do mode only) and records the decision on it.
+fn decide_intent(settings: &ProfileSettings, api_key: Option<&str>, thread: &mut ChatThread) -> bool {
+  if !settings.auto_do_mode.unwrap_or(false) {
+    return false;
+  }
+  let Some(last_user) = thread.messages.iter_mut().rev().find(|m| matches!(m.role, ChatRole::User)) else {
+    return false;
+  };
+  if last_user.intent.is_some() {
+    return false;
+  }
+
+  let cfg = settings.intent.clone().unwrap_or_default();
+  let base_url = settings.ollama_base_url.clone().unwrap_or_else(|| "http://localhost:11434".to_string());
+  let model = settings.ollama_model.clone().unwrap_or_else(|| "ollama/huihui_ai/qwen3-abliterated:8b".to_string());
+  last_user.intent = Some(crate::intent::classify(&cfg, &base_url, api_key, &model, &last_user.text));
+  true
+}
+
+fn base_msgs_for_thread(system: &str, auto_do_mode: bool, thread: &ChatThread, assistant_message_id: &str, take_last: usize) -> Vec<OllamaMessage> {
+  let mut msgs: Vec<OllamaMessage> = vec![OllamaMessage {
+    role: OllamaRole::System,
+    content: system.to_string(),
+  }];
+
+  // The placeholder we're about to fill is not part of the prompt.
+  let history = thread.messages.iter().filter(|m| m.id != assistant_message_id);
+  let history: Vec<&ChatMessage> = history.collect();
+  for m in history.iter().rev().take(take_last).rev() {
+    let role = match m.role {
+      ChatRole::User => OllamaRole::User,
+      ChatRole::Assistant => OllamaRole::Assistant,
+      ChatRole::Tool => OllamaRole::User,
+    };
+    msgs.push(OllamaMessage { role, content: m.text.clone() });
+  }
+
+  if auto_do_mode {
+    // If the last user message was classified as an action request, force tool-mode.
+    if let Some(last_user) = thread.messages.iter().rev().find(|m| matches!(m.role, ChatRole::User)) {
+      if last_user.intent.as_ref().is_some_and(|d| d.force_tools) {
+        msgs.push(OllamaMessage {
+          role: OllamaRole::User,
+          content: "AUTO-DO: This is an action request. Reply with a single tool JSON (exec/web_get) to actually do the work. Do not answer with a plan.".to_string(),
+        });
+      }
+    }
+  }
+
+  msgs
+}
+
+fn prompt_chars(msgs: &[OllamaMessage]) -> usize {
+  msgs.iter().map(|m| m.content.len()).sum()
+}
+
+struct Engine<'a, S: AgentSink + ?Sized> {
+  req: &'a AgentRun,
+  sink: &'a S,
+}
+
+impl<S: AgentSink + ?Sized> Engine<'_, S> {
+  fn event(&self, message_id: &str, delta: String, done: bool) -> ChatStreamEvent {
+    ChatStreamEvent {
+      profile_id: self.req.profile_id.clone(),
+      chat_id: self.req.chat_id.clone(),
+      message_id: message_id.to_string(),
+      delta,
+      done,
+      error: None,
+      new_role: None,
+      new_created_at_ms: None,
+      pause: None,
+    }
+  }
+
+  fn load(&self) -> Result<ChatThread> {
+    load_thread_in(&self.req.profile_dir, &self.req.chat_id).context("reload thread")
+  }
+
+  // Reload, apply `f`, save, under the profile dir lock (another process may be editing the chat).
+  fn update_thread<F: FnOnce(&mut ChatThread)>(&self, f: F) -> Result<()> {
+    let _lock = DirLock::acquire(&self.req.profile_dir)?;
+    let mut t = self.load()?;
+    f(&mut t);
+    save_thread_in(&self.req.profile_dir, &t)
+  }
+
+  fn update_assistant<F: FnOnce(&mut ChatMessage)>(&self, f: F) -> Result<()> {
+    self.update_thread(|t| {
+      if let Some(m) = t.messages.iter_mut().find(|m| m.id == self.req.assistant_message_id) {
+        f(m);
+      }
+    })
+  }
+
+  // Appends a tool step to the thread and tells the sink about the new message. Secret values
+  // are redacted first.
+  fn record_tool_message(&self, text: String) {
+    let text = self.req.secrets.redact(&text);
+    let tool_id = new_id("t");
+    let created_at_ms = crate::chats::now_ms();
+    self
+      .update_thread(|t| {
+        t.messages.push(ChatMessage {
+          id: tool_id.clone(),
+          role: ChatRole::Tool,
+          text: text.clone(),
+          created_at_ms,
+          intent: None,
+          pause: None,
+        })
+      })
+      .ok();
+
+    let mut ev = self.event(&tool_id, text, true);
+    ev.new_role = Some("tool".to_string());
+    ev.new_created_at_ms = Some(created_at_ms);
+    self.sink.chat_event(ev);
+  }
+
+  // One model call. Streams deltas to the sink (and throttled to disk) in streaming mode.
+  fn model_call(&self, base_url: &str, model: &str, msgs: &[OllamaMessage]) -> Result<String> {
+    let req = OllamaChatReq {
+      model: model.to_string(),
+      messages: msgs.to_vec(),
+      stream: self.req.stream,
+      options: self.req.settings.ollama_num_ctx.map(|n| OllamaOptions { num_ctx: Some(n) }),
+    };
+
+    if !self.req.stream {
+      return Ok(crate::ollama::chat(base_url, self.req.secrets.ollama_key.as_deref(), req)?.message.content);
+    }
+
+    let id = self.req.assistant_message_id.as_str();
+    let mut accumulated = String::new();
+    let mut last_persist = Instant::now();
+    crate::ollama::chat_stream(base_url, self.req.secrets.ollama_key.as_deref(), req, |delta, done| {
+      if !delta.is_empty() {
+        accumulated.push_str(&delta);
+        self.sink.chat_event(self.event(id, delta.clone(), false));
+
+        if last_persist.elapsed() > Duration::from_millis(250) {
+          self.update_assistant(|m| m.text.push_str(&delta)).ok();
+          last_persist = Instant::now();
+        }
+      }
+
+      if done {
+        self.sink.chat_event(self.event(id, "".to_string(), true));
+      }
+
+      Ok(())
+    })?;
+    Ok(accumulated)
+  }
+
+  fn run_loop(&self, trace: &mut AgentTrace) -> Result<LoopEnd> {
+    let req = self.req;
+    let settings = &req.settings;
+
+    // The classifier may call the model, so decide on a copy and merge the result back.
+    let mut thread = self.load()?;
+    if decide_intent(settings, req.secrets.ollama_key.as_deref(), &mut thread) {
+      let decided: Vec<ChatMessage> = thread.messages.iter().filter(|m| m.intent.is_some()).cloned().collect();
+      self
+        .update_thread(|t| {
+          for m in t.messages.iter_mut().filter(|m| m.intent.is_none()) {
+            if let Some(d) = decided.iter().find(|d| d.id == m.id) {
+              m.intent = d.intent.clone();
+            }
+          }
+        })
+        .ok();
+    }
+
+    let base_url = settings.ollama_base_url.clone().unwrap_or_else(|| "http://localhost:11434".to_string());
+    let model_id = settings.ollama_model.clone().unwrap_or_else(|| "ollama/huihui_ai/qwen3-abliterated:8b".to_string());
+    let model = strip_ollama_prefix(&model_id);
+    let dev_full_exec_auto = settings.dev_full_exec_auto.unwrap_or(false);
+    let auto_do_mode = settings.auto_do_mode.unwrap_or(false);
+
+    let mut limits = settings.agent_limits.clone().unwrap_or_default();
+    if let Some(n) = req.max_steps {
+      limits.max_steps = n;
+    }
+    let mut budget = LoopBudget::new(limits);
+
+    // Keep last N messages.
+    let mut msgs = base_msgs_for_thread(&req.system_prompt, auto_do_mode, &thread, &req.assistant_message_id, 16);
+
+    loop {
+      if let Some(p) = budget.check() {
+        return Ok(LoopEnd::Paused(p));
+      }
+      budget.steps += 1;
+
+      let started = Instant::now();
+      let prompt_messages = msgs.len();
+      let prompt_chars = prompt_chars(&msgs);
+      let content = self.model_call(&base_url, &model, &msgs)?;
+      trace.steps.push(TraceStep {
+        index: budget.steps,
+        prompt_messages,
+        prompt_chars,
+        model_latency_ms: started.elapsed().as_millis() as u64,
+        response_chars: content.len(),
+        tool: None,
+        result_bytes: None,
+        note: None,
+      });
+
+      // Persist the full step output (streaming persists are throttled).
+      if !content.is_empty() {
+        self.update_assistant(|m| m.text = content.clone()).ok();
+      }
+
+      let Some(call) = parse_tool_call(&content) else {
+        // Not a tool call => the content is the answer.
+        return Ok(LoopEnd::Reply(content));
+      };
+
+      let tool = call.name();
+      let result = match call {
+        ToolCall::Final { text } => {
+          self.update_assistant(|m| m.text = text.clone()).ok();
+          return Ok(LoopEnd::Reply(text));
+        }
+        ToolCall::WebGet { url } => {
+          if !is_http_url(&url) {
+            // Tell the model this tool only supports http(s)
+            trace.tool(tool, 0, Some("rejected: not an http(s) URL"));
+            format!("Tool error: web_get only supports http(s) URLs, got: {url}. Use exec (cat/ls) for local files.")
+          } else {
+            let out = crate::tools::web_get(&url).unwrap_or_else(|e| format!("[tool_error] {e}"));
+            budget.add_tool_output(&out);
+            trace.tool(tool, out.len(), None);
+            self.record_tool_message(format!("web_get:\n{url}\n\n{out}"));
+            format!("Tool result (web_get):\nURL: {url}\n\n{out}")
+          }
+        }
+        ToolCall::Exec { cmd } => {
+          if !dev_full_exec_auto {
+            trace.tool(tool, 0, Some("denied"));
+            self.record_tool_message(format!("exec denied (Developer Mode off):\n$ {cmd}"));
+            "Tool denied: exec is disabled (Developer Mode off). Return a final answer without exec.".to_string()
+          } else {
+            let cwd = &req.profile_dir;
+            let out = crate::tools::exec(&cmd, cwd, &req.secrets.exec_env).unwrap_or_else(|e| format!("[tool_error] {e}"));
+            budget.add_tool_output(&out);
+            trace.tool(tool, out.len(), None);
+            self.record_tool_message(format!("exec (cwd={}):\n$ {}\n\n{}", cwd.to_string_lossy(), cmd, out));
+            format!("Tool result (exec):\n$ {cmd}\n\n{out}")
+          }
+        }
+        call @ (ToolCall::Remember { .. } | ToolCall::Recall { .. } | ToolCall::Forget { .. }) => {
+          let out = crate::memory::run_tool(&req.memory_path, &call);
+          budget.add_tool_output(&out);
+          trace.tool(tool, out.len(), None);
+          self.record_tool_message(format!("{tool}:\n{out}"));
+          format!("Tool result ({tool}):\n{out}")
+        }
+      };
+
+      msgs.push(OllamaMessage { role: OllamaRole::Assistant, content });
+      msgs.push(OllamaMessage { role: OllamaRole::User, content: req.secrets.redact(&result) });
+    }
+  }
+}
+
+// Runs the tool loop for one assistant message and leaves the outcome in the thread:
+// the reply text, a pause note (with `pause` set), or an error.
+pub fn run<S: AgentSink + ?Sized>(req: &AgentRun, sink: &S) -> AgentOutcome {
+  let engine = Engine { req, sink };
+  let mut trace = AgentTrace::new(&req.assistant_message_id);
+  trace.warnings.extend(req.secrets.skipped.iter().cloned());
+
+  let end = match engine.run_loop(&mut trace) {
+    Ok(end) => end,
+    Err(e) => LoopEnd::Failed(format!("{e:#}")),
+  };
+
+  match &end {
+    LoopEnd::Reply(_) => trace.finish("final"),
+    LoopEnd::Paused(p) => {
+      trace.finish("paused");
+      let note = crate::trace::pause_text(p);
+      engine
+        .update_assistant(|m| {
+          m.text = if m.text.is_empty() { note.clone() } else { format!("{}\n\n{note}", m.text) };
+          m.pause = Some(p.clone());
+        })
+        .ok();
+      let mut ev = engine.event(&req.assistant_message_id, format!("\n\n{note}"), true);
+      ev.pause = Some(p.clone());
+      sink.chat_event(ev);
+    }
+    LoopEnd::Failed(err) => {
+      trace.finish("error");
+      // Store error as assistant message text (keeps UI consistent)
+      engine
+        .update_assistant(|m| {
+          if m.text.is_empty() {
+            m.text = format!("[error] {err}");
+          }
+        })
+        .ok();
+      let mut ev = engine.event(&req.assistant_message_id, "".to_string(), true);
+      ev.error = Some(err.clone());
+      sink.chat_event(ev);
+    }
+  }
+
+  AgentOutcome { end, trace }
+}
@@ -0,0 +1,512 @@
+use std::{
+  collections::HashMap,
+  io::{BufRead, BufReader, Read, Write},
+  net::{SocketAddr, TcpListener, TcpStream},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  },
+  thread::JoinHandle,
+  time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::agent::{strip_ollama_prefix, LoopEnd};
+use crate::chat_stream::ReplyText;
+use crate::chats::{self, ChatMessage, ChatRole};
+use crate::core::{EventSink, NullSink, Storage};
+use crate::settings::ApiServerConfig;
+
+// Opt-in OpenAI-compatible API on 127.0.0.1, so editors and scripts can use a profile's agent
+// (tools, memory, prompt, limits). One server per profile, serving `/v1/models` and
+// `/v1/chat/completions` (blocking or SSE). Requests need `Authorization: Bearer <api_token>`,
+// where the token is the profile's `api_token` secret (secret_set).
+
+pub const API_TOKEN_SECRET: &str = "api_token";
+
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+pub struct ApiServer {
+  addr: SocketAddr,
+  stop: Arc<AtomicBool>,
+  accept: Option<JoinHandle<()>>,
+}
+
+impl ApiServer {
+  pub fn addr(&self) -> SocketAddr {
+    self.addr
+  }
+}
+
+impl Drop for ApiServer {
+  // Stops accepting and releases the port; requests already running finish on their own.
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::SeqCst);
+    // Wake the accept loop so it sees the flag.
+    let _ = TcpStream::connect(self.addr);
+    if let Some(h) = self.accept.take() {
+      let _ = h.join();
+    }
+  }
+}
+
+struct Ctx {
+  st: Storage,
+  profile_id: String,
+  chat: Option<String>,
+  token: String,
+}
+
+// Binds 127.0.0.1:{config.port} (0 picks a free port) and serves until the returned handle is dropped.
+pub fn start(st: Storage, profile_id: &str, config: &ApiServerConfig, token: &str) -> Result<ApiServer> {
+  if token.trim().is_empty() {
+    return Err(anyhow!("API token is empty"));
+  }
+  let listener = TcpListener::bind(("127.0.0.1", config.port)).with_context(|| format!("failed to listen on 127.0.0.1:{}", config.port))?;
+  let addr = listener.local_addr()?;
+  let stop = Arc::new(AtomicBool::new(false));
+  let ctx = Arc::new(Ctx {
+    st,
+    profile_id: profile_id.to_string(),
+    chat: config.chat.clone(),
+    token: token.trim().to_string(),
+  });
+
+  let flag = stop.clone();
+  let accept = std::thread::spawn(move || {
+    for conn in listener.incoming() {
+      if flag.load(Ordering::SeqCst) {
+        break;
+      }
+      let Ok(stream) = conn else { continue };
+      let ctx = ctx.clone();
+      std::thread::spawn(move || handle(&ctx, stream));
+    }
+  });
+
+  Ok(ApiServer { addr, stop, accept: Some(accept) })
+}
+
+struct Request {
+  method: String,
+  path: String,
+  headers: HashMap<String, String>,
+  body: Vec<u8>,
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request> {
+  let mut r = BufReader::new(stream);
+  let mut line = String::new();
+  r.read_line(&mut line).context("failed to read request")?;
+  let mut parts = line.split_whitespace();
+  let method = parts.next().ok_or_else(|| anyhow!("empty request"))?.to_string();
+  let target = parts.next().ok_or_else(|| anyhow!("missing request path"))?;
+  let path = target.split('?').next().unwrap_or_default().to_string();
+
+  let mut headers = HashMap::new();
+  loop {
+    let mut h = String::new();
+    if r.read_line(&mut h).context("failed to read headers")? == 0 {
+      break;
+    }
+    let h = h.trim_end();
+    if h.is_empty() {
+      break;
+    }
+    if let Some((k, v)) = h.split_once(':') {
+      headers.insert(k.trim().to_ascii_lowercase(), v.trim().to_string());
+    }
+  }
+
+  let len: usize = headers.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+  if len > MAX_BODY_BYTES {
+    return Err(anyhow!("request body too large"));
+  }
+  let mut body = vec![0; len];
+  r.read_exact(&mut body).context("failed to read request body")?;
+  Ok(Request { method, path, headers, body })
+}
+
+fn authorized(req: &Request, token: &str) -> bool {
+  let Some(given) = req.headers.get("authorization").and_then(|h| h.strip_prefix("Bearer ")) else {
+    return false;
+  };
+  // Compare without an early exit on the first differing byte.
+  let (a, b) = (given.trim().as_bytes(), token.as_bytes());
+  a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// An OpenAI-style error response.
+struct ApiError {
+  status: u16,
+  kind: &'static str,
+  message: String,
+}
+
+impl ApiError {
+  fn new(status: u16, kind: &'static str, message: impl Into<String>) -> Self {
+    Self { status, kind, message: message.into() }
+  }
+
+  fn body(&self) -> Value {
+    json!({ "error": { "message": self.message, "type": self.kind } })
+  }
+}
+
+impl From<anyhow::Error> for ApiError {
+  fn from(e: anyhow::Error) -> Self {
+    let message = format!("{e:#}");
+    if message.contains("chat is busy") {
+      Self::new(409, "conflict", message)
+    } else {
+      Self::new(500, "server_error", message)
+    }
+  }
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &Value) {
+  let reason = match status {
+    200 => "OK",
+    400 => "Bad Request",
+    401 => "Unauthorized",
+    404 => "Not Found",
+    409 => "Conflict",
+    502 => "Bad Gateway",
+    _ => "Internal Server Error",
+  };
+  let body = body.to_string();
+  let head = format!(
+    "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+    body.len()
+  );
+  let _ = stream.write_all(head.as_bytes());
+  let _ = stream.write_all(body.as_bytes());
+}
+
+fn handle(ctx: &Ctx, mut stream: TcpStream) {
+  stream.set_read_timeout(Some(Duration::from_secs(30))).ok();
+  let req = match read_request(&stream) {
+    Ok(r) => r,
+    Err(e) => {
+      respond(&mut stream, 400, &ApiError::new(400, "invalid_request_error", format!("{e:#}")).body());
+      return;
+    }
+  };
+  if !authorized(&req, &ctx.token) {
+    respond(&mut stream, 401, &ApiError::new(401, "authentication_error", "invalid or missing bearer token").body());
+    return;
+  }
+
+  let res = match (req.method.as_str(), req.path.as_str()) {
+    ("GET", "/v1/models") => models(ctx).map(|v| respond(&mut stream, 200, &v)),
+    ("POST", "/v1/chat/completions") => completions(ctx, &mut stream, &req.body),
+    _ => Err(ApiError::new(404, "invalid_request_error", format!("unknown endpoint: {} {}", req.method, req.path))),
+  };
+  if let Err(e) = res {
+    respond(&mut stream, e.status, &e.body());
+  }
+}
+
+fn now_secs() -> i64 {
+  chats::now_ms() / 1000
+}
+
+// The profile's Ollama model is the one model the server offers.
+fn served_model(ctx: &Ctx) -> Result<String> {
+  let s = crate::settings::load_settings(&ctx.st, &ctx.profile_id)?;
+  Ok(s.ollama_model.unwrap_or_default())
+}
+
+fn models(ctx: &Ctx) -> Result<Value, ApiError> {
+  let model = served_model(ctx)?;
+  Ok(json!({
+    "object": "list",
+    "data": [{ "id": model, "object": "model", "created": 0, "owned_by": "openclaw-desktop" }],
+  }))
+}
+
+#[derive(Deserialize)]
+struct CompletionReq {
+  #[serde(default)]
+  model: String,
+  messages: Vec<ReqMessage>,
+  #[serde(default)]
+  stream: bool,
+  // Extension: chat (id or title) to write into; overrides the profile's api_server.chat.
+  #[serde(default)]
+  chat: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReqMessage {
+  role: String,
+  #[serde(default)]
+  content: Value,
+}
+
+impl ReqMessage {
+  // Plain string content, or the text parts of a content array.
+  fn text(&self) -> String {
+    match &self.content {
+      Value::String(s) => s.clone(),
+      Value::Array(parts) => parts.iter().filter_map(|p| p["text"].as_str()).collect::<Vec<_>>().join("\n"),
+      _ => String::new(),
+    }
+  }
+}
+
+// Client messages as thread history, plus their system/developer messages as extra system prompt.
+fn detached_history(messages: &[ReqMessage]) -> (Vec<ChatMessage>, String) {
+  let mut system = vec![];
+  let mut history = vec![];
+  for m in messages {
+    let role = match m.role.as_str() {
+      "system" | "developer" => {
+        system.push(m.text());
+        continue;
+      }
+      "user" => ChatRole::User,
+      "assistant" => ChatRole::Assistant,
+      "tool" => ChatRole::Tool,
+      _ => continue,
+    };
+    history.push(ChatMessage {
+      id: crate::core::new_id("m"),
+      role,
+      text: m.text(),
+      created_at_ms: chats::now_ms(),
+      intent: None,
+      pause: None,
+    });
+  }
+  (history, system.join("\n\n"))
+}
+
+fn completions(ctx: &Ctx, stream: &mut TcpStream, body: &[u8]) -> Result<(), ApiError> {
+  let req: CompletionReq = serde_json::from_slice(body).map_err(|e| ApiError::new(400, "invalid_request_error", format!("invalid request: {e}")))?;
+
+  let model = served_model(ctx)?;
+  if !req.model.is_empty() && req.model != model && req.model != strip_ollama_prefix(&model) {
+    return Err(ApiError::new(404, "invalid_request_error", format!("model not found: {} (this profile serves {model})", req.model)));
+  }
+  let last_user = req
+    .messages
+    .iter()
+    .rev()
+    .find(|m| m.role == "user")
+    .map(|m| m.text())
+    .filter(|t| !t.trim().is_empty())
+    .ok_or_else(|| ApiError::new(400, "invalid_request_error", "messages must end with a user message"))?;
+
+  // With a target chat, only the latest user message is sent; the chat keeps its own history
+  // and prompt. Without one, the client's messages are the whole conversation.
+  let target = req.chat.clone().or_else(|| ctx.chat.clone());
+  let run = |sink: &dyn EventSink| -> Result<LoopEnd> {
+    match &target {
+      Some(name) => {
+        let chat = chats::find_or_create(&ctx.st, &ctx.profile_id, name)?;
+        Ok(chats::send(&ctx.st, sink, &ctx.profile_id, &chat.id, last_user.trim(), req.stream)?.1)
+      }
+      None => {
+        let (history, system) = detached_history(&req.messages);
+        chats::run_detached(&ctx.st, sink, &ctx.profile_id, history, Some(&system), req.stream)
+      }
+    }
+  };
+
+  let id = crate::core::new_id("chatcmpl");
+  if req.stream {
+    let sink = SseSink::new(stream, &id, &model)?;
+    let end = run(&sink)?;
+    sink.finish(&end);
+    return Ok(());
+  }
+
+  let (content, finish_reason) = match run(&NullSink)? {
+    LoopEnd::Reply(text) => (text, "stop"),
+    LoopEnd::Paused(p) => (crate::trace::pause_text(&p), "length"),
+    LoopEnd::Failed(e) => return Err(ApiError::new(502, "server_error", e)),
+  };
+  respond(
+    stream,
+    200,
+    &json!({
+      "id": id,
+      "object": "chat.completion",
+      "created": now_secs(),
+      "model": model,
+      "choices": [{ "index": 0, "message": { "role": "assistant", "content": content }, "finish_reason": finish_reason }],
+    }),
+  );
+  Ok(())
+}
+
+struct SseState {
+  stream: TcpStream,
+  // Headers go out with the first event, so errors before the loop starts are plain responses.
+  started: bool,
+  reply: ReplyText,
+}
+
+// Turns chat_stream events into `chat.completion.chunk` server-sent events. Tool steps are not
+// forwarded; the client only sees answer text.
+struct SseSink {
+  state: Mutex<SseState>,
+  id: String,
+  model: String,
+  created: i64,
+}
+
+impl SseSink {
+  fn new(stream: &TcpStream, id: &str, model: &str) -> Result<Self> {
+    let stream = stream.try_clone().context("failed to clone connection")?;
+    Ok(Self {
+      state: Mutex::new(SseState { stream, started: false, reply: ReplyText::default() }),
+      id: id.to_string(),
+      model: model.to_string(),
+      created: now_secs(),
+    })
+  }
+
+  fn chunk(&self, delta: Value, finish_reason: Option<&str>) -> Value {
+    json!({
+      "id": self.id,
+      "object": "chat.completion.chunk",
+      "created": self.created,
+      "model": self.model,
+      "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+    })
+  }
+
+  fn send(&self, st: &mut SseState, data: &Value) {
+    if !st.started {
+      st.started = true;
+      let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+      let _ = st.stream.write_all(head.as_bytes());
+      let role = self.chunk(json!({ "role": "assistant", "content": "" }), None);
+      let _ = st.stream.write_all(format!("data: {role}\n\n").as_bytes());
+    }
+    // A client that went away doesn't stop the loop; the reply still lands in the chat.
+    let _ = st.stream.write_all(format!("data: {data}\n\n").as_bytes());
+    let _ = st.stream.flush();
+  }
+
+  fn finish(&self, end: &LoopEnd) {
+    let Ok(mut st) = self.state.lock() else { return };
+    match end {
+      LoopEnd::Reply(_) => self.send(&mut st, &self.chunk(json!({}), Some("stop"))),
+      LoopEnd::Paused(_) => self.send(&mut st, &self.chunk(json!({}), Some("length"))),
+      // Already reported by the error event.
+      LoopEnd::Failed(_) => {}
+    }
+    let _ = st.stream.write_all(b"data: [DONE]\n\n");
+  }
+}
+
+impl EventSink for SseSink {
+  fn emit_json(&self, event: &str, ev: Value) {
+    if event != "chat_stream" || ev["new_role"].as_str() == Some("tool") {
+      return;
+    }
+    let Ok(mut st) = self.state.lock() else { return };
+    let delta = ev["delta"].as_str().unwrap_or_default();
+    if let Some(err) = ev["error"].as_str() {
+      self.send(&mut st, &ApiError::new(502, "server_error", err).body());
+      return;
+    }
+    if !ev["pause"].is_null() {
+      self.send(&mut st, &self.chunk(json!({ "content": delta }), None));
+      return;
+    }
+    let (text, _) = st.reply.push(delta, ev["done"].as_bool().unwrap_or(false));
+    if !text.is_empty() {
+      self.send(&mut st, &self.chunk(json!({ "content": text }), None));
+    }
+  }
+}
+
+// Running servers, by profile id.
+static SERVERS: Lazy<Mutex<HashMap<String, ApiServer>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// Why an enabled server didn't start with the app, by profile id, until it's started or stopped.
+static START_ERRORS: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize)]
+pub struct ApiServerStatus {
+  pub running: bool,
+  // e.g. "http://127.0.0.1:8765/v1"
+  pub base_url: Option<String>,
+  pub config: Option<ApiServerConfig>,
+  // Set when the server is enabled but failed to start with the app.
+  pub start_error: Option<String>,
+}
+
+pub fn status(st: &Storage, profile_id: &str) -> Result<ApiServerStatus> {
+  let config = crate::settings::load_settings(st, profile_id)?.api_server;
+  let servers = SERVERS.lock().map_err(|_| anyhow!("api server lock poisoned"))?;
+  let base_url = servers.get(profile_id).map(|s| format!("http://{}/v1", s.addr()));
+  let start_error = START_ERRORS.lock().map_err(|_| anyhow!("api server lock poisoned"))?.get(profile_id).cloned();
+  Ok(ApiServerStatus { running: base_url.is_some(), base_url, config, start_error })
+}
+
+// (Re)starts the profile's server with its current settings and token.
+pub fn start_for_profile(st: &Storage, profile_id: &str) -> Result<ApiServerStatus> {
+  let config = crate::settings::load_settings(st, profile_id)?
+    .api_server
+    .ok_or_else(|| anyhow!("the API server is not enabled for this profile"))?;
+  let token = crate::secrets::get_secret(st, profile_id, API_TOKEN_SECRET)?
+    .filter(|t| !t.trim().is_empty())
+    .ok_or_else(|| anyhow!("set the `{API_TOKEN_SECRET}` secret for this profile first"))?;
+  crate::secrets::mark_used(st, profile_id, &[API_TOKEN_SECRET]);
+
+  {
+    let mut servers = SERVERS.lock().map_err(|_| anyhow!("api server lock poisoned"))?;
+    // Drop the old server first so a restart can reuse its port.
+    servers.remove(profile_id);
+    let server = start(st.clone(), profile_id, &config, &token)?;
+    servers.insert(profile_id.to_string(), server);
+  }
+  START_ERRORS.lock().map_err(|_| anyhow!("api server lock poisoned"))?.remove(profile_id);
+  status(st, profile_id)
+}
+
+pub fn stop_for_profile(st: &Storage, profile_id: &str) -> Result<ApiServerStatus> {
+  SERVERS.lock().map_err(|_| anyhow!("api server lock poisoned"))?.remove(profile_id);
+  START_ERRORS.lock().map_err(|_| anyhow!("api server lock poisoned"))?.remove(profile_id);
+  status(st, profile_id)
+}
+
+// App startup: starts the server for every profile that has it enabled. Failures show up in
+// `status` as `start_error`.
+pub fn start_enabled(st: &Storage) {
+  let Ok(store) = crate::profiles::ensure_default_profile(st) else { return };
+  for p in store.profiles {
+    let enabled = crate::settings::load_settings(st, &p.id).is_ok_and(|s| s.api_server.is_some());
+    if enabled {
+      if let Err(e) = start_for_profile(st, &p.id) {
+        if let Ok(mut errors) = START_ERRORS.lock() {
+          errors.insert(p.id, format!("{e:#}"));
+        }
+      }
+    }
+  }
+}
+
+#[tauri::command]
+pub fn api_server_status(storage: State<'_, Storage>, profile_id: String) -> Result<ApiServerStatus, String> {
+  status(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn api_server_start(storage: State<'_, Storage>, profile_id: String) -> Result<ApiServerStatus, String> {
+  start_for_profile(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn api_server_stop(storage: State<'_, Storage>, profile_id: String) -> Result<ApiServerStatus, String> {
+  stop_for_profile(&storage, &profile_id).map_err(|e| e.to_string())
+}
@@ -0,0 +1,289 @@
+// Headless front end for the desktop app's data: same profiles, chats and settings files,
+// safe to run while the GUI is open (see core::DirLock and the per-chat send locks).
+
+use std::{
+  io::{Read, Write},
+  path::PathBuf,
+  process::ExitCode,
+  sync::Mutex,
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+
+use tauri_appopenclaw_macapp_lib::{
+  agent::LoopEnd,
+  chat_stream::ReplyText,
+  chats,
+  core::{EventSink, Storage},
+  gateway, profiles, settings,
+};
+
+const USAGE: &str = "usage: openclaw-desktop [--data-dir DIR] [--profile ID] <command> [args]
+
+commands:
+  profiles                        list profiles (* marks the active one)
+  chats                           list chats in the profile
+  chats create [TITLE]            create a chat and print its id
+  send <CHAT_ID> <TEXT>           send a message and stream the reply to stdout
+                                  (TEXT `-` reads stdin; --no-stream prints it when done)
+  export <CHAT_ID> [--json]       print the thread as markdown (or JSON)
+  gateway <status|start|stop|restart>  (--json adds the parsed status)
+  settings                        print the profile settings as JSON
+  settings get <KEY>
+  settings set <KEY> <VALUE>      VALUE is parsed as JSON when possible; `null` clears
+
+The data dir defaults to the GUI's (override with --data-dir or OPENCLAW_DESKTOP_DATA_DIR).
+The profile defaults to the GUI's active profile.";
+
+struct Cli {
+  data_dir: Option<PathBuf>,
+  profile: Option<String>,
+  no_stream: bool,
+  json: bool,
+  args: Vec<String>,
+}
+
+fn parse_args(raw: Vec<String>) -> Result<Cli> {
+  let mut cli = Cli { data_dir: None, profile: None, no_stream: false, json: false, args: vec![] };
+  let mut it = raw.into_iter();
+  while let Some(a) = it.next() {
+    match a.as_str() {
+      "--data-dir" => cli.data_dir = Some(it.next().ok_or_else(|| anyhow!("--data-dir needs a value"))?.into()),
+      "--profile" => cli.profile = Some(it.next().ok_or_else(|| anyhow!("--profile needs a value"))?),
+      "--no-stream" => cli.no_stream = true,
+      "--json" => cli.json = true,
+      "-h" | "--help" => return Err(anyhow!("help")),
+      _ => cli.args.push(a),
+    }
+  }
+  Ok(cli)
+}
+
+fn storage(cli: &Cli) -> Result<Storage> {
+  let default = Storage::default_location()?;
+  let root = cli
+    .data_dir
+    .clone()
+    .or_else(|| std::env::var_os("OPENCLAW_DESKTOP_DATA_DIR").map(PathBuf::from));
+  Ok(match root {
+    Some(r) => Storage::new(r, default.home()),
+    None => default,
+  })
+}
+
+fn profile_id(st: &Storage, cli: &Cli) -> Result<String> {
+  let store = profiles::ensure_default_profile(st)?;
+  match &cli.profile {
+    Some(p) => store
+      .profiles
+      .iter()
+      .find(|x| &x.id == p || &x.name == p)
+      .map(|x| x.id.clone())
+      .ok_or_else(|| anyhow!("profile not found: {p}")),
+    None => store.active_profile_id.ok_or_else(|| anyhow!("no active profile")),
+  }
+}
+
+fn print_json<T: serde::Serialize>(v: &T) -> Result<()> {
+  println!("{}", serde_json::to_string_pretty(v)?);
+  Ok(())
+}
+
+// Writes streamed reply text to stdout; tool steps are only reported on stderr, so stdout
+// carries just the answer.
+#[derive(Default)]
+struct StdoutSink {
+  reply: Mutex<ReplyText>,
+}
+
+impl EventSink for StdoutSink {
+  fn emit_json(&self, event: &str, ev: Value) {
+    if event != "chat_stream" {
+      return;
+    }
+    let delta = ev["delta"].as_str().unwrap_or_default();
+    if ev["new_role"].as_str() == Some("tool") {
+      eprintln!("[tool] {}", delta.lines().next().unwrap_or_default());
+      return;
+    }
+    if let Some(err) = ev["error"].as_str() {
+      eprintln!("error: {err}");
+      return;
+    }
+    if !ev["pause"].is_null() {
+      eprintln!("{}", delta.trim());
+      return;
+    }
+
+    let Ok(mut reply) = self.reply.lock() else { return };
+    let (text, answered) = reply.push(delta, ev["done"].as_bool().unwrap_or(false));
+    let mut out = std::io::stdout();
+    let _ = out.write_all(text.as_bytes());
+    if answered {
+      let _ = out.write_all(b"\n");
+    }
+    let _ = out.flush();
+  }
+}
+
+fn cmd_send(st: &Storage, pid: &str, cli: &Cli) -> Result<ExitCode> {
+  let chat_id = cli.args.get(1).ok_or_else(|| anyhow!("send needs a chat id"))?;
+  let mut text = cli.args[2..].join(" ");
+  if text == "-" {
+    text.clear();
+    std::io::stdin().read_to_string(&mut text).context("failed to read stdin")?;
+  }
+  if text.trim().is_empty() {
+    return Err(anyhow!("send needs a message"));
+  }
+
+  let sink = StdoutSink::default();
+  let (thread, end) = chats::send(st, &sink, pid, chat_id, text.trim(), !cli.no_stream)?;
+  match end {
+    LoopEnd::Reply(_) => {
+      if cli.no_stream {
+        if let Some(m) = thread.messages.iter().rev().find(|m| matches!(m.role, chats::ChatRole::Assistant)) {
+          println!("{}", m.text);
+        }
+      }
+      Ok(ExitCode::SUCCESS)
+    }
+    LoopEnd::Paused(p) => {
+      eprintln!("{}", tauri_appopenclaw_macapp_lib::trace::pause_text(&p));
+      Ok(ExitCode::from(3))
+    }
+    LoopEnd::Failed(e) => Err(anyhow!(e)),
+  }
+}
+
+fn cmd_export(st: &Storage, pid: &str, cli: &Cli) -> Result<()> {
+  let chat_id = cli.args.get(1).ok_or_else(|| anyhow!("export needs a chat id"))?;
+  let idx = chats::load_index(st, pid)?;
+  let chat = idx.chats.iter().find(|c| &c.id == chat_id).ok_or_else(|| anyhow!("chat not found"))?;
+  let thread = chats::load_thread(st, pid, chat_id)?;
+  if cli.json {
+    print_json(&thread)
+  } else {
+    print!("{}", chats::export_markdown(chat, &thread));
+    Ok(())
+  }
+}
+
+fn cmd_settings(st: &Storage, pid: &str, cli: &Cli) -> Result<()> {
+  let current = serde_json::to_value(settings::load_settings(st, pid)?)?;
+  match cli.args.get(1).map(|s| s.as_str()) {
+    None => print_json(&current),
+    Some("get") => {
+      let key = cli.args.get(2).ok_or_else(|| anyhow!("settings get needs a key"))?;
+      let v = current.get(key).ok_or_else(|| anyhow!("unknown setting: {key}"))?;
+      match v {
+        Value::String(s) => println!("{s}"),
+        other => println!("{}", serde_json::to_string_pretty(other)?),
+      }
+      Ok(())
+    }
+    Some("set") => {
+      let key = cli.args.get(2).ok_or_else(|| anyhow!("settings set needs a key"))?;
+      let raw = cli.args.get(3).ok_or_else(|| anyhow!("settings set needs a value"))?;
+      if key == "version" || current.get(key).is_none() {
+        return Err(anyhow!("unknown setting: {key}"));
+      }
+      // `8` or `true` parse as JSON; fall back to the raw string for string settings.
+      let parsed = serde_json::from_str::<Value>(raw).ok();
+      let candidates: Vec<Value> = parsed.into_iter().chain([Value::String(raw.clone())]).collect();
+
+      // Validate against the current settings first, then re-apply under the settings lock.
+      let with = |s: &settings::ProfileSettings| -> Result<settings::ProfileSettings> {
+        let base = serde_json::to_value(s)?;
+        let next = candidates
+          .iter()
+          .find_map(|value| {
+            let mut v = base.clone();
+            v[key.as_str()] = value.clone();
+            serde_json::from_value::<settings::ProfileSettings>(v).ok()
+          })
+          .ok_or_else(|| anyhow!("invalid value for {key}: {raw}"))?;
+        if let Some(l) = &next.agent_limits {
+          l.validate()?;
+        }
+        Ok(next)
+      };
+      with(&settings::load_settings(st, pid)?)?;
+      let saved = settings::update_settings(st, pid, |s| {
+        if let Ok(next) = with(s) {
+          *s = next;
+        }
+      })?;
+      print_json(&saved)
+    }
+    Some(other) => Err(anyhow!("unknown settings command: {other}")),
+  }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+  let st = storage(&cli)?;
+  let cmd = cli.args.first().map(|s| s.as_str()).ok_or_else(|| anyhow!("help"))?;
+
+  if cmd == "profiles" {
+    let store = profiles::ensure_default_profile(&st)?;
+    for p in &store.profiles {
+      let mark = if store.active_profile_id.as_deref() == Some(p.id.as_str()) { "*" } else { " " };
+      println!("{mark} {}\t{}", p.id, p.name);
+    }
+    return Ok(ExitCode::SUCCESS);
+  }
+
+  let pid = profile_id(&st, &cli)?;
+  match cmd {
+    "chats" => match cli.args.get(1).map(|s| s.as_str()) {
+      None => {
+        for c in chats::load_index(&st, &pid)?.chats {
+          println!("{}\t{}", c.id, c.title);
+        }
+      }
+      Some("create") => {
+        let title = Some(cli.args[2..].join(" ")).filter(|t| !t.trim().is_empty());
+        println!("{}", chats::create(&st, &pid, title)?.id);
+      }
+      Some(other) => return Err(anyhow!("unknown chats command: {other}")),
+    },
+    "send" => return cmd_send(&st, &pid, &cli),
+    "export" => cmd_export(&st, &pid, &cli)?,
+    "gateway" => {
+      let action = cli.args.get(1).map(|s| s.as_str()).unwrap_or("status");
+      if !["status", "start", "stop", "restart"].contains(&action) {
+        return Err(anyhow!("unknown gateway command: {action}"));
+      }
+      let out = gateway::gateway_cmd(&st, &pid, action, None, None)?;
+      if cli.json {
+        print_json(&out)?;
+      } else {
+        print!("{}", out.stdout);
+        eprint!("{}", out.stderr);
+      }
+      return Ok(if out.exit_code == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE });
+    }
+    "settings" => cmd_settings(&st, &pid, &cli)?,
+    other => return Err(anyhow!("unknown command: {other}")),
+  }
+  Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+  let cli = match parse_args(std::env::args().skip(1).collect()) {
+    Ok(c) if !c.args.is_empty() => c,
+    _ => {
+      eprintln!("{USAGE}");
+      return ExitCode::from(2);
+    }
+  };
+
+  match run(cli) {
+    Ok(code) => code,
+    Err(e) => {
+      eprintln!("error: {e:#}");
+      ExitCode::FAILURE
+    }
+  }
+}
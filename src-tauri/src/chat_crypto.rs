@@ -0,0 +1,473 @@
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  path::{Path, PathBuf},
+  sync::Mutex,
+};
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::core::{new_id, write_atomic, DirLock, Storage};
+use crate::crypto::{self, DerivedKey, Sealed};
+
+// Opt-in encryption at rest for a profile's chat history (chats.json and the chat_{id}.json
+// threads). profiles/{id}/chats_key.json marks the profile as encrypted and holds what gets its key
+// back: a check value sealed with the key, and where the passphrase comes from (typed by the user,
+// or a random one kept in the OS keychain). The key stays in memory from unlock until lock.
+// Encrypted files keep their names and hold the sealed JSON, tagged with the key's id.
+
+const KEY_FILE: &str = "chats_key.json";
+// AEAD context; the file name is appended so files can't be swapped for one another.
+const CONTEXT: &str = "openclaw-desktop chat history v1";
+// Sealed files are written compact, so they start with this; plain ones are pretty-printed.
+const SEALED_PREFIX: &str = "{\"encrypted\":";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+  Keychain,
+  Passphrase,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct KeyFile {
+  version: u32,
+  key_id: String,
+  source: KeySource,
+  // key_id sealed with the key; also carries the KDF parameters.
+  check: Sealed,
+  // The next key while a rotation is under way. Files may be sealed with either until the
+  // rotation finishes and makes it the key.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pending: Option<Box<KeyFile>>,
+}
+
+impl KeyFile {
+  // The key, then the pending one.
+  fn all(&self) -> impl Iterator<Item = &KeyFile> {
+    std::iter::once(self).chain(self.pending.as_deref())
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+  // key_id of the key it was sealed with.
+  encrypted: String,
+  sealed: Sealed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EncryptionStatus {
+  pub enabled: bool,
+  pub source: Option<KeySource>,
+  pub unlocked: bool,
+  // A key rotation was interrupted; rotating again finishes it.
+  pub rotating: bool,
+  // Cleanup that failed without affecting the result, e.g. a retired key left in the keychain.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub warnings: Vec<String>,
+}
+
+#[derive(Clone)]
+struct Unlocked {
+  key_id: String,
+  key: DerivedKey,
+}
+
+// Unlocked keys by profile data dir: the key, and the pending one during a rotation.
+static KEYS: Lazy<Mutex<HashMap<PathBuf, Vec<Unlocked>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// Keychain-backed profiles the user locked; they aren't unlocked again on their own.
+static LOCKED: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn read_key_file(dir: &Path) -> Result<Option<KeyFile>> {
+  let p = dir.join(KEY_FILE);
+  if !p.exists() {
+    return Ok(None);
+  }
+  let raw = fs::read_to_string(&p).context("failed to read chats_key.json")?;
+  Ok(Some(serde_json::from_str(&raw).context("failed to parse chats_key.json")?))
+}
+
+fn write_key_file(dir: &Path, kf: &KeyFile) -> Result<()> {
+  write_atomic(&dir.join(KEY_FILE), serde_json::to_string_pretty(kf)?).context("failed to write chats_key.json")
+}
+
+// Profile dirs are profiles/{id}.
+fn profile_id_of(dir: &Path) -> String {
+  dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn keychain_entry(profile_id: &str, key_id: &str) -> Result<keyring::Entry> {
+  crate::secrets::keychain_entry(&crate::secrets::service(profile_id), &format!("chat-history-key-{key_id}"))
+}
+
+fn keychain_passphrase(profile_id: &str, key_id: &str) -> Result<String> {
+  match keychain_entry(profile_id, key_id)?.get_password() {
+    Ok(p) => Ok(p),
+    Err(keyring::Error::NoEntry) => Err(anyhow!("the chat history key is missing from the keychain")),
+    Err(e) => Err(anyhow!(e)),
+  }
+}
+
+// Deletes the keychain copy of key `kf` alone (not its pending key).
+fn forget_keychain(profile_id: &str, kf: &KeyFile) -> Result<()> {
+  if kf.source != KeySource::Keychain {
+    return Ok(());
+  }
+  match keychain_entry(profile_id, &kf.key_id)?.delete_credential() {
+    Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+    Err(e) => Err(anyhow!(e)),
+  }
+}
+
+fn open_key(dir: &Path, kf: &KeyFile, passphrase: Option<&str>) -> Result<Unlocked> {
+  let pass = match kf.source {
+    KeySource::Passphrase => passphrase.ok_or_else(|| anyhow!("passphrase required"))?.to_string(),
+    KeySource::Keychain => keychain_passphrase(&profile_id_of(dir), &kf.key_id)?,
+  };
+  let key = crypto::derive_key(&pass, &kf.check.kdf)?;
+  if crypto::open_with(&key, CONTEXT, &kf.check)? != kf.key_id.as_bytes() {
+    return Err(anyhow!("corrupt chats_key.json"));
+  }
+  Ok(Unlocked { key_id: kf.key_id.clone(), key })
+}
+
+// A fresh key. A keychain one gets a random passphrase, stored before anything is sealed with it.
+fn new_key(dir: &Path, source: KeySource, passphrase: Option<&str>) -> Result<(KeyFile, Unlocked)> {
+  let key_id = new_id("k");
+  let pass = match source {
+    KeySource::Passphrase => passphrase.filter(|p| !p.is_empty()).ok_or_else(|| anyhow!("passphrase required"))?.to_string(),
+    KeySource::Keychain => {
+      let p = B64.encode(crypto::random_bytes::<32>()?);
+      keychain_entry(&profile_id_of(dir), &key_id)?.set_password(&p).map_err(|e| anyhow!(e))?;
+      p
+    }
+  };
+  let key = crypto::derive_key(&pass, &crypto::new_kdf()?)?;
+  let check = crypto::seal_with(&key, CONTEXT, key_id.as_bytes())?;
+  Ok((KeyFile { version: 1, key_id: key_id.clone(), source, check, pending: None }, Unlocked { key_id, key }))
+}
+
+fn cached(dir: &Path, key_id: &str) -> Result<Option<Unlocked>> {
+  let keys = KEYS.lock().map_err(|_| anyhow!("chat key lock poisoned"))?;
+  Ok(keys.get(dir).and_then(|ks| ks.iter().find(|u| u.key_id == key_id).cloned()))
+}
+
+fn remember(dir: &Path, u: &Unlocked) -> Result<()> {
+  let mut keys = KEYS.lock().map_err(|_| anyhow!("chat key lock poisoned"))?;
+  let ks = keys.entry(dir.to_path_buf()).or_default();
+  ks.retain(|k| k.key_id != u.key_id);
+  ks.push(u.clone());
+  LOCKED.lock().map_err(|_| anyhow!("chat key lock poisoned"))?.remove(dir);
+  Ok(())
+}
+
+// Drops keys that `kf` no longer names.
+fn retain(dir: &Path, kf: &KeyFile) -> Result<()> {
+  if let Some(ks) = KEYS.lock().map_err(|_| anyhow!("chat key lock poisoned"))?.get_mut(dir) {
+    ks.retain(|u| kf.all().any(|k| k.key_id == u.key_id));
+  }
+  Ok(())
+}
+
+fn forget(dir: &Path) -> Result<()> {
+  KEYS.lock().map_err(|_| anyhow!("chat key lock poisoned"))?.remove(dir);
+  Ok(())
+}
+
+// Key `kf` of `dir`: from memory or, for a keychain key the user hasn't locked, the keychain.
+fn unlocked(dir: &Path, kf: &KeyFile) -> Result<Unlocked> {
+  if let Some(u) = cached(dir, &kf.key_id)? {
+    return Ok(u);
+  }
+  let locked = LOCKED.lock().map_err(|_| anyhow!("chat key lock poisoned"))?.contains(dir);
+  if kf.source == KeySource::Keychain && !locked {
+    let u = open_key(dir, kf, None)?;
+    remember(dir, &u)?;
+    return Ok(u);
+  }
+  Err(anyhow!("chat history is locked; unlock the profile to use its chats"))
+}
+
+// The key new writes in `dir` are sealed with, or None when its chats aren't encrypted.
+fn key_for(dir: &Path) -> Result<Option<Unlocked>> {
+  match read_key_file(dir)? {
+    Some(kf) => unlocked(dir, &kf).map(Some),
+    None => Ok(None),
+  }
+}
+
+// The key chat file `name` was sealed with: the key or, mid-rotation, the pending one.
+fn opener(dir: &Path, kf: Option<&KeyFile>, name: &str, key_id: &str) -> Result<Unlocked> {
+  let kf = kf.ok_or_else(|| anyhow!("{name} is encrypted, but the profile has no chat key"))?;
+  let k = kf.all().find(|k| k.key_id == key_id).ok_or_else(|| anyhow!("{name} was encrypted with another key"))?;
+  unlocked(dir, k)
+}
+
+fn as_sealed(raw: &str) -> Option<EncryptedFile> {
+  if !raw.starts_with(SEALED_PREFIX) {
+    return None;
+  }
+  serde_json::from_str(raw).ok()
+}
+
+fn open_file(u: &Unlocked, name: &str, f: &EncryptedFile) -> Result<String> {
+  let plain = crypto::open_with(&u.key, &format!("{CONTEXT}:{name}"), &f.sealed).with_context(|| format!("failed to decrypt {name}"))?;
+  String::from_utf8(plain).with_context(|| format!("corrupt {name}"))
+}
+
+fn seal_file(u: &Unlocked, name: &str, plain: &str) -> Result<String> {
+  let sealed = crypto::seal_with(&u.key, &format!("{CONTEXT}:{name}"), plain.as_bytes())?;
+  Ok(serde_json::to_string(&EncryptedFile { encrypted: u.key_id.clone(), sealed })?)
+}
+
+// The JSON of chat file `name` in `dir` read as `raw`: as is, or opened when it is sealed.
+pub(crate) fn decode(dir: &Path, name: &str, raw: String) -> Result<String> {
+  let Some(f) = as_sealed(&raw) else { return Ok(raw) };
+  let u = opener(dir, read_key_file(dir)?.as_ref(), name, &f.encrypted)?;
+  open_file(&u, name, &f)
+}
+
+// What to write for chat file `name` in `dir`: `plain`, sealed when the profile is encrypted.
+pub(crate) fn encode(dir: &Path, name: &str, plain: String) -> Result<String> {
+  match key_for(dir)? {
+    Some(u) => seal_file(&u, name, &plain),
+    None => Ok(plain),
+  }
+}
+
+fn chat_files(dir: &Path) -> Result<Vec<String>> {
+  let mut names = vec![];
+  for e in fs::read_dir(dir)?.flatten() {
+    let name = e.file_name().to_string_lossy().to_string();
+    if e.file_type()?.is_file() && (name == "chats.json" || (name.starts_with("chat_") && name.ends_with(".json"))) {
+      names.push(name);
+    }
+  }
+  names.sort();
+  Ok(names)
+}
+
+// Re-encodes every chat file of `dir` (opened with the keys of `kf`) to key `to`, or to plain JSON
+// when None. Files already sealed with `to` are left alone. All files are read before any is
+// written, so one that can't be opened changes nothing. Callers hold the dir lock.
+fn rewrite(dir: &Path, kf: Option<&KeyFile>, to: Option<&Unlocked>) -> Result<()> {
+  let mut out = vec![];
+  for name in chat_files(dir)? {
+    let path = dir.join(&name);
+    let raw = fs::read_to_string(&path).with_context(|| format!("failed to read {name}"))?;
+    let sealed = as_sealed(&raw);
+    if let (Some(f), Some(u)) = (&sealed, to) {
+      if f.encrypted == u.key_id {
+        continue;
+      }
+    }
+    let plain = match sealed {
+      Some(f) => open_file(&opener(dir, kf, &name, &f.encrypted)?, &name, &f)?,
+      None => raw,
+    };
+    let data = match to {
+      Some(u) => seal_file(u, &name, &plain)?,
+      None => plain,
+    };
+    out.push((path, data));
+  }
+  for (path, data) in out {
+    write_atomic(&path, data).with_context(|| format!("failed to write {}", path.display()))?;
+  }
+  Ok(())
+}
+
+pub fn status(st: &Storage, profile_id: &str) -> Result<EncryptionStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  let kf = read_key_file(&dir)?;
+  let unlocked = match &kf {
+    Some(kf) => cached(&dir, &kf.key_id)?.is_some(),
+    None => false,
+  };
+  Ok(EncryptionStatus {
+    enabled: kf.is_some(),
+    source: kf.as_ref().map(|k| k.source),
+    unlocked,
+    rotating: kf.is_some_and(|k| k.pending.is_some()),
+    warnings: vec![],
+  })
+}
+
+// Encrypts the profile's chat history with a new key and keeps it unlocked.
+pub fn enable(st: &Storage, profile_id: &str, source: KeySource, passphrase: Option<&str>) -> Result<EncryptionStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  {
+    let _lock = DirLock::acquire(&dir)?;
+    if read_key_file(&dir)?.is_some() {
+      return Err(anyhow!("chat history is already encrypted"));
+    }
+    let (kf, u) = new_key(&dir, source, passphrase)?;
+    // Written first: until every file is sealed, the plain ones still read fine.
+    write_key_file(&dir, &kf)?;
+    remember(&dir, &u)?;
+    rewrite(&dir, None, Some(&u))?;
+  }
+  status(st, profile_id)
+}
+
+// Decrypts the chat history back to plain JSON and drops the key. Needs the profile unlocked.
+pub fn disable(st: &Storage, profile_id: &str) -> Result<EncryptionStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  let mut warnings = vec![];
+  {
+    let _lock = DirLock::acquire(&dir)?;
+    let kf = read_key_file(&dir)?.ok_or_else(|| anyhow!("chat history is not encrypted"))?;
+    rewrite(&dir, Some(&kf), None)?;
+    fs::remove_file(dir.join(KEY_FILE)).context("failed to remove chats_key.json")?;
+    forget(&dir)?;
+    for k in kf.all() {
+      if let Err(e) = forget_keychain(profile_id, k) {
+        warnings.push(format!("chat history key left in the keychain: {e:#}"));
+      }
+    }
+  }
+  Ok(EncryptionStatus { warnings, ..status(st, profile_id)? })
+}
+
+// Reseals every file with the pending key of `kf`, then makes it the key and retires the old
+// one, which no file uses any more. Callers hold the dir lock.
+fn finish_rotation(dir: &Path, profile_id: &str, kf: KeyFile, warnings: &mut Vec<String>) -> Result<KeyFile> {
+  let mut next = *kf.pending.clone().ok_or_else(|| anyhow!("no key rotation under way"))?;
+  let u = unlocked(dir, &next)?;
+  rewrite(dir, Some(&kf), Some(&u))?;
+  next.pending = None;
+  write_key_file(dir, &next)?;
+  retain(dir, &next)?;
+  if let Err(e) = forget_keychain(profile_id, &kf) {
+    warnings.push(format!("old chat history key left in the keychain: {e:#}"));
+  }
+  Ok(next)
+}
+
+// Re-encrypts the chat history under a new key from `source`. Needs the profile unlocked. The new
+// key is saved as pending before any file is resealed, so an interrupted rotation leaves every
+// file readable; rotating again finishes it before starting the new one.
+pub fn rotate(st: &Storage, profile_id: &str, source: KeySource, passphrase: Option<&str>) -> Result<EncryptionStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  let mut warnings = vec![];
+  {
+    let _lock = DirLock::acquire(&dir)?;
+    let mut kf = read_key_file(&dir)?.ok_or_else(|| anyhow!("chat history is not encrypted"))?;
+    if kf.pending.is_some() {
+      kf = finish_rotation(&dir, profile_id, kf, &mut warnings)?;
+    }
+    unlocked(&dir, &kf)?;
+    let (next, u) = new_key(&dir, source, passphrase)?;
+    kf.pending = Some(Box::new(next));
+    if let Err(e) = write_key_file(&dir, &kf) {
+      // Nothing is sealed with the new key yet.
+      let _ = kf.pending.as_deref().map(|next| forget_keychain(profile_id, next));
+      return Err(e);
+    }
+    remember(&dir, &u)?;
+    finish_rotation(&dir, profile_id, kf, &mut warnings)?;
+  }
+  Ok(EncryptionStatus { warnings, ..status(st, profile_id)? })
+}
+
+// Unlocks the key, and the pending one of an interrupted rotation, that `passphrase` (or the
+// keychain) opens. `passphrase` is only needed for a passphrase key. Nothing opening is an error.
+pub fn unlock(st: &Storage, profile_id: &str, passphrase: Option<&str>) -> Result<EncryptionStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  let kf = read_key_file(&dir)?.ok_or_else(|| anyhow!("chat history is not encrypted"))?;
+  let mut err = None;
+  let mut opened = false;
+  for k in kf.all() {
+    match open_key(&dir, k, passphrase) {
+      Ok(u) => {
+        remember(&dir, &u)?;
+        opened = true;
+      }
+      Err(e) => {
+        err.get_or_insert(e);
+      }
+    }
+  }
+  match err {
+    Some(e) if !opened => Err(e),
+    _ => status(st, profile_id),
+  }
+}
+
+// Forgets the keys until the next unlock.
+pub fn lock(st: &Storage, profile_id: &str) -> Result<EncryptionStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  forget(&dir)?;
+  if read_key_file(&dir)?.is_some() {
+    LOCKED.lock().map_err(|_| anyhow!("chat key lock poisoned"))?.insert(dir);
+  }
+  status(st, profile_id)
+}
+
+// Encrypts the chat history of the profile in `to` like the one in `from`, so chats copied over
+// stay encrypted: a keychain key is made fresh, a passphrase key is shared (same passphrase).
+// Needs `from` unlocked; does nothing when its chats aren't encrypted.
+pub(crate) fn copy_key(from: &Path, to: &Path) -> Result<()> {
+  let Some(kf) = read_key_file(from)? else { return Ok(()) };
+  let u = unlocked(from, &kf)?;
+  let (kf, u) = match kf.source {
+    KeySource::Keychain => new_key(to, KeySource::Keychain, None)?,
+    KeySource::Passphrase => (KeyFile { pending: None, ..kf }, u),
+  };
+  write_key_file(to, &kf)?;
+  remember(to, &u)
+}
+
+// Copies the keychain copies of the chat keys of the profile in `dir` from profile `from`'s
+// service to `to`'s, for a profile that takes `dir` over under a new id.
+pub(crate) fn copy_keychain_keys(dir: &Path, from: &str, to: &str) -> Result<()> {
+  let Some(kf) = read_key_file(dir)? else { return Ok(()) };
+  let copied = kf.all().filter(|k| k.source == KeySource::Keychain).try_for_each(|k| {
+    let p = keychain_passphrase(from, &k.key_id)?;
+    keychain_entry(to, &k.key_id)?.set_password(&p).map_err(|e| anyhow!(e))
+  });
+  copied
+}
+
+// Removes the keychain copies of the chat keys of a profile whose data is in `dir` (which may be
+// in the trash). The rest goes with the dir.
+pub(crate) fn remove_key(dir: &Path, profile_id: &str) -> Result<()> {
+  let Some(kf) = read_key_file(dir)? else { return Ok(()) };
+  let removed = kf.all().try_for_each(|k| forget_keychain(profile_id, k));
+  removed
+}
+
+#[tauri::command]
+pub fn chats_encryption_status(storage: State<'_, Storage>, profile_id: String) -> Result<EncryptionStatus, String> {
+  status(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn chats_encryption_enable(storage: State<'_, Storage>, profile_id: String, source: KeySource, passphrase: Option<String>) -> Result<EncryptionStatus, String> {
+  enable(&storage, &profile_id, source, passphrase.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn chats_encryption_disable(storage: State<'_, Storage>, profile_id: String) -> Result<EncryptionStatus, String> {
+  disable(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn chats_encryption_rotate(storage: State<'_, Storage>, profile_id: String, source: KeySource, passphrase: Option<String>) -> Result<EncryptionStatus, String> {
+  rotate(&storage, &profile_id, source, passphrase.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn chats_encryption_unlock(storage: State<'_, Storage>, profile_id: String, passphrase: Option<String>) -> Result<EncryptionStatus, String> {
+  unlock(&storage, &profile_id, passphrase.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn chats_encryption_lock(storage: State<'_, Storage>, profile_id: String) -> Result<EncryptionStatus, String> {
+  lock(&storage, &profile_id).map_err(|e| e.to_string())
+}
@@ -12,4 +12,48 @@ pub struct ChatStreamEvent {
   // If present, indicates this event is for a newly created message.
   pub new_role: Option<String>,
   pub new_created_at_ms: Option<i64>,
+
+  // Set when the tool loop paused on a limit; the UI can offer `chat_continue`.
+  pub pause: Option<crate::trace::LoopPause>,
+}
+
+// Progress from a chat backend that can't stream the reply itself (OpenClaw agent runs report
+// what they're doing on stderr). Emitted as `chat_progress`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatProgressEvent {
+  pub profile_id: String,
+  pub chat_id: String,
+  pub message_id: String,
+  pub line: String,
+}
+
+// Answer text for clients that only want the reply (CLI stdout, the local API). Each model step
+// arrives as deltas ending in `done`; steps that turn out to be tool-call JSON are held back.
+#[derive(Default)]
+pub struct ReplyText {
+  step: String,
+  sent: usize,
+}
+
+impl ReplyText {
+  // Feeds one assistant delta. Returns the newly visible text, and whether a step that was
+  // part of the answer (not a tool call) just ended.
+  pub fn push(&mut self, delta: &str, done: bool) -> (String, bool) {
+    self.step.push_str(delta);
+    let held = self.step.trim_start().starts_with('{') || self.step.trim().is_empty();
+    if !done {
+      if held {
+        return (String::new(), false);
+      }
+      let out = self.step[self.sent..].to_string();
+      self.sent = self.step.len();
+      return (out, false);
+    }
+
+    let answer = crate::agent::parse_tool_call(&self.step).is_none();
+    let out = if answer { self.step[self.sent..].to_string() } else { String::new() };
+    self.step.clear();
+    self.sent = 0;
+    (out, answer)
+  }
 }
@@ -1,45 +1,38 @@
 use std::{
-  collections::{HashMap, HashSet},
+  collections::HashMap,
   fs,
-  path::PathBuf,
-  sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex},
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
   thread,
-  time::{Duration, Instant},
+  time::Duration,
 };
 
 use once_cell::sync::Lazy;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, State};
 
-use crate::ollama::{OllamaChatReq, OllamaMessage, OllamaRole};
-use crate::tools::ToolCall;
+use crate::core::{ensure_unique_ids, new_id, try_lock_file, write_atomic, DirLock, EventSink, Storage};
+use crate::chat_stream::ChatStreamEvent;
+use crate::openclaw_agents::AgentCli;
+use crate::openclaw_exec::RunOpts;
+use crate::settings::ChatBackend;
 
-fn now_ms() -> i64 {
+
+pub(crate) fn now_ms() -> i64 {
   let dur = std::time::SystemTime::now()
     .duration_since(std::time::UNIX_EPOCH)
     .unwrap_or_default();
   dur.as_millis() as i64
 }
 
-fn openclaw_path(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  crate::settings::resolve_openclaw_bin(app, profile_id)
-}
-
-fn profile_dir(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  let base = app.path().app_data_dir().context("app_data_dir not available")?;
-  let dir = base.join("profiles").join(profile_id);
-  fs::create_dir_all(&dir).context("failed to create profile data dir")?;
-  Ok(dir)
-}
-
-fn chats_path(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  Ok(profile_dir(app, profile_id)?.join("chats.json"))
+fn openclaw_path(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  crate::settings::resolve_openclaw_bin(st, profile_id)
 }
 
-fn chat_thread_path(app: &AppHandle, profile_id: &str, chat_id: &str) -> Result<PathBuf> {
-  Ok(profile_dir(app, profile_id)?.join(format!("chat_{chat_id}.json")))
+fn chat_thread_path(st: &Storage, profile_id: &str, chat_id: &str) -> Result<PathBuf> {
+  Ok(st.profile_dir(profile_id)?.join(format!("chat_{chat_id}.json")))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +45,12 @@ pub struct Chat {
   pub agent_id: Option<String>,
   pub thinking: Option<String>,
   pub worker: Option<String>,
+  // Prompt preset (see prompts.rs) overriding the profile's system prompt template.
+  #[serde(default)]
+  pub prompt_preset_id: Option<String>,
+  // Overrides the profile's chat_backend.
+  #[serde(default)]
+  pub backend: Option<ChatBackend>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +79,12 @@ pub struct ChatMessage {
   pub role: ChatRole,
   pub text: String,
   pub created_at_ms: i64,
+  // Auto-do intent decision for user messages (why tools were or weren't forced).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub intent: Option<crate::intent::IntentDecision>,
+  // Set on an assistant message when the tool loop hit a limit; cleared by `chat_continue`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub pause: Option<crate::trace::LoopPause>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,47 +100,116 @@ impl ChatThread {
   }
 }
 
-fn load_index(app: &AppHandle, profile_id: &str) -> Result<ChatIndex> {
-  let path = chats_path(app, profile_id)?;
+// Path-based store functions take the profile data dir (profiles/{id}); the Storage
+// wrappers below resolve it from the storage root.
+
+fn read_index_in(dir: &Path) -> Result<ChatIndex> {
+  let path = dir.join("chats.json");
   if !path.exists() {
     return Ok(ChatIndex::default());
   }
   let raw = fs::read_to_string(&path).context("failed to read chats.json")?;
-  Ok(serde_json::from_str(&raw).context("failed to parse chats.json")?)
+  let raw = crate::chat_crypto::decode(dir, "chats.json", raw)?;
+  serde_json::from_str(&raw).context("failed to parse chats.json")
+}
+
+pub fn load_index_in(dir: &Path) -> Result<ChatIndex> {
+  let idx = read_index_in(dir)?;
+  ensure_unique_ids("chat", idx.chats.iter().map(|c| c.id.as_str())).context("invalid chats.json")?;
+  Ok(idx)
+}
+
+// One-time fix for ids made before they were collision-free (see core::new_id): a chat whose id
+// repeats an earlier one gets a new id, a copy of the thread they shared and a new session, and
+// repeated message ids in a thread are replaced. Unique ids are left as they are. Returns what
+// changed.
+pub(crate) fn repair_ids_in(dir: &Path) -> Result<Vec<String>> {
+  let _lock = DirLock::acquire(dir)?;
+  let mut notes = vec![];
+  let mut idx = read_index_in(dir)?;
+  let mut seen = std::collections::HashSet::new();
+  let mut changed = false;
+  for chat in idx.chats.iter_mut() {
+    if seen.insert(chat.id.clone()) {
+      continue;
+    }
+    let id = new_id("c");
+    let mut thread = load_thread_in(dir, &chat.id)?;
+    thread.chat_id = id.clone();
+    save_thread_in(dir, &thread)?;
+    notes.push(format!("chat \"{}\": duplicate id {} replaced by {id}", chat.title, chat.id));
+    chat.id = id;
+    chat.session_id = format!("desktop-{}", chat.id);
+    changed = true;
+  }
+  if changed {
+    save_index_in(dir, &idx)?;
+  }
+  for chat in &idx.chats {
+    let mut thread = load_thread_in(dir, &chat.id)?;
+    let mut seen = std::collections::HashSet::new();
+    let mut fixed = 0;
+    for m in thread.messages.iter_mut() {
+      if !seen.insert(m.id.clone()) {
+        m.id = new_id("m");
+        fixed += 1;
+      }
+    }
+    if fixed > 0 {
+      save_thread_in(dir, &thread)?;
+      notes.push(format!("chat \"{}\": replaced {fixed} duplicate message id(s)", chat.title));
+    }
+  }
+  Ok(notes)
 }
 
-fn save_index(app: &AppHandle, profile_id: &str, idx: &ChatIndex) -> Result<()> {
-  let path = chats_path(app, profile_id)?;
+pub fn save_index_in(dir: &Path, idx: &ChatIndex) -> Result<()> {
+  let path = dir.join("chats.json");
   let raw = serde_json::to_string_pretty(idx).context("failed to serialize chats index")?;
-  fs::write(&path, raw).context("failed to write chats.json")?;
+  let raw = crate::chat_crypto::encode(dir, "chats.json", raw)?;
+  write_atomic(&path, raw).context("failed to write chats.json")?;
   Ok(())
 }
 
-fn load_thread(app: &AppHandle, profile_id: &str, chat_id: &str) -> Result<ChatThread> {
-  let path = chat_thread_path(app, profile_id, chat_id)?;
+pub fn load_index(st: &Storage, profile_id: &str) -> Result<ChatIndex> {
+  load_index_in(&st.profile_dir(profile_id)?)
+}
+
+fn save_index(st: &Storage, profile_id: &str, idx: &ChatIndex) -> Result<()> {
+  save_index_in(&st.profile_dir(profile_id)?, idx)
+}
+
+pub fn load_thread_in(dir: &Path, chat_id: &str) -> Result<ChatThread> {
+  let name = format!("chat_{chat_id}.json");
+  let path = dir.join(&name);
   if !path.exists() {
     return Ok(ChatThread::new(chat_id.to_string()));
   }
   let raw = fs::read_to_string(&path).context("failed to read chat thread")?;
-  Ok(serde_json::from_str(&raw).context("failed to parse chat thread")?)
+  let raw = crate::chat_crypto::decode(dir, &name, raw)?;
+  serde_json::from_str(&raw).context("failed to parse chat thread")
 }
 
-fn save_thread(app: &AppHandle, profile_id: &str, t: &ChatThread) -> Result<()> {
-  let path = chat_thread_path(app, profile_id, &t.chat_id)?;
+pub fn save_thread_in(dir: &Path, t: &ChatThread) -> Result<()> {
+  let name = format!("chat_{}.json", t.chat_id);
   let raw = serde_json::to_string_pretty(t).context("failed to serialize chat thread")?;
-  fs::write(&path, raw).context("failed to write chat thread")?;
+  let raw = crate::chat_crypto::encode(dir, &name, raw)?;
+  let path = dir.join(name);
+  write_atomic(&path, raw).context("failed to write chat thread")?;
   Ok(())
 }
 
-static ID_SEQ: Lazy<AtomicU64> = Lazy::new(|| AtomicU64::new(1));
+pub fn load_thread(st: &Storage, profile_id: &str, chat_id: &str) -> Result<ChatThread> {
+  load_thread_in(&st.profile_dir(profile_id)?, chat_id)
+}
 
-fn new_id(prefix: &str) -> String {
-  let ms = now_ms();
-  let seq = ID_SEQ.fetch_add(1, Ordering::Relaxed);
-  format!("{prefix}_{ms}_{seq}")
+fn save_thread(st: &Storage, profile_id: &str, t: &ChatThread) -> Result<()> {
+  save_thread_in(&st.profile_dir(profile_id)?, t)
 }
 
-static INFLIGHT: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+// Chats with a send in progress. Each entry holds an exclusive lock on
+// profiles/{id}/locks/chat_{chat_id}.lock so the CLI and the GUI can't run the same chat at once.
+static INFLIGHT: Lazy<Mutex<HashMap<String, fs::File>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 static WORKER_LOCKS: Lazy<Mutex<HashMap<String, Arc<Mutex<()>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
 fn worker_key(profile_id: &str, worker: &str) -> String {
@@ -152,36 +226,42 @@ fn inflight_key(profile_id: &str, chat_id: &str) -> String {
   format!("{profile_id}::{chat_id}")
 }
 
-#[tauri::command]
-pub fn chats_list(app: AppHandle, profile_id: String) -> Result<ChatIndex, String> {
-  load_index(&app, &profile_id).map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-pub fn chat_thread(app: AppHandle, profile_id: String, chat_id: String) -> Result<ChatThread, String> {
-  load_thread(&app, &profile_id, &chat_id).map_err(|e| e.to_string())
+// Removes a desktop chat's OpenClaw session. Attached sessions from other channels are left alone.
+fn drop_session(st: &Storage, profile_id: &str, session_id: &str) {
+  if session_id.starts_with(crate::openclaw_sessions::DESKTOP_PREFIX) {
+    let _ = crate::openclaw_sessions::remove(st, profile_id, session_id);
+  }
 }
 
-#[tauri::command]
-pub fn chat_reset(app: AppHandle, profile_id: String, chat_id: String) -> Result<ChatThread, String> {
-  // Clears thread messages and any inflight state.
-  let mut t = ChatThread::new(chat_id.clone());
-  save_thread(&app, &profile_id, &t).map_err(|e| e.to_string())?;
-
-  let key = inflight_key(&profile_id, &chat_id);
-  if let Ok(mut s) = INFLIGHT.lock() {
-    s.remove(&key);
+pub fn reset(st: &Storage, profile_id: &str, chat_id: &str) -> Result<ChatThread> {
+  // Clears thread messages and any inflight state, and moves the chat to a fresh OpenClaw session
+  // so the agent forgets the old conversation too.
+  let _lock = DirLock::acquire(&st.profile_dir(profile_id)?)?;
+  let mut idx = load_index(st, profile_id)?;
+  let old_session = idx.chats.iter_mut().find(|c| c.id == chat_id).map(|c| {
+    let old = std::mem::replace(&mut c.session_id, format!("desktop-{chat_id}-{}", now_ms()));
+    c.updated_at_ms = now_ms();
+    old
+  });
+  if old_session.is_some() {
+    save_index(st, profile_id, &idx)?;
+  }
+  let t = ChatThread::new(chat_id.to_string());
+  save_thread(st, profile_id, &t)?;
+  clear_inflight(profile_id, chat_id);
+  if let Some(old) = old_session {
+    drop_session(st, profile_id, &old);
   }
-
   Ok(t)
 }
 
-#[tauri::command]
-pub fn chats_create(app: AppHandle, profile_id: String, title: Option<String>) -> Result<Chat, String> {
-  let mut idx = load_index(&app, &profile_id).map_err(|e| e.to_string())?;
-
+// Adds a chat to a loaded index and writes it with an empty thread. Callers hold the dir lock.
+// `session_id` attaches an existing OpenClaw session instead of starting a new one; such chats
+// use the OpenClaw backend, since Ollama never saw the session's history.
+fn insert_chat_in(dir: &Path, idx: &mut ChatIndex, title: Option<String>, session_id: Option<String>) -> Result<Chat> {
   let id = new_id("c");
-  let session_id = format!("desktop-{}", &id);
+  let backend = session_id.is_some().then_some(ChatBackend::Openclaw);
+  let session_id = session_id.unwrap_or_else(|| format!("desktop-{}", &id));
   let t = title.unwrap_or_else(|| "New chat".to_string());
 
   let chat = Chat {
@@ -193,611 +273,500 @@ pub fn chats_create(app: AppHandle, profile_id: String, title: Option<String>) -
     agent_id: None,
     thinking: Some("low".to_string()),
     worker: Some("default".to_string()),
+    prompt_preset_id: None,
+    backend,
   };
 
   idx.chats.insert(0, chat.clone());
-  save_index(&app, &profile_id, &idx).map_err(|e| e.to_string())?;
+  save_index_in(dir, idx)?;
   // Create empty thread file
   let thread = ChatThread::new(id);
-  save_thread(&app, &profile_id, &thread).map_err(|e| e.to_string())?;
+  save_thread_in(dir, &thread)?;
 
   Ok(chat)
 }
 
-#[tauri::command]
-pub fn chats_rename(app: AppHandle, profile_id: String, chat_id: String, title: String) -> Result<ChatIndex, String> {
-  let mut idx = load_index(&app, &profile_id).map_err(|e| e.to_string())?;
-  let title = title.trim();
-  if title.is_empty() {
-    return Err("title required".to_string());
+// Writes `idx`'s chats into the profile at `dir` under fresh ids, each with its own new OpenClaw
+// session, replacing whatever chats the profile had. `thread_of` supplies a chat's thread by its
+// old id. Returns the old-to-new id map.
+pub(crate) fn copy_chats_in<F>(dir: &Path, idx: &mut ChatIndex, mut thread_of: F) -> Result<HashMap<String, String>>
+where
+  F: FnMut(&str) -> Result<Option<ChatThread>>,
+{
+  let _lock = DirLock::acquire(dir)?;
+  let mut ids = HashMap::new();
+  for chat in idx.chats.iter_mut() {
+    let id = new_id("c");
+    let mut thread = thread_of(&chat.id)?.unwrap_or_else(|| ChatThread::new(id.clone()));
+    thread.chat_id = id.clone();
+    save_thread_in(dir, &thread)?;
+    chat.session_id = format!("desktop-{id}");
+    ids.insert(std::mem::replace(&mut chat.id, id.clone()), id);
+  }
+  save_index_in(dir, idx)?;
+  Ok(ids)
+}
+
+pub fn create_chat_in(dir: &Path, title: Option<String>) -> Result<Chat> {
+  let _lock = DirLock::acquire(dir)?;
+  let mut idx = load_index_in(dir)?;
+  insert_chat_in(dir, &mut idx, title, None)
+}
+
+// Chat with this id, else the first one titled `name`; creates a chat titled `name` if neither exists.
+pub fn find_or_create(st: &Storage, profile_id: &str, name: &str) -> Result<Chat> {
+  let dir = st.profile_dir(profile_id)?;
+  let _lock = DirLock::acquire(&dir)?;
+  let mut idx = load_index_in(&dir)?;
+  let found = idx.chats.iter().find(|c| c.id == name).or_else(|| idx.chats.iter().find(|c| c.title == name));
+  match found {
+    Some(c) => Ok(c.clone()),
+    None => insert_chat_in(&dir, &mut idx, Some(name.to_string()), None),
   }
+}
+
+pub fn create(st: &Storage, profile_id: &str, title: Option<String>) -> Result<Chat> {
+  create_chat_in(&st.profile_dir(profile_id)?, title)
+}
+
+// New chat continuing an existing OpenClaw session (see openclaw_sessions::list). The desktop
+// thread starts empty; the agent keeps the session's history.
+pub fn attach_session(st: &Storage, profile_id: &str, session_id: &str, title: Option<String>) -> Result<Chat> {
+  let session_id = session_id.trim();
+  if !crate::openclaw_sessions::exists(st, profile_id, session_id)? {
+    return Err(anyhow!("OpenClaw session {session_id} not found"));
+  }
+  let dir = st.profile_dir(profile_id)?;
+  let _lock = DirLock::acquire(&dir)?;
+  let mut idx = load_index_in(&dir)?;
+  if let Some(c) = idx.chats.iter().find(|c| c.session_id == session_id) {
+    return Err(anyhow!("session {session_id} is already used by chat \"{}\"", c.title));
+  }
+  insert_chat_in(&dir, &mut idx, title.or_else(|| Some(format!("Session {session_id}"))), Some(session_id.to_string()))
+}
+
+// Loads the index, applies `f` to one chat, bumps updated_at_ms and saves.
+fn update_chat<F>(st: &Storage, profile_id: &str, chat_id: &str, f: F) -> Result<ChatIndex>
+where
+  F: FnOnce(&mut Chat),
+{
+  let _lock = DirLock::acquire(&st.profile_dir(profile_id)?)?;
+  let mut idx = load_index(st, profile_id)?;
   let c = idx
     .chats
     .iter_mut()
     .find(|c| c.id == chat_id)
-    .ok_or_else(|| "chat not found".to_string())?;
-  c.title = title.to_string();
+    .ok_or_else(|| anyhow!("chat not found"))?;
+  f(c);
   c.updated_at_ms = now_ms();
-  save_index(&app, &profile_id, &idx).map_err(|e| e.to_string())?;
+  save_index(st, profile_id, &idx)?;
   Ok(idx)
 }
 
-#[tauri::command]
-pub fn chats_update(app: AppHandle, profile_id: String, chat_id: String, thinking: Option<String>, agent_id: Option<String>, worker: Option<String>) -> Result<ChatIndex, String> {
-  let mut idx = load_index(&app, &profile_id).map_err(|e| e.to_string())?;
-  let c = idx
-    .chats
-    .iter_mut()
-    .find(|c| c.id == chat_id)
-    .ok_or_else(|| "chat not found".to_string())?;
-
-  c.thinking = thinking.and_then(|t| {
+fn non_empty(v: Option<String>) -> Option<String> {
+  v.and_then(|t| {
     let x = t.trim().to_string();
     if x.is_empty() { None } else { Some(x) }
-  });
+  })
+}
 
-  c.agent_id = agent_id.and_then(|a| {
-    let x = a.trim().to_string();
-    if x.is_empty() { None } else { Some(x) }
-  });
+pub fn rename(st: &Storage, profile_id: &str, chat_id: &str, title: &str) -> Result<ChatIndex> {
+  let title = title.trim();
+  if title.is_empty() {
+    return Err(anyhow!("title required"));
+  }
+  update_chat(st, profile_id, chat_id, |c| c.title = title.to_string())
+}
 
-  c.worker = worker.and_then(|w| {
-    let x = w.trim().to_string();
-    if x.is_empty() { None } else { Some(x) }
-  });
+pub fn update(st: &Storage, profile_id: &str, chat_id: &str, thinking: Option<String>, agent_id: Option<String>, worker: Option<String>) -> Result<ChatIndex> {
+  let agent_id = non_empty(agent_id);
+  if let Some(id) = &agent_id {
+    crate::openclaw_agents::validate_id(id)?;
+  }
+  update_chat(st, profile_id, chat_id, |c| {
+    c.thinking = non_empty(thinking);
+    c.agent_id = agent_id;
+    c.worker = non_empty(worker);
+  })
+}
+
+pub fn set_prompt_preset(st: &Storage, profile_id: &str, chat_id: &str, preset_id: Option<String>) -> Result<ChatIndex> {
+  let preset_id = non_empty(preset_id);
+  if let Some(pid) = &preset_id {
+    let lib = crate::prompts::load_library(st, profile_id)?;
+    if !lib.presets.iter().any(|p| &p.id == pid) {
+      return Err(anyhow!("preset not found"));
+    }
+  }
+  update_chat(st, profile_id, chat_id, |c| c.prompt_preset_id = preset_id)
+}
 
-  c.updated_at_ms = now_ms();
-  save_index(&app, &profile_id, &idx).map_err(|e| e.to_string())?;
+// Picks the OpenClaw agent that answers the chat; it must exist. None uses the desktop agent.
+pub fn set_agent(st: &Storage, profile_id: &str, chat_id: &str, agent_id: Option<String>) -> Result<ChatIndex> {
+  let agent_id = non_empty(agent_id);
+  if let Some(id) = &agent_id {
+    crate::openclaw_agents::validate_id(id)?;
+    if !crate::openclaw_agents::list(st, profile_id)?.iter().any(|a| &a.id == id) {
+      return Err(anyhow!("agent {id} not found"));
+    }
+  }
+  update_chat(st, profile_id, chat_id, |c| c.agent_id = agent_id)
+}
+
+// Unsets `agent_id` on chats using it (after the agent is deleted).
+pub fn clear_agent(st: &Storage, profile_id: &str, agent_id: &str) -> Result<ChatIndex> {
+  let _lock = DirLock::acquire(&st.profile_dir(profile_id)?)?;
+  let mut idx = load_index(st, profile_id)?;
+  for c in idx.chats.iter_mut().filter(|c| c.agent_id.as_deref() == Some(agent_id)) {
+    c.agent_id = None;
+    c.updated_at_ms = now_ms();
+  }
+  save_index(st, profile_id, &idx)?;
   Ok(idx)
 }
 
-#[tauri::command]
-pub fn chats_delete(app: AppHandle, profile_id: String, chat_id: String) -> Result<ChatIndex, String> {
-  let mut idx = load_index(&app, &profile_id).map_err(|e| e.to_string())?;
+pub fn set_backend(st: &Storage, profile_id: &str, chat_id: &str, backend: Option<ChatBackend>) -> Result<ChatIndex> {
+  update_chat(st, profile_id, chat_id, |c| c.backend = backend)
+}
+
+pub fn delete(st: &Storage, profile_id: &str, chat_id: &str) -> Result<ChatIndex> {
+  let _lock = DirLock::acquire(&st.profile_dir(profile_id)?)?;
+  let mut idx = load_index(st, profile_id)?;
+  let session = idx.chats.iter().find(|c| c.id == chat_id).map(|c| c.session_id.clone());
   idx.chats.retain(|c| c.id != chat_id);
-  save_index(&app, &profile_id, &idx).map_err(|e| e.to_string())?;
+  save_index(st, profile_id, &idx)?;
+  if let Some(s) = session {
+    drop_session(st, profile_id, &s);
+  }
 
   // best-effort delete thread file
-  if let Ok(p) = chat_thread_path(&app, &profile_id, &chat_id) {
+  if let Ok(p) = chat_thread_path(st, profile_id, chat_id) {
     let _ = fs::remove_file(p);
   }
+  crate::trace::delete_traces(st, profile_id, chat_id);
 
   Ok(idx)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AgentJsonResult {
-  result: Option<AgentJsonPayload>,
+#[tauri::command]
+pub fn chats_list(storage: State<'_, Storage>, profile_id: String) -> Result<ChatIndex, String> {
+  load_index(&storage, &profile_id).map_err(|e| e.to_string())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AgentsListJson {
-  agents: Vec<AgentListItem>,
+#[tauri::command]
+pub fn chat_thread(storage: State<'_, Storage>, profile_id: String, chat_id: String) -> Result<ChatThread, String> {
+  load_thread(&storage, &profile_id, &chat_id).map_err(|e| e.to_string())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AgentListItem {
-  id: String,
+#[tauri::command]
+pub fn chat_reset(storage: State<'_, Storage>, profile_id: String, chat_id: String) -> Result<ChatThread, String> {
+  reset(&storage, &profile_id, &chat_id).map_err(|e| e.to_string())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AgentJsonPayload {
-  payloads: Vec<AgentPayload>,
+#[tauri::command]
+pub fn chats_create(storage: State<'_, Storage>, profile_id: String, title: Option<String>) -> Result<Chat, String> {
+  create(&storage, &profile_id, title).map_err(|e| e.to_string())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AgentPayload {
-  text: Option<String>,
+#[tauri::command]
+pub fn chats_attach_session(storage: State<'_, Storage>, profile_id: String, session_id: String, title: Option<String>) -> Result<Chat, String> {
+  attach_session(&storage, &profile_id, &session_id, title).map_err(|e| e.to_string())
 }
 
-fn ensure_desktop_agent(app: &AppHandle, bin: PathBuf, openclaw_profile: &str, agent_id: &str, model_id: &str) -> Result<()> {
-  // Create tiny workspace (so embedded runs don't inject the huge /Users/.../clawd workspace)
-  let ws = crate::desktop_agent::ensure_minimal_workspace(app, &openclaw_profile.replace("ocd-", "p_"))
-    .or_else(|_| crate::desktop_agent::ensure_minimal_workspace(app, agent_id))
-    .unwrap_or_else(|_| {
-      // fallback to app data dir without failing hard
-      app.path().app_data_dir().unwrap_or_else(|_| std::env::temp_dir()).join("workspace")
-    });
-
-  let list_args: Vec<String> = vec![
-    "--profile".into(),
-    openclaw_profile.to_string(),
-    "agents".into(),
-    "list".into(),
-    "--json".into(),
-  ];
-
-  let out = crate::openclaw_exec::run_openclaw(app, bin.clone(), list_args).context("agents list")?;
-  if out.status.success() {
-    let stdout = String::from_utf8_lossy(&out.stdout).to_string();
-    if let Ok(parsed) = serde_json::from_str::<AgentsListJson>(&stdout) {
-      if parsed.agents.iter().any(|a| a.id == agent_id) {
-        return Ok(());
-      }
-    }
-  }
-
-  // Add agent non-interactively.
-  let add_args: Vec<String> = vec![
-    "--profile".into(),
-    openclaw_profile.to_string(),
-    "agents".into(),
-    "add".into(),
-    "--non-interactive".into(),
-    "--workspace".into(),
-    ws.to_string_lossy().to_string(),
-    "--model".into(),
-    model_id.to_string(),
-    agent_id.to_string(),
-  ];
-
-  let out2 = crate::openclaw_exec::run_openclaw(app, bin, add_args).context("agents add")?;
-  if !out2.status.success() {
-    let stderr = String::from_utf8_lossy(&out2.stderr).to_string();
-    return Err(anyhow::anyhow!(stderr));
-  }
-
-  Ok(())
+#[tauri::command]
+pub fn chats_rename(storage: State<'_, Storage>, profile_id: String, chat_id: String, title: String) -> Result<ChatIndex, String> {
+  rename(&storage, &profile_id, &chat_id, &title).map_err(|e| e.to_string())
 }
 
-fn strip_ollama_prefix(model_id: &str) -> String {
-  model_id.strip_prefix("ollama/").unwrap_or(model_id).to_string()
+#[tauri::command]
+pub fn chats_update(storage: State<'_, Storage>, profile_id: String, chat_id: String, thinking: Option<String>, agent_id: Option<String>, worker: Option<String>) -> Result<ChatIndex, String> {
+  update(&storage, &profile_id, &chat_id, thinking, agent_id, worker).map_err(|e| e.to_string())
 }
 
-fn system_prompt(dev_full_exec_auto: bool, auto_do_mode: bool) -> String {
-  let mut s = String::new();
-  s.push_str("You are OpenClaw Desktop running locally. You can call tools when needed.\n\n");
-  if auto_do_mode {
-    s.push_str("AUTO-DO MODE: Enabled. For action requests, you MUST use tools (exec/web_get) rather than giving plans.\n");
-    s.push_str("If you claim you did something, it must be backed by tool output.\n\n");
-  }
-
-  s.push_str("TOOL CALLS:\n");
-  s.push_str("When responding, you may return a single JSON object matching one of these shapes (no extra text):\n");
-  s.push_str("- {\"tool\":\"web_get\",\"url\":\"https://example.com\"}\n");
-  s.push_str("- {\"tool\":\"exec\",\"cmd\":\"<shell command>\"}\n");
-  s.push_str("- {\"tool\":\"final\",\"text\":\"<final answer>\"}\n\n");
-  if dev_full_exec_auto {
-    s.push_str("EXEC MODE: FULL EXEC AUTO is ENABLED. You may run any shell command you deem necessary. Be careful and prefer read-only commands.\n");
-  } else {
-    s.push_str("EXEC MODE: restricted. Prefer web_get; avoid exec unless explicitly requested.\n");
-  }
-  s
+#[tauri::command]
+pub fn chats_set_prompt_preset(storage: State<'_, Storage>, profile_id: String, chat_id: String, preset_id: Option<String>) -> Result<ChatIndex, String> {
+  set_prompt_preset(&storage, &profile_id, &chat_id, preset_id).map_err(|e| e.to_string())
 }
 
-fn is_action_request(s: &str) -> bool {
-  let t = s.to_lowercase();
-  let kws = [
-    "do it",
-    "get it done",
-    "fix",
-    "install",
-    "set up",
-    "setup",
-    "run",
-    "execute",
-    "create",
-    "delete",
-    "remove",
-    "update",
-    "build",
-    "deploy",
-  ];
-  kws.iter().any(|k| t.contains(k))
-}
-
-fn extract_first_json_object(s: &str) -> Option<(String, String)> {
-  // Extract the first {...} JSON object from a string, ignoring braces inside strings.
-  let mut in_str = false;
-  let mut escape = false;
-  let mut depth: i32 = 0;
-  let mut start: Option<usize> = None;
-
-  for (i, ch) in s.char_indices() {
-    if in_str {
-      if escape {
-        escape = false;
-      } else if ch == '\\' {
-        escape = true;
-      } else if ch == '"' {
-        in_str = false;
-      }
-      continue;
-    }
-
-    if ch == '"' {
-      in_str = true;
-      continue;
-    }
-
-    if ch == '{' {
-      if depth == 0 {
-        start = Some(i);
-      }
-      depth += 1;
-      continue;
-    }
-
-    if ch == '}' {
-      if depth > 0 {
-        depth -= 1;
-        if depth == 0 {
-          if let Some(st) = start {
-            let json = s[st..=i].to_string();
-            let rest = s[i + 1..].to_string();
-            return Some((json, rest));
-          }
-        }
-      }
-    }
-  }
-
-  None
+#[tauri::command(async)]
+pub fn chats_set_agent(storage: State<'_, Storage>, profile_id: String, chat_id: String, agent_id: Option<String>) -> Result<ChatIndex, String> {
+  set_agent(&storage, &profile_id, &chat_id, agent_id).map_err(|e| e.to_string())
 }
 
-fn is_http_url(u: &str) -> bool {
-  u.starts_with("http://") || u.starts_with("https://")
+#[tauri::command]
+pub fn chats_set_backend(storage: State<'_, Storage>, profile_id: String, chat_id: String, backend: Option<ChatBackend>) -> Result<ChatIndex, String> {
+  set_backend(&storage, &profile_id, &chat_id, backend).map_err(|e| e.to_string())
 }
 
-fn base_msgs_for_thread(dev_full_exec_auto: bool, auto_do_mode: bool, thread: &ChatThread, take_last: usize) -> Vec<OllamaMessage> {
-  let mut msgs: Vec<OllamaMessage> = vec![OllamaMessage {
-    role: OllamaRole::System,
-    content: system_prompt(dev_full_exec_auto, auto_do_mode),
-  }];
-
-  for m in thread.messages.iter().rev().take(take_last).rev() {
-    let role = match m.role {
-      ChatRole::User => OllamaRole::User,
-      ChatRole::Assistant => OllamaRole::Assistant,
-      ChatRole::Tool => OllamaRole::User,
+#[tauri::command]
+pub fn chats_delete(storage: State<'_, Storage>, profile_id: String, chat_id: String) -> Result<ChatIndex, String> {
+  delete(&storage, &profile_id, &chat_id).map_err(|e| e.to_string())
+}
+
+// Readable transcript of a chat, oldest message first.
+pub fn export_markdown(chat: &Chat, thread: &ChatThread) -> String {
+  let mut s = format!("# {}\n", chat.title);
+  for m in &thread.messages {
+    let who = match m.role {
+      ChatRole::User => "User",
+      ChatRole::Assistant => "Assistant",
+      ChatRole::Tool => "Tool",
     };
-    msgs.push(OllamaMessage { role, content: m.text.clone() });
-  }
-
-  if auto_do_mode {
-    // If the last user message looks like an action request, force tool-mode.
-    if let Some(last_user) = thread.messages.iter().rev().find(|m| matches!(m.role, ChatRole::User)) {
-      if is_action_request(&last_user.text) {
-        msgs.push(OllamaMessage {
-          role: OllamaRole::User,
-          content: "AUTO-DO: This is an action request. Reply with a single tool JSON (exec/web_get) to actually do the work. Do not answer with a plan.".to_string(),
-        });
+    s.push_str(&format!("\n## {who} ({})\n\n", crate::memory::date_from_ms(m.created_at_ms)));
+    match m.role {
+      ChatRole::Tool => s.push_str(&format!("```\n{}\n```\n", m.text.trim_end())),
+      _ => {
+        s.push_str(m.text.trim_end());
+        s.push('\n');
       }
     }
   }
+  s
+}
 
-  msgs
-}
-
-fn run_ollama_with_tools(app: &AppHandle, profile_id: &str, thread: &ChatThread) -> Result<String> {
-  let settings = crate::settings::load_settings(app, profile_id).unwrap_or_default();
-  let base_url = settings
-    .ollama_base_url
-    .unwrap_or_else(|| "http://localhost:11434".to_string());
-  let model_id = settings
-    .ollama_model
-    .unwrap_or_else(|| "ollama/huihui_ai/qwen3-abliterated:8b".to_string());
-  let model = strip_ollama_prefix(&model_id);
-  let dev_full_exec_auto = settings.dev_full_exec_auto.unwrap_or(false);
-  let auto_do_mode = settings.auto_do_mode.unwrap_or(false);
-
-  // Keep last N messages.
-  let mut msgs: Vec<OllamaMessage> = base_msgs_for_thread(dev_full_exec_auto, auto_do_mode, thread, 16);
-
-  // Tool loop
-  for _step in 0..6 {
-    let resp = crate::ollama::chat(
-      &base_url,
-      OllamaChatReq {
-        model: model.clone(),
-        messages: msgs.clone(),
-        stream: false,
-      },
-    )?;
-
-    let content = resp.message.content;
-
-    let parsed = serde_json::from_str::<ToolCall>(&content)
-      .ok()
-      .or_else(|| extract_first_json_object(&content).and_then(|(j, _rest)| serde_json::from_str::<ToolCall>(&j).ok()));
-
-    if let Some(call) = parsed {
-      match call {
-        ToolCall::Final { text } => return Ok(text),
-        ToolCall::WebGet { url } => {
-          let out = crate::tools::web_get(&url).unwrap_or_else(|e| format!("[tool_error] {e}"));
-          msgs.push(OllamaMessage { role: OllamaRole::Assistant, content });
-          msgs.push(OllamaMessage { role: OllamaRole::User, content: format!("Tool result (web_get):\nURL: {url}\n\n{out}") });
-          continue;
-        }
-        ToolCall::Exec { cmd } => {
-          if !dev_full_exec_auto {
-            msgs.push(OllamaMessage { role: OllamaRole::Assistant, content });
-            msgs.push(OllamaMessage { role: OllamaRole::User, content: "Tool denied: exec is disabled (Developer Mode off). Return a final answer without exec.".to_string() });
-            continue;
-          }
-
-          let cwd = profile_dir(app, profile_id).unwrap_or_else(|_| std::env::temp_dir());
-          let out = crate::tools::exec(&cmd, &cwd).unwrap_or_else(|e| format!("[tool_error] {e}"));
-          msgs.push(OllamaMessage { role: OllamaRole::Assistant, content });
-          msgs.push(OllamaMessage { role: OllamaRole::User, content: format!("Tool result (exec):\n$ {cmd}\n\n{out}") });
-          continue;
-        }
-      }
-    }
+#[derive(Debug, Serialize, Deserialize)]
+struct AgentJsonResult {
+  result: Option<AgentJsonPayload>,
+}
 
-    return Ok(content);
-  }
+#[derive(Debug, Serialize, Deserialize)]
+struct AgentJsonPayload {
+  payloads: Vec<AgentPayload>,
+}
 
-  Err(anyhow::anyhow!("tool loop exceeded"))
-}
-
-fn stream_ollama_into_thread(app: &AppHandle, profile_id: &str, chat_id: &str, assistant_message_id: &str) -> Result<()> {
-  let settings = crate::settings::load_settings(app, profile_id).unwrap_or_default();
-  let base_url = settings
-    .ollama_base_url
-    .unwrap_or_else(|| "http://localhost:11434".to_string());
-  let model_id = settings
-    .ollama_model
-    .unwrap_or_else(|| "ollama/huihui_ai/qwen3-abliterated:8b".to_string());
-  let model = strip_ollama_prefix(&model_id);
-  let dev_full_exec_auto = settings.dev_full_exec_auto.unwrap_or(false);
-  let auto_do_mode = settings.auto_do_mode.unwrap_or(false);
-
-  let thread0 = load_thread(app, profile_id, chat_id).context("load thread")?;
-  let mut msgs: Vec<OllamaMessage> = base_msgs_for_thread(dev_full_exec_auto, auto_do_mode, &thread0, 16);
-
-  let mut accumulated = String::new();
-  let mut last_persist = Instant::now();
-
-  // up to N steps (tool loop)
-  for _step in 0..6 {
-    accumulated.clear();
-
-    crate::ollama::chat_stream(
-      &base_url,
-      OllamaChatReq {
-        model: model.clone(),
-        messages: msgs.clone(),
-        stream: true,
-      },
-      |delta, done| {
-        if !delta.is_empty() {
-          accumulated.push_str(&delta);
-
-          let _ = app.emit(
-            "chat_stream",
-            crate::chat_stream::ChatStreamEvent {
-              profile_id: profile_id.to_string(),
-              chat_id: chat_id.to_string(),
-              message_id: assistant_message_id.to_string(),
-              delta: delta.clone(),
-              done: false,
-              error: None,
-              new_role: None,
-              new_created_at_ms: None,
-            },
-          );
-
-          if last_persist.elapsed() > Duration::from_millis(250) {
-            let mut t = load_thread(app, profile_id, chat_id).context("reload thread")?;
-            if let Some(m) = t.messages.iter_mut().find(|m| m.id == assistant_message_id) {
-              m.text.push_str(&delta);
-            }
-            save_thread(app, profile_id, &t).ok();
-            last_persist = Instant::now();
-          }
-        }
-
-        if done {
-          let _ = app.emit(
-            "chat_stream",
-            crate::chat_stream::ChatStreamEvent {
-              profile_id: profile_id.to_string(),
-              chat_id: chat_id.to_string(),
-              message_id: assistant_message_id.to_string(),
-              delta: "".to_string(),
-              done: true,
-              error: None,
-              new_role: None,
-              new_created_at_ms: None,
-            },
-          );
-        }
-
-        Ok(())
-      },
-    )?;
-
-    // Final persist
-    let mut t = load_thread(app, profile_id, chat_id).context("reload thread")?;
-    if let Some(m) = t.messages.iter_mut().find(|m| m.id == assistant_message_id) {
-      // In case we throttled persists, ensure full content is present.
-      if !accumulated.is_empty() {
-        m.text = accumulated.clone();
-      }
-    }
-    save_thread(app, profile_id, &t).ok();
-
-    // Tool handling
-    let parsed = serde_json::from_str::<ToolCall>(&accumulated)
-      .ok()
-      .or_else(|| extract_first_json_object(&accumulated).and_then(|(j, _rest)| serde_json::from_str::<ToolCall>(&j).ok()));
-
-    if let Some(call) = parsed {
-      match call {
-        ToolCall::Final { text } => {
-          let mut t2 = load_thread(app, profile_id, chat_id).context("reload thread")?;
-          if let Some(m) = t2.messages.iter_mut().find(|m| m.id == assistant_message_id) {
-            m.text = text;
-          }
-          save_thread(app, profile_id, &t2).ok();
-          return Ok(());
-        }
-        ToolCall::WebGet { url } => {
-          if !is_http_url(&url) {
-            // Tell the model this tool only supports http(s)
-            msgs.push(OllamaMessage { role: OllamaRole::Assistant, content: accumulated.clone() });
-            msgs.push(OllamaMessage {
-              role: OllamaRole::User,
-              content: format!("Tool error: web_get only supports http(s) URLs, got: {url}. Use exec (cat/ls) for local files."),
-            });
-            continue;
-          }
-
-          let out = crate::tools::web_get(&url).unwrap_or_else(|e| format!("[tool_error] {e}"));
-
-          // Record tool step in thread
-          let tool_id = new_id("t");
-          {
-            let t = load_thread(app, profile_id, chat_id).ok();
-            if let Some(mut t) = t {
-              t.messages.push(ChatMessage {
-                id: tool_id.clone(),
-                role: ChatRole::Tool,
-                text: format!("web_get:\n{url}\n\n{}", out),
-                created_at_ms: now_ms(),
-              });
-              save_thread(app, profile_id, &t).ok();
-            }
-          }
-
-          let created_at_ms = now_ms();
-          let _ = app.emit(
-            "chat_stream",
-            crate::chat_stream::ChatStreamEvent {
-              profile_id: profile_id.to_string(),
-              chat_id: chat_id.to_string(),
-              message_id: tool_id,
-              delta: format!("web_get:\n{url}\n\n{}", out),
-              done: true,
-              error: None,
-              new_role: Some("tool".to_string()),
-              new_created_at_ms: Some(created_at_ms),
-            },
-          );
-
-          msgs.push(OllamaMessage { role: OllamaRole::Assistant, content: accumulated.clone() });
-          msgs.push(OllamaMessage { role: OllamaRole::User, content: format!("Tool result (web_get):\nURL: {url}\n\n{out}") });
-          continue;
-        }
-        ToolCall::Exec { cmd } => {
-          if !dev_full_exec_auto {
-            msgs.push(OllamaMessage { role: OllamaRole::Assistant, content: accumulated.clone() });
-            msgs.push(OllamaMessage { role: OllamaRole::User, content: "Tool denied: exec is disabled (Developer Mode off). Return a final answer without exec.".to_string() });
-            continue;
-          }
-          let cwd = profile_dir(app, profile_id).unwrap_or_else(|_| std::env::temp_dir());
-          let out = crate::tools::exec(&cmd, &cwd).unwrap_or_else(|e| format!("[tool_error] {e}"));
-
-          // Record tool step in thread
-          let tool_id = new_id("t");
-          {
-            let t = load_thread(app, profile_id, chat_id).ok();
-            if let Some(mut t) = t {
-              t.messages.push(ChatMessage {
-                id: tool_id.clone(),
-                role: ChatRole::Tool,
-                text: format!("exec (cwd={}):\n$ {}\n\n{}", cwd.to_string_lossy(), cmd, out),
-                created_at_ms: now_ms(),
-              });
-              save_thread(app, profile_id, &t).ok();
-            }
-          }
-
-          let created_at_ms = now_ms();
-          let _ = app.emit(
-            "chat_stream",
-            crate::chat_stream::ChatStreamEvent {
-              profile_id: profile_id.to_string(),
-              chat_id: chat_id.to_string(),
-              message_id: tool_id,
-              delta: format!("exec (cwd={}):\n$ {}\n\n{}", cwd.to_string_lossy(), cmd, out),
-              done: true,
-              error: None,
-              new_role: Some("tool".to_string()),
-              new_created_at_ms: Some(created_at_ms),
-            },
-          );
-
-          msgs.push(OllamaMessage { role: OllamaRole::Assistant, content: accumulated.clone() });
-          msgs.push(OllamaMessage { role: OllamaRole::User, content: format!("Tool result (exec):\n$ {cmd}\n\n{out}") });
-          continue;
-        }
-      }
-    }
+#[derive(Debug, Serialize, Deserialize)]
+struct AgentPayload {
+  text: Option<String>,
+}
 
-    // Not a tool call => final content already streamed.
-    return Ok(());
-  }
+fn system_prompt_for(st: &Storage, profile_id: &str, chat_id: &str, settings: &crate::settings::ProfileSettings) -> String {
+  let preset_id = load_index(st, profile_id)
+    .ok()
+    .and_then(|idx| idx.chats.into_iter().find(|c| c.id == chat_id))
+    .and_then(|c| c.prompt_preset_id);
+  let memory = crate::memory::prompt_block_for(st, profile_id, settings);
+  crate::prompts::build_system_prompt(st, profile_id, settings, preset_id.as_deref(), memory.as_deref())
+}
+
+// Everything the agent engine needs for one assistant message.
+fn agent_run(st: &Storage, profile_id: &str, chat_id: &str, assistant_message_id: &str, max_steps: Option<u32>, stream: bool) -> Result<crate::agent::AgentRun> {
+  let settings = crate::settings::load_settings(st, profile_id).unwrap_or_default();
+  let system_prompt = system_prompt_for(st, profile_id, chat_id, &settings);
+  Ok(crate::agent::AgentRun {
+    profile_id: profile_id.to_string(),
+    chat_id: chat_id.to_string(),
+    assistant_message_id: assistant_message_id.to_string(),
+    profile_dir: st.profile_dir(profile_id)?,
+    memory_path: crate::memory::memory_path(st, profile_id)?,
+    settings,
+    system_prompt,
+    max_steps,
+    stream,
+    secrets: crate::secrets::RunSecrets::for_profile(st, profile_id),
+  })
+}
+
+// Runs the agent engine for an assistant message already present in the thread, then saves its trace.
+fn run_agent_for(st: &Storage, sink: &dyn EventSink, profile_id: &str, chat_id: &str, assistant_message_id: &str, max_steps: Option<u32>, stream: bool) -> crate::agent::AgentOutcome {
+  let outcome = match agent_run(st, profile_id, chat_id, assistant_message_id, max_steps, stream) {
+    Ok(run) => crate::agent::run(&run, sink),
+    Err(e) => crate::agent::AgentOutcome {
+      end: crate::agent::LoopEnd::Failed(format!("{e:#}")),
+      trace: crate::trace::AgentTrace::new(assistant_message_id),
+    },
+  };
+  crate::trace::save_trace(st, profile_id, chat_id, &outcome.trace).ok();
+  outcome
+}
 
-  Err(anyhow::anyhow!("tool loop exceeded"))
+// One `openclaw agent` turn.
+struct AgentTurn<'a> {
+  profile_id: &'a str,
+  session_id: &'a str,
+  message: &'a str,
+  thinking: Option<&'a str>,
+  // None uses the desktop agent for the profile and model, creating it if needed.
+  agent_id: Option<&'a str>,
+  model_id: &'a str,
 }
 
-fn run_agent(app: &AppHandle, bin: PathBuf, openclaw_profile: &str, session_id: &str, message: &str, thinking: Option<&str>, agent_id: Option<&str>) -> Result<String> {
+// Runs a turn and returns the reply text. `opts` carries the cancel handle and the sink for output
+// lines.
+fn run_agent(st: &Storage, cli: &AgentCli, turn: &AgentTurn, opts: &RunOpts) -> Result<String> {
+  const AGENT_TIMEOUT_SECS: u64 = 120;
   let mut args: Vec<String> = vec![
     "agent".into(),
     "--local".into(),
     "--session-id".into(),
-    session_id.into(),
+    turn.session_id.into(),
     "--message".into(),
-    message.into(),
+    turn.message.into(),
     "--json".into(),
-    "--channel".into(),
-    "last".into(),
     "--timeout".into(),
-    "120".into(),
+    AGENT_TIMEOUT_SECS.to_string(),
   ];
+  if cli.caps.agent_channel {
+    args.extend(["--channel".into(), "last".into()]);
+  }
 
-  if let Some(t) = thinking {
+  if let Some(t) = turn.thinking {
     args.push("--thinking".into());
     args.push(t.into());
   }
 
-  // If no explicit agent provided, use a deterministic per-profile desktop agent.
-  let chosen_agent = agent_id.map(|x| x.to_string()).unwrap_or_else(|| crate::desktop_agent::default_agent_id(openclaw_profile));
+  // A chat's own agent was checked when it was picked; the default one is created on first use.
+  let chosen_agent = match turn.agent_id {
+    Some(id) => id.to_string(),
+    None => {
+      let id = crate::desktop_agent::default_agent_id(&cli.openclaw_profile, turn.model_id);
+      crate::openclaw_agents::validate_id(&id)?;
+      cli.ensure(st, turn.profile_id, &id, turn.model_id)?;
+      id
+    }
+  };
 
   args.push("--agent".into());
-  args.push(chosen_agent.clone());
+  args.push(chosen_agent);
 
   // Prefix with OpenClaw profile so our app profiles stay isolated.
-  let mut full_args: Vec<String> = vec!["--profile".into(), openclaw_profile.to_string()];
+  let mut full_args: Vec<String> = vec!["--profile".into(), cli.openclaw_profile.clone()];
   full_args.extend(args);
 
-  // Ensure the selected agent exists (creates a minimal-workspace agent by default).
-  let model_id = "ollama/huihui_ai/qwen3-abliterated:8b";
-  ensure_desktop_agent(app, bin.clone(), openclaw_profile, &chosen_agent, model_id).ok();
-
+  // Leave the agent's own `--timeout` room to report before killing it.
+  let opts = RunOpts { timeout: Duration::from_secs(AGENT_TIMEOUT_SECS + 30), node: cli.node.clone(), env: cli.env.clone(), ..opts.clone() };
   let mut last_err: Option<anyhow::Error> = None;
   for attempt in 0..3 {
-    let out = crate::openclaw_exec::run_openclaw(app, bin.clone(), full_args.clone())
-      .context("failed to run openclaw agent")?;
-
-    if out.status.success() {
-      let stdout = String::from_utf8_lossy(&out.stdout).to_string();
-      let parsed: AgentJsonResult = serde_json::from_str(&stdout).context("failed to parse agent JSON")?;
-      let text = parsed
-        .result
-        .and_then(|r| r.payloads.into_iter().find_map(|p| p.text))
-        .unwrap_or_else(|| "(no text payload)".to_string());
-
-      return Ok(text);
+    match crate::openclaw_exec::run(cli.bin.clone(), full_args.clone(), &opts)?.success() {
+      Ok(out) => {
+        let doc = crate::cli_output::json_document(&out.stdout).ok_or_else(|| anyhow!("openclaw agent printed no JSON"))?;
+        let parsed: AgentJsonResult = serde_json::from_value(doc).context("failed to parse agent JSON")?;
+        let text = parsed
+          .result
+          .and_then(|r| r.payloads.into_iter().find_map(|p| p.text))
+          .unwrap_or_else(|| "(no text payload)".to_string());
+
+        return Ok(text);
+      }
+      // A run that crashed can leave the session locked; clear the lock if it's stale and retry.
+      Err(e) if e.to_string().contains("session file locked") && attempt < 2 => {
+        last_err = Some(e.into());
+        let _ = crate::openclaw_sessions::clear_locks(st, turn.profile_id, Some(turn.session_id), false);
+        thread::sleep(Duration::from_millis(650));
+      }
+      Err(e) => return Err(e.into()),
     }
+  }
 
-    let stderr = String::from_utf8_lossy(&out.stderr).to_string();
-    let err = anyhow::anyhow!(stderr);
+  Err(last_err.unwrap_or_else(|| anyhow::anyhow!("openclaw agent failed")))
+}
 
-    // Handle occasional stale session locks after interrupted runs.
-    if err.to_string().contains("session file locked") && attempt < 2 {
-      last_err = Some(err);
-      thread::sleep(Duration::from_millis(650));
-      continue;
+// Forwards an agent run's stderr to the UI as chat_progress events.
+struct ProgressSink<'a> {
+  sink: &'a dyn EventSink,
+  profile_id: &'a str,
+  chat_id: &'a str,
+  message_id: &'a str,
+}
+
+impl EventSink for ProgressSink<'_> {
+  fn emit_json(&self, event: &str, payload: serde_json::Value) {
+    if event != crate::openclaw_exec::OUTPUT_EVENT || payload["stream"] != "stderr" {
+      return;
+    }
+    let line = payload["line"].as_str().unwrap_or_default().trim();
+    if line.is_empty() {
+      return;
     }
+    let ev = crate::chat_stream::ChatProgressEvent {
+      profile_id: self.profile_id.to_string(),
+      chat_id: self.chat_id.to_string(),
+      message_id: self.message_id.to_string(),
+      line: line.to_string(),
+    };
+    crate::core::emit(self.sink, "chat_progress", &ev);
+  }
+}
+
+// Answers the assistant placeholder through `openclaw agent`, using the chat's OpenClaw session.
+// The run can be cancelled with `openclaw_cancel(assistant_message_id)`.
+fn run_openclaw_for(st: &Storage, sink: &dyn EventSink, profile_id: &str, chat: &Chat, assistant_message_id: &str) -> crate::agent::LoopEnd {
+  let dir = match st.profile_dir(profile_id) {
+    Ok(d) => d,
+    Err(e) => return crate::agent::LoopEnd::Failed(format!("{e:#}")),
+  };
+  let res = (|| {
+    let thread = load_thread_in(&dir, &chat.id)?;
+    let pos = thread.messages.iter().position(|m| m.id == assistant_message_id).ok_or_else(|| anyhow!("assistant message not found"))?;
+    let message = thread.messages[..pos]
+      .iter()
+      .rev()
+      .find(|m| matches!(m.role, ChatRole::User))
+      .map(|m| m.text.clone())
+      .ok_or_else(|| anyhow!("no user message to send"))?;
+
+    let settings = crate::settings::load_settings(st, profile_id).unwrap_or_default();
+    let model_id = settings.ollama_model.clone().ok_or_else(|| anyhow!("no model set for this profile"))?;
+    let cli = AgentCli::for_profile(st, profile_id)?;
+    let progress = ProgressSink { sink, profile_id, chat_id: &chat.id, message_id: assistant_message_id };
+    let opts = RunOpts { handle: Some(assistant_message_id), sink: Some(&progress), ..RunOpts::default() };
+    let turn = AgentTurn {
+      profile_id,
+      session_id: &chat.session_id,
+      message: &message,
+      thinking: chat.thinking.as_deref(),
+      agent_id: chat.agent_id.as_deref(),
+      model_id: &model_id,
+    };
+    run_agent(st, &cli, &turn, &opts)
+  })();
+
+  let (text, end) = match res {
+    Ok(text) => (text.clone(), crate::agent::LoopEnd::Reply(text)),
+    Err(e) => (format!("[error] {e:#}"), crate::agent::LoopEnd::Failed(format!("{e:#}"))),
+  };
+  let saved = DirLock::acquire(&dir).and_then(|_lock| {
+    let mut t = load_thread_in(&dir, &chat.id)?;
+    if let Some(m) = t.messages.iter_mut().find(|m| m.id == assistant_message_id) {
+      m.text = text.clone();
+    }
+    save_thread_in(&dir, &t)
+  });
 
-    return Err(err);
+  let mut ev = ChatStreamEvent {
+    profile_id: profile_id.to_string(),
+    chat_id: chat.id.clone(),
+    message_id: assistant_message_id.to_string(),
+    delta: String::new(),
+    done: true,
+    error: None,
+    new_role: None,
+    new_created_at_ms: None,
+    pause: None,
+  };
+  match (&end, saved) {
+    (crate::agent::LoopEnd::Failed(err), _) => ev.error = Some(err.clone()),
+    (_, Err(e)) => ev.error = Some(format!("failed to save reply: {e:#}")),
+    (_, Ok(())) => ev.delta = text,
   }
+  crate::agent::AgentSink::chat_event(sink, ev);
+  end
+}
 
-  Err(last_err.unwrap_or_else(|| anyhow::anyhow!("openclaw agent failed")))
+// The chat's own backend, else the profile default, else Ollama.
+fn backend_for(st: &Storage, profile_id: &str, chat: &Chat) -> ChatBackend {
+  chat
+    .backend
+    .or_else(|| crate::settings::load_settings(st, profile_id).ok().and_then(|s| s.chat_backend))
+    .unwrap_or(ChatBackend::Ollama)
+}
+
+// Answers the assistant placeholder with the chat's backend.
+fn run_backend(st: &Storage, sink: &dyn EventSink, profile_id: &str, chat_id: &str, assistant_message_id: &str, max_steps: Option<u32>, stream: bool) -> crate::agent::LoopEnd {
+  let chat = load_index(st, profile_id).ok().and_then(|idx| idx.chats.into_iter().find(|c| c.id == chat_id));
+  match chat {
+    Some(chat) if backend_for(st, profile_id, &chat) == ChatBackend::Openclaw => run_openclaw_for(st, sink, profile_id, &chat, assistant_message_id),
+    _ => run_agent_for(st, sink, profile_id, chat_id, assistant_message_id, max_steps, stream).end,
+  }
 }
 
 #[derive(Debug, Serialize)]
@@ -812,170 +781,217 @@ pub struct ChatSendStreamResult {
   pub worker: String,
 }
 
-#[tauri::command]
-pub fn chat_send(app: AppHandle, profile_id: String, chat_id: String, text: String) -> Result<ChatSendResult, String> {
-  // Prevent concurrent sends per chat.
-  {
-    let key = inflight_key(&profile_id, &chat_id);
-    let mut s = INFLIGHT.lock().map_err(|_| "inflight lock poisoned".to_string())?;
-    if s.contains(&key) {
-      return Err("chat is busy (inflight)".to_string());
-    }
-    s.insert(key);
+fn mark_inflight(st: &Storage, profile_id: &str, chat_id: &str) -> Result<()> {
+  let key = inflight_key(profile_id, chat_id);
+  let mut s = INFLIGHT.lock().map_err(|_| anyhow!("inflight lock poisoned"))?;
+  if s.contains_key(&key) {
+    return Err(anyhow!("chat is busy (inflight)"));
   }
+  let path = st.profile_dir(profile_id)?.join("locks").join(format!("chat_{chat_id}.lock"));
+  let lease = try_lock_file(&path)?.ok_or_else(|| anyhow!("chat is busy (in use by another openclaw-desktop process)"))?;
+  s.insert(key, lease);
+  Ok(())
+}
+
+fn clear_inflight(profile_id: &str, chat_id: &str) {
+  let key = inflight_key(profile_id, chat_id);
+  if let Ok(mut s) = INFLIGHT.lock() {
+    s.remove(&key);
+  }
+}
 
-  let mut idx = load_index(&app, &profile_id).map_err(|e| e.to_string())?;
+// A user message and its empty assistant placeholder, persisted and ready for the agent engine.
+pub struct PendingSend {
+  pub thread: ChatThread,
+  pub assistant_message_id: String,
+  pub worker: String,
+}
+
+pub fn begin_send_in(dir: &Path, chat_id: &str, text: &str) -> Result<PendingSend> {
+  let _lock = DirLock::acquire(dir)?;
+  let mut idx = load_index_in(dir)?;
   let pos = idx
     .chats
     .iter()
     .position(|c| c.id == chat_id)
-    .ok_or_else(|| "chat not found".to_string())?;
+    .ok_or_else(|| anyhow::anyhow!("chat not found"))?;
 
-  let chat_id2 = idx.chats[pos].id.clone();
+  let worker = idx.chats[pos].worker.clone().unwrap_or_else(|| "default".to_string());
 
-  let mut thread = load_thread(&app, &profile_id, &chat_id2).map_err(|e| e.to_string())?;
+  let mut thread = load_thread_in(dir, chat_id)?;
 
   let msg_user = ChatMessage {
     id: new_id("m"),
     role: ChatRole::User,
-    text: text.clone(),
+    text: text.to_string(),
     created_at_ms: now_ms(),
+    intent: None,
+    pause: None,
   };
   thread.messages.push(msg_user);
 
-  // Persist user message even if agent call fails.
-  idx.chats[pos].updated_at_ms = now_ms();
-  save_thread(&app, &profile_id, &thread).map_err(|e| e.to_string())?;
-  save_index(&app, &profile_id, &idx).map_err(|e| e.to_string())?;
-
-  // Fast path: call Ollama directly (tool loop handled in-process)
-  let reply = match run_ollama_with_tools(&app, &profile_id, &thread) {
-    Ok(r) => r,
-    Err(e) => {
-      // Store error as assistant message (keeps UI consistent)
-      let msg_ai = ChatMessage {
-        id: new_id("m"),
-        role: ChatRole::Assistant,
-        text: format!("[error] {e:#}"),
-        created_at_ms: now_ms(),
-      };
-      thread.messages.push(msg_ai);
-      save_thread(&app, &profile_id, &thread).map_err(|e| e.to_string())?;
-
-      // Clear inflight
-      let key = inflight_key(&profile_id, &chat_id);
-      if let Ok(mut s) = INFLIGHT.lock() {
-        s.remove(&key);
-      }
-
-      return Ok(ChatSendResult { thread });
-    }
-  };
-
+  // Create placeholder assistant message to stream into.
+  let assistant_message_id = new_id("m");
   let msg_ai = ChatMessage {
-    id: new_id("m"),
+    id: assistant_message_id.clone(),
     role: ChatRole::Assistant,
-    text: reply,
+    text: "".to_string(),
     created_at_ms: now_ms(),
+    intent: None,
+    pause: None,
   };
   thread.messages.push(msg_ai);
 
+  // Persist immediately (the user message survives a failed agent call).
   idx.chats[pos].updated_at_ms = now_ms();
-  save_thread(&app, &profile_id, &thread).map_err(|e| e.to_string())?;
-  save_index(&app, &profile_id, &idx).map_err(|e| e.to_string())?;
-
-  // Clear inflight
-  let key = inflight_key(&profile_id, &chat_id);
-  if let Ok(mut s) = INFLIGHT.lock() {
-    s.remove(&key);
-  }
+  save_thread_in(dir, &thread)?;
+  save_index_in(dir, &idx)?;
 
-  Ok(ChatSendResult { thread })
+  Ok(PendingSend { thread, assistant_message_id, worker })
 }
 
-#[tauri::command]
-pub fn chat_send_stream(app: AppHandle, profile_id: String, chat_id: String, text: String) -> Result<ChatSendStreamResult, String> {
-  // Prevent concurrent sends per chat.
-  {
-    let key = inflight_key(&profile_id, &chat_id);
-    let mut s = INFLIGHT.lock().map_err(|_| "inflight lock poisoned".to_string())?;
-    if s.contains(&key) {
-      return Err("chat is busy (inflight)".to_string());
-    }
-    s.insert(key);
+// Marks the chat inflight and persists the user message; clears the mark if that fails.
+fn begin_send(st: &Storage, profile_id: &str, chat_id: &str, text: &str) -> Result<PendingSend> {
+  mark_inflight(st, profile_id, chat_id)?;
+  let res = st.profile_dir(profile_id).and_then(|dir| begin_send_in(&dir, chat_id, text));
+  if res.is_err() {
+    clear_inflight(profile_id, chat_id);
   }
+  res
+}
 
-  let mut idx = load_index(&app, &profile_id).map_err(|e| e.to_string())?;
-  let pos = idx
-    .chats
-    .iter()
-    .position(|c| c.id == chat_id)
-    .ok_or_else(|| "chat not found".to_string())?;
-
-  let worker = idx.chats[pos].worker.clone().unwrap_or_else(|| "default".to_string());
-
-  let chat_id2 = idx.chats[pos].id.clone();
+// Sends and runs the tool loop on the calling thread. With `stream`, deltas reach `sink` as they
+// arrive; either way the call returns the updated thread and how the loop ended once the reply is
+// in the thread.
+pub fn send(st: &Storage, sink: &dyn EventSink, profile_id: &str, chat_id: &str, text: &str, stream: bool) -> Result<(ChatThread, crate::agent::LoopEnd)> {
+  // Prevent concurrent sends per chat.
+  let pending = begin_send(st, profile_id, chat_id, text)?;
 
-  let mut thread = load_thread(&app, &profile_id, &chat_id2).map_err(|e| e.to_string())?;
+  let end = run_backend(st, sink, profile_id, chat_id, &pending.assistant_message_id, None, stream);
+  clear_inflight(profile_id, chat_id);
 
-  let msg_user = ChatMessage {
-    id: new_id("m"),
-    role: ChatRole::User,
-    text: text.clone(),
-    created_at_ms: now_ms(),
-  };
-  thread.messages.push(msg_user);
+  Ok((load_thread(st, profile_id, chat_id)?, end))
+}
 
-  // Create placeholder assistant message to stream into.
+// Runs the tool loop over `messages` in a thread that is not in the chat index (local API requests
+// without a target chat). `extra_system` is appended to the profile's system prompt. The thread
+// file only exists while the loop runs and no trace is kept.
+pub fn run_detached(st: &Storage, sink: &dyn EventSink, profile_id: &str, messages: Vec<ChatMessage>, extra_system: Option<&str>, stream: bool) -> Result<crate::agent::LoopEnd> {
+  let mut thread = ChatThread::new(new_id("api"));
+  thread.messages = messages;
   let assistant_message_id = new_id("m");
-  let msg_ai = ChatMessage {
+  thread.messages.push(ChatMessage {
     id: assistant_message_id.clone(),
     role: ChatRole::Assistant,
     text: "".to_string(),
     created_at_ms: now_ms(),
-  };
-  thread.messages.push(msg_ai);
-
-  // Persist immediately
-  idx.chats[pos].updated_at_ms = now_ms();
-  save_thread(&app, &profile_id, &thread).map_err(|e| e.to_string())?;
-  save_index(&app, &profile_id, &idx).map_err(|e| e.to_string())?;
+    intent: None,
+    pause: None,
+  });
+  save_thread(st, profile_id, &thread)?;
 
-  // Spawn background streaming task
-  let app2 = app.clone();
-  let profile_id2 = profile_id.clone();
-  let chat_id3 = chat_id2.clone();
-  let assistant_id2 = assistant_message_id.clone();
-  let worker2 = worker.clone();
+  let res = agent_run(st, profile_id, &thread.chat_id, &assistant_message_id, None, stream).map(|mut run| {
+    if let Some(extra) = extra_system.filter(|x| !x.trim().is_empty()) {
+      run.system_prompt = format!("{}\n\n{}", run.system_prompt, extra.trim());
+    }
+    crate::agent::run(&run, sink).end
+  });
+  fs::remove_file(chat_thread_path(st, profile_id, &thread.chat_id)?).ok();
+  res
+}
 
+// Runs the streaming tool loop on a background thread, serialized per worker.
+fn spawn_stream(st: Storage, sink: Arc<dyn EventSink>, profile_id: String, chat_id: String, assistant_message_id: String, worker: String, max_steps: Option<u32>) {
   std::thread::spawn(move || {
     // Serialize work per worker.
-    let lock = get_worker_lock(&profile_id2, &worker2);
+    let lock = get_worker_lock(&profile_id, &worker);
     let _guard = lock.lock().ok();
 
-    let res = stream_ollama_into_thread(&app2, &profile_id2, &chat_id3, &assistant_id2);
-    if let Err(e) = res {
-      let _ = app2.emit(
-        "chat_stream",
-        crate::chat_stream::ChatStreamEvent {
-          profile_id: profile_id2.clone(),
-          chat_id: chat_id3.clone(),
-          message_id: assistant_id2.clone(),
-          delta: "".to_string(),
-          done: true,
-          error: Some(e.to_string()),
-          new_role: None,
-          new_created_at_ms: None,
-        },
-      );
-    }
+    run_backend(&st, &*sink, &profile_id, &chat_id, &assistant_message_id, max_steps, true);
 
-    // Clear inflight
-    let key = inflight_key(&profile_id2, &chat_id3);
-    if let Ok(mut s) = INFLIGHT.lock() {
-      s.remove(&key);
-    }
+    clear_inflight(&profile_id, &chat_id);
   });
+}
+
+// Streaming send: persists the user message and returns immediately; deltas go to `sink`.
+pub fn send_stream(st: &Storage, sink: Arc<dyn EventSink>, profile_id: &str, chat_id: &str, text: &str) -> Result<ChatSendStreamResult> {
+  // Prevent concurrent sends per chat.
+  let PendingSend { thread, assistant_message_id, worker } = begin_send(st, profile_id, chat_id, text)?;
+
+  // Spawn background streaming task
+  spawn_stream(st.clone(), sink, profile_id.to_string(), chat_id.to_string(), assistant_message_id.clone(), worker.clone(), None);
+
+  Ok(ChatSendStreamResult { thread, assistant_message_id, worker })
+}
+
+// Resumes a tool loop that paused on a limit, allowing `steps` more model calls
+// (defaults to the profile's max_steps).
+pub fn continue_paused(st: &Storage, sink: Arc<dyn EventSink>, profile_id: &str, chat_id: &str, steps: Option<u32>) -> Result<ChatSendStreamResult> {
+  mark_inflight(st, profile_id, chat_id)?;
+
+  let res = (|| {
+    let _lock = DirLock::acquire(&st.profile_dir(profile_id)?)?;
+    let mut idx = load_index(st, profile_id)?;
+    let pos = idx
+      .chats
+      .iter()
+      .position(|c| c.id == chat_id)
+      .ok_or_else(|| anyhow!("chat not found"))?;
+    let worker = idx.chats[pos].worker.clone().unwrap_or_else(|| "default".to_string());
+
+    let mut thread = load_thread(st, profile_id, chat_id)?;
+    let paused = thread
+      .messages
+      .iter_mut()
+      .rev()
+      .find(|m| matches!(m.role, ChatRole::Assistant))
+      .filter(|m| m.pause.is_some())
+      .ok_or_else(|| anyhow!("nothing to continue"))?;
+    paused.pause = None;
+
+    let assistant_message_id = new_id("m");
+    thread.messages.push(ChatMessage {
+      id: assistant_message_id.clone(),
+      role: ChatRole::Assistant,
+      text: "".to_string(),
+      created_at_ms: now_ms(),
+      intent: None,
+      pause: None,
+    });
+
+    idx.chats[pos].updated_at_ms = now_ms();
+    save_thread(st, profile_id, &thread)?;
+    save_index(st, profile_id, &idx)?;
+    Ok::<_, anyhow::Error>((thread, assistant_message_id, worker))
+  })();
+
+  let (thread, assistant_message_id, worker) = match res {
+    Ok(v) => v,
+    Err(e) => {
+      clear_inflight(profile_id, chat_id);
+      return Err(e);
+    }
+  };
+
+  let steps = steps.filter(|n| *n > 0);
+  spawn_stream(st.clone(), sink, profile_id.to_string(), chat_id.to_string(), assistant_message_id.clone(), worker.clone(), steps);
 
   Ok(ChatSendStreamResult { thread, assistant_message_id, worker })
 }
+
+// Blocks until the reply is in (up to the OpenClaw agent timeout), so not on the main thread.
+#[tauri::command(async)]
+pub fn chat_send(app: AppHandle, storage: State<'_, Storage>, profile_id: String, chat_id: String, text: String) -> Result<ChatSendResult, String> {
+  let (thread, _) = send(&storage, &app, &profile_id, &chat_id, &text, false).map_err(|e| e.to_string())?;
+  Ok(ChatSendResult { thread })
+}
+
+#[tauri::command]
+pub fn chat_send_stream(app: AppHandle, storage: State<'_, Storage>, profile_id: String, chat_id: String, text: String) -> Result<ChatSendStreamResult, String> {
+  send_stream(&storage, Arc::new(app), &profile_id, &chat_id, &text).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn chat_continue(app: AppHandle, storage: State<'_, Storage>, profile_id: String, chat_id: String, steps: Option<u32>) -> Result<ChatSendStreamResult, String> {
+  continue_paused(&storage, Arc::new(app), &profile_id, &chat_id, steps).map_err(|e| e.to_string())
+}
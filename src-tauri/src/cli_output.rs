@@ -0,0 +1,147 @@
+use serde_json::Value;
+
+// Tolerant readers for `openclaw` CLI output. OpenClaw's `--json` shapes and plain-text layouts
+// have shifted between releases, so the parsers look for fields by name rather than by path.
+
+// Lowercase with `_`, `-` and spaces removed, so `uptime_secs`, `uptimeSecs` and `Uptime secs` match.
+pub fn norm_key(k: &str) -> String {
+  k.chars().filter(|c| !matches!(c, '_' | '-' | ' ')).flat_map(|c| c.to_lowercase()).collect()
+}
+
+// The JSON document in `stdout`, skipping any lines printed before it (banners, warnings).
+pub fn json_document(stdout: &str) -> Option<Value> {
+  let mut offset = 0;
+  for line in stdout.split_inclusive('\n') {
+    if line.trim_start().starts_with(['{', '[']) {
+      if let Ok(v) = serde_json::from_str(stdout[offset..].trim()) {
+        return Some(v);
+      }
+    }
+    offset += line.len();
+  }
+  None
+}
+
+// First value under any of `keys` (compared with `norm_key`), searching nested objects
+// breadth-first so top-level fields win over nested ones.
+pub fn find<'a>(v: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+  let keys: Vec<String> = keys.iter().map(|k| norm_key(k)).collect();
+  let mut queue = vec![v];
+  while !queue.is_empty() {
+    let mut next = vec![];
+    for v in queue {
+      match v {
+        Value::Object(map) => {
+          if let Some((_, found)) = map.iter().find(|(k, x)| keys.contains(&norm_key(k)) && !x.is_null()) {
+            return Some(found);
+          }
+          next.extend(map.values());
+        }
+        Value::Array(items) => next.extend(items.iter()),
+        _ => {}
+      }
+    }
+    queue = next;
+  }
+  None
+}
+
+pub fn as_u64(v: &Value) -> Option<u64> {
+  match v {
+    Value::Number(n) => n.as_u64().or_else(|| n.as_f64().filter(|f| *f >= 0.0).map(|f| f as u64)),
+    Value::String(s) => s.trim().parse().ok(),
+    _ => None,
+  }
+}
+
+pub fn as_text(v: &Value) -> Option<String> {
+  match v {
+    Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+    Value::Number(n) => Some(n.to_string()),
+    _ => None,
+  }
+}
+
+// The number following `key` in `line` ("pid 4242", "pid=4242", "(pid: 4242)").
+pub fn number_after(line: &str, key: &str) -> Option<u64> {
+  let lower = line.to_lowercase();
+  let mut from = 0;
+  while let Some(i) = lower[from..].find(key) {
+    let rest = &lower[from + i + key.len()..];
+    let rest = rest.trim_start_matches([' ', ':', '=', '#', '\t']);
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if !digits.is_empty() {
+      return digits.parse().ok();
+    }
+    from += i + key.len();
+  }
+  None
+}
+
+// Port from the first `host:port` in `line` (URLs, "127.0.0.1:18789", "localhost:18789").
+pub fn port_in(line: &str) -> Option<u16> {
+  line.match_indices(':').find_map(|(i, _)| {
+    let before = line[..i].chars().last()?;
+    if !(before.is_ascii_alphanumeric() || before == ']') {
+      return None;
+    }
+    let digits: String = line[i + 1..].chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 2 || line[i + 1 + digits.len()..].starts_with(|c: char| c.is_ascii_alphanumeric()) {
+      return None;
+    }
+    digits.parse().ok()
+  })
+}
+
+// Seconds in a human duration: "93s", "2h 3m", "1 day, 4 hours", "3d4h", "01:02:03", "12:30" (mm:ss).
+pub fn parse_duration(s: &str) -> Option<u64> {
+  let s = s.trim().trim_end_matches('.').to_lowercase();
+
+  // Trailing clock part, optionally after "N days,".
+  let mut total = 0u64;
+  let mut found = false;
+  let mut rest = s.as_str();
+  if let Some(clock) = s.split([' ', ',']).rev().find(|t| !t.is_empty()).filter(|t| t.contains(':')) {
+    let parts: Vec<u64> = clock.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    total = parts.iter().fold(0, |acc, p| acc * 60 + p);
+    found = true;
+    rest = s[..s.len() - clock.len()].trim_end_matches([' ', ',']);
+    if rest.is_empty() {
+      return Some(total);
+    }
+  }
+
+  let mut num = String::new();
+  let mut unit = String::new();
+  let flush = |num: &mut String, unit: &mut String, total: &mut u64, found: &mut bool| {
+    let mult = match unit.as_str() {
+      "w" | "wk" | "week" | "weeks" => Some(604_800),
+      "d" | "day" | "days" => Some(86_400),
+      "h" | "hr" | "hrs" | "hour" | "hours" => Some(3_600),
+      "m" | "min" | "mins" | "minute" | "minutes" => Some(60),
+      "s" | "sec" | "secs" | "second" | "seconds" | "" => Some(1),
+      "ms" => Some(0),
+      _ => None,
+    };
+    if let (Ok(n), Some(mult)) = (num.parse::<u64>(), mult) {
+      *total += n * mult;
+      *found = true;
+    }
+    num.clear();
+    unit.clear();
+  };
+  for c in rest.chars() {
+    if c.is_ascii_digit() {
+      if !unit.is_empty() {
+        flush(&mut num, &mut unit, &mut total, &mut found);
+      }
+      num.push(c);
+    } else if c.is_ascii_alphabetic() {
+      unit.push(c);
+    } else if !unit.is_empty() {
+      flush(&mut num, &mut unit, &mut total, &mut found);
+    }
+  }
+  flush(&mut num, &mut unit, &mut total, &mut found);
+  found.then_some(total)
+}
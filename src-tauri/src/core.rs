@@ -0,0 +1,186 @@
+use std::{
+  fs::{self, File, OpenOptions, TryLockError},
+  io::Write,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+  },
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+// Host-independent plumbing for the chat/settings/gateway logic: where data lives (`Storage`)
+// and where events go (`EventSink`). The Tauri commands build these from the AppHandle; a CLI,
+// a local API server or a test can build them from plain paths.
+
+// Tauri `identifier` from tauri.conf.json; app_data_dir is <data dir>/<identifier>.
+pub const APP_IDENTIFIER: &str = "com.openclaw.macapp";
+
+#[derive(Debug, Clone)]
+pub struct Storage {
+  // App data dir: profiles.json and profiles/{id}/...
+  root: PathBuf,
+  // User home, for files owned by the openclaw CLI (~/.openclaw).
+  home: PathBuf,
+}
+
+impl Storage {
+  pub fn new(root: impl Into<PathBuf>, home: impl Into<PathBuf>) -> Self {
+    Self { root: root.into(), home: home.into() }
+  }
+
+  pub fn from_app(app: &AppHandle) -> Result<Self> {
+    let root = app.path().app_data_dir().context("app_data_dir not available")?;
+    let home = app.path().home_dir().context("home_dir not available")?;
+    Ok(Self::new(root, home))
+  }
+
+  // Same location Tauri resolves for app_data_dir, for front ends without an AppHandle.
+  pub fn default_location() -> Result<Self> {
+    let data = dirs::data_dir().ok_or_else(|| anyhow!("data dir not available"))?;
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("home dir not available"))?;
+    Ok(Self::new(data.join(APP_IDENTIFIER), home))
+  }
+
+  pub fn root(&self) -> &Path {
+    &self.root
+  }
+
+  pub fn home(&self) -> &Path {
+    &self.home
+  }
+
+  pub fn profiles_path(&self) -> Result<PathBuf> {
+    fs::create_dir_all(&self.root).context("failed to create app data dir")?;
+    Ok(self.root.join("profiles.json"))
+  }
+
+  pub fn profile_dir(&self, profile_id: &str) -> Result<PathBuf> {
+    let dir = self.root.join("profiles").join(profile_id);
+    fs::create_dir_all(&dir).context("failed to create profile data dir")?;
+    Ok(dir)
+  }
+
+  // Default OpenClaw logs live in ~/.openclaw/logs
+  pub fn openclaw_logs_dir(&self) -> PathBuf {
+    self.home.join(".openclaw").join("logs")
+  }
+}
+
+// The GUI and the CLI can run at the same time against the same files. Every read-modify-write
+// of a JSON store holds a `DirLock` on the directory it lives in, and files are replaced
+// atomically so readers never see a half-written file.
+
+// Advisory lock on `{dir}/.lock`; released on drop. Not re-entrant: take it once, at the
+// outermost function that loads and saves.
+pub struct DirLock {
+  _file: File,
+}
+
+impl DirLock {
+  pub fn acquire(dir: &Path) -> Result<Self> {
+    fs::create_dir_all(dir).context("failed to create lock dir")?;
+    let file = OpenOptions::new()
+      .create(true)
+      .truncate(false)
+      .write(true)
+      .open(dir.join(".lock"))
+      .context("failed to open lock file")?;
+    file.lock().context("failed to lock data dir")?;
+    Ok(Self { _file: file })
+  }
+}
+
+// Non-blocking exclusive lock on `path`. Ok(None) means another handle (in this or another
+// process) holds it.
+pub fn try_lock_file(path: &Path) -> Result<Option<File>> {
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).context("failed to create lock dir")?;
+  }
+  let file = OpenOptions::new()
+    .create(true)
+    .truncate(false)
+    .write(true)
+    .open(path)
+    .context("failed to open lock file")?;
+  match file.try_lock() {
+    Ok(()) => Ok(Some(file)),
+    Err(TryLockError::WouldBlock) => Ok(None),
+    Err(TryLockError::Error(e)) => Err(anyhow!(e).context("failed to lock file")),
+  }
+}
+
+static TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+// Write to a sibling temp file, then rename over `path`.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+  let name = path.file_name().ok_or_else(|| anyhow!("invalid path: {}", path.display()))?;
+  let seq = TMP_SEQ.fetch_add(1, Ordering::Relaxed);
+  let tmp = path.with_file_name(format!(".{}.tmp-{}-{seq}", name.to_string_lossy(), std::process::id()));
+  let mut f = File::create(&tmp).with_context(|| format!("failed to create {}", tmp.display()))?;
+  f.write_all(contents.as_ref()).with_context(|| format!("failed to write {}", tmp.display()))?;
+  f.sync_all().ok();
+  fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+}
+
+// Ids are `{prefix}_{ulid}`: 48 bits of milliseconds and 80 random bits in lowercase Crockford
+// base32, so they sort by creation time and don't collide across processes or launches. Ids
+// made in the same millisecond by this process increment the random part to keep their order.
+// Older ids (`p_{ms}`, `c_{ms}_{seq}`) stay valid; nothing parses an id.
+const CROCKFORD: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+static LAST_ULID: Mutex<(u64, u128)> = Mutex::new((0, 0));
+
+fn ulid() -> String {
+  let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+  let mut last = LAST_ULID.lock().unwrap_or_else(|e| e.into_inner());
+  let (ms, rand) = if now <= last.0 && last.1 + 1 < 1 << 80 {
+    (last.0, last.1 + 1)
+  } else {
+    let mut bytes = [0u8; 16];
+    // A zero fallback still gives unique ids within this process.
+    let _ = getrandom::getrandom(&mut bytes[6..]);
+    (now.max(last.0 + u64::from(now <= last.0)), u128::from_be_bytes(bytes))
+  };
+  *last = (ms, rand);
+  let n = (u128::from(ms) << 80) | rand;
+  (0..26).rev().map(|i| CROCKFORD[((n >> (i * 5)) & 31) as usize] as char).collect()
+}
+
+pub fn new_id(prefix: &str) -> String {
+  format!("{prefix}_{}", ulid())
+}
+
+// Error naming the first id that appears twice.
+pub fn ensure_unique_ids<'a>(what: &str, ids: impl IntoIterator<Item = &'a str>) -> Result<()> {
+  let mut seen = std::collections::HashSet::new();
+  match ids.into_iter().find(|id| !seen.insert(*id)) {
+    Some(id) => Err(anyhow!("duplicate {what} id {id}")),
+    None => Ok(()),
+  }
+}
+
+pub trait EventSink: Send + Sync {
+  fn emit_json(&self, event: &str, payload: serde_json::Value);
+}
+
+pub fn emit<T: Serialize>(sink: &(impl EventSink + ?Sized), event: &str, payload: &T) {
+  if let Ok(v) = serde_json::to_value(payload) {
+    sink.emit_json(event, v);
+  }
+}
+
+impl EventSink for AppHandle {
+  fn emit_json(&self, event: &str, payload: serde_json::Value) {
+    let _ = self.emit(event, payload);
+  }
+}
+
+// For callers that only care about what ends up on disk.
+pub struct NullSink;
+
+impl EventSink for NullSink {
+  fn emit_json(&self, _event: &str, _payload: serde_json::Value) {}
+}
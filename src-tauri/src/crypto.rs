@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use chacha20poly1305::{
+  aead::{Aead, KeyInit, Payload},
+  Key, XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+
+// Passphrase encryption for data that leaves the keychain (exported secrets, the file vault,
+// encrypted chat history).
+// XChaCha20-Poly1305 with a key from Argon2id; the KDF parameters travel with the ciphertext so
+// they can be raised later without breaking old files.
+
+// Argon2id cost: 19 MiB, 2 passes (the OWASP baseline).
+const M_COST_KIB: u32 = 19 * 1024;
+const T_COST: u32 = 2;
+const P_COST: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+  pub m_cost_kib: u32,
+  pub t_cost: u32,
+  pub p_cost: u32,
+  // base64
+  pub salt: String,
+}
+
+// Ciphertext plus everything but the passphrase needed to open it. Stored as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sealed {
+  pub v: u32,
+  pub kdf: KdfParams,
+  // base64, 24 bytes
+  pub nonce: String,
+  // base64
+  pub data: String,
+}
+
+pub fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+  let mut out = [0u8; N];
+  getrandom::getrandom(&mut out).map_err(|e| anyhow!("no randomness available: {e}"))?;
+  Ok(out)
+}
+
+// A key derived from a passphrase, with the KDF parameters that reproduce it. Held in memory to
+// seal and open repeatedly without paying for the KDF each time.
+#[derive(Clone)]
+pub struct DerivedKey {
+  key: Key,
+  pub kdf: KdfParams,
+}
+
+impl Drop for DerivedKey {
+  fn drop(&mut self) {
+    self.key.iter_mut().for_each(|b| *b = 0);
+  }
+}
+
+// Fresh parameters (new salt) at the current cost.
+pub fn new_kdf() -> Result<KdfParams> {
+  Ok(KdfParams { m_cost_kib: M_COST_KIB, t_cost: T_COST, p_cost: P_COST, salt: B64.encode(random_bytes::<16>()?) })
+}
+
+pub fn derive_key(passphrase: &str, kdf: &KdfParams) -> Result<DerivedKey> {
+  if passphrase.is_empty() {
+    return Err(anyhow!("passphrase required"));
+  }
+  let salt = B64.decode(&kdf.salt).map_err(|_| anyhow!("corrupt salt"))?;
+  let params = Params::new(kdf.m_cost_kib, kdf.t_cost, kdf.p_cost, Some(32)).map_err(|e| anyhow!("bad KDF parameters: {e}"))?;
+  let mut key = Key::default();
+  Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+    .map_err(|e| anyhow!("key derivation failed: {e}"))?;
+  Ok(DerivedKey { key, kdf: kdf.clone() })
+}
+
+// Encrypts `plaintext` under `passphrase`. `context` names what the data is for and must match
+// on open, so a ciphertext can't be passed off as another kind.
+pub fn seal(passphrase: &str, context: &str, plaintext: &[u8]) -> Result<Sealed> {
+  seal_with(&derive_key(passphrase, &new_kdf()?)?, context, plaintext)
+}
+
+pub fn open(passphrase: &str, context: &str, sealed: &Sealed) -> Result<Vec<u8>> {
+  open_with(&derive_key(passphrase, &sealed.kdf)?, context, sealed)
+}
+
+pub fn seal_with(key: &DerivedKey, context: &str, plaintext: &[u8]) -> Result<Sealed> {
+  let nonce = random_bytes::<24>()?;
+  let data = XChaCha20Poly1305::new(&key.key)
+    .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad: context.as_bytes() })
+    .map_err(|_| anyhow!("encryption failed"))?;
+  Ok(Sealed { v: 1, kdf: key.kdf.clone(), nonce: B64.encode(nonce), data: B64.encode(data) })
+}
+
+pub fn open_with(key: &DerivedKey, context: &str, sealed: &Sealed) -> Result<Vec<u8>> {
+  if sealed.v != 1 {
+    return Err(anyhow!("unsupported encryption version {}", sealed.v));
+  }
+  let nonce = B64.decode(&sealed.nonce).ok().filter(|n| n.len() == 24).ok_or_else(|| anyhow!("corrupt nonce"))?;
+  let data = B64.decode(&sealed.data).map_err(|_| anyhow!("corrupt ciphertext"))?;
+  XChaCha20Poly1305::new(&key.key)
+    .decrypt(XNonce::from_slice(&nonce), Payload { msg: &data, aad: context.as_bytes() })
+    .map_err(|_| anyhow!("wrong passphrase (or the data was altered)"))
+}
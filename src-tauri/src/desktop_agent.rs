@@ -1,23 +1,17 @@
 use std::{fs, path::PathBuf};
 
 use anyhow::{Context, Result};
-use tauri::{AppHandle, Manager};
 
-fn profile_dir(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  let base = app.path().app_data_dir().context("app_data_dir not available")?;
-  let dir = base.join("profiles").join(profile_id);
-  fs::create_dir_all(&dir).context("failed to create profile data dir")?;
-  Ok(dir)
-}
+use crate::core::Storage;
 
-pub fn workspace_dir(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  let dir = profile_dir(app, profile_id)?.join("workspace");
+pub fn workspace_dir(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  let dir = st.profile_dir(profile_id)?.join("workspace");
   fs::create_dir_all(&dir).context("failed to create workspace dir")?;
   Ok(dir)
 }
 
-pub fn ensure_minimal_workspace(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  let dir = workspace_dir(app, profile_id)?;
+pub fn ensure_minimal_workspace(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  let dir = workspace_dir(st, profile_id)?;
 
   // Keep these tiny. The point is to avoid injecting the user's entire /Users/.../clawd workspace.
   let soul = dir.join("SOUL.md");
@@ -51,10 +45,27 @@ pub fn ensure_minimal_workspace(app: &AppHandle, profile_id: &str) -> Result<Pat
   Ok(dir)
 }
 
-pub fn default_agent_id(profile_id: &str) -> String {
-  let safe = profile_id
-    .chars()
-    .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
-    .collect::<String>();
-  format!("desktop-{safe}")
+// Agent the desktop app creates for a profile and model when a chat doesn't name one. One agent
+// per model, since an agent's model is fixed when it's created. Ids over OpenClaw's 64 characters
+// (see openclaw_agents::validate_id) keep the start of the model and end in a hash of both.
+pub fn default_agent_id(profile_id: &str, model_id: &str) -> String {
+  let slug = |s: &str| {
+    let mut out = String::new();
+    for c in s.chars().map(|c| c.to_ascii_lowercase()) {
+      if c.is_ascii_alphanumeric() {
+        out.push(c);
+      } else if !out.ends_with('-') {
+        out.push('-');
+      }
+    }
+    out.trim_matches('-').to_string()
+  };
+  let id = format!("desktop-{}-{}", slug(profile_id), slug(model_id));
+  if id.len() <= 64 {
+    return id;
+  }
+  // FNV-1a: ids have to stay the same across builds, which std's hasher doesn't promise.
+  let hash = format!("{profile_id}\0{model_id}").bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+  let model: String = slug(model_id).chars().take(64 - "desktop-".len() - 1 - 16).collect();
+  format!("desktop-{}-{hash:016x}", model.trim_end_matches('-'))
 }
@@ -0,0 +1,324 @@
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  path::{Path, PathBuf},
+  process::Command,
+  sync::Mutex,
+  time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::State;
+
+use crate::core::Storage;
+use crate::openclaw_exec::RunOpts;
+
+// Finds `node` and `openclaw` installs. GUI apps on macOS start with a bare PATH, so besides PATH
+// we look where version managers and package managers put binaries: nvm, fnm, volta, asdf,
+// Homebrew and the npm global prefix. Each candidate is asked for its version; the first usable
+// one wins unless the profile pins a path in settings.
+
+// OpenClaw needs Node 22 or newer.
+pub const MIN_NODE_MAJOR: u64 = 22;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tool {
+  Node,
+  Openclaw,
+}
+
+impl Tool {
+  fn bin_name(self) -> &'static str {
+    match self {
+      Self::Node => "node",
+      Self::Openclaw => "openclaw",
+    }
+  }
+}
+
+// Where to look. Built from the process environment; tests fill it in directly.
+#[derive(Debug, Clone, Default)]
+pub struct SearchEnv {
+  pub path: Vec<PathBuf>,
+  pub nvm_dir: Option<PathBuf>,
+  pub fnm_dirs: Vec<PathBuf>,
+  pub volta_home: Option<PathBuf>,
+  pub asdf_dir: Option<PathBuf>,
+  pub brew_bins: Vec<PathBuf>,
+  pub npm_prefixes: Vec<PathBuf>,
+}
+
+impl SearchEnv {
+  pub fn from_process(home: &Path) -> Self {
+    let var = |k: &str| std::env::var_os(k).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let path = std::env::var_os("PATH").map(|p| std::env::split_paths(&p).collect()).unwrap_or_default();
+
+    let mut fnm_dirs: Vec<PathBuf> = var("FNM_DIR").into_iter().collect();
+    fnm_dirs.push(var("XDG_DATA_HOME").unwrap_or_else(|| home.join(".local/share")).join("fnm"));
+    fnm_dirs.push(home.join("Library/Application Support/fnm"));
+    fnm_dirs.push(home.join(".fnm"));
+
+    let mut npm_prefixes: Vec<PathBuf> = var("NPM_CONFIG_PREFIX").into_iter().collect();
+    npm_prefixes.extend(npmrc_prefix(&home.join(".npmrc")));
+    npm_prefixes.push(home.join(".npm-global"));
+    npm_prefixes.push(home.join(".npm-packages"));
+
+    Self {
+      path,
+      nvm_dir: Some(var("NVM_DIR").unwrap_or_else(|| home.join(".nvm"))),
+      fnm_dirs,
+      volta_home: Some(var("VOLTA_HOME").unwrap_or_else(|| home.join(".volta"))),
+      asdf_dir: Some(var("ASDF_DATA_DIR").unwrap_or_else(|| home.join(".asdf"))),
+      brew_bins: ["/opt/homebrew/bin", "/usr/local/bin", "/home/linuxbrew/.linuxbrew/bin"].iter().map(PathBuf::from).collect(),
+      npm_prefixes,
+    }
+  }
+
+  // Directories that may hold `tool`, most preferred first, with where each came from.
+  fn bin_dirs(&self, tool: Tool) -> Vec<(&'static str, PathBuf)> {
+    let mut dirs: Vec<(&'static str, PathBuf)> = self.path.iter().map(|p| ("path", p.clone())).collect();
+    if let Some(nvm) = &self.nvm_dir {
+      let default = fs::read_to_string(nvm.join("alias/default")).unwrap_or_default();
+      let mut versions = version_dirs(&nvm.join("versions/node"));
+      // nvm's default alias ("22", "v22.3.0", "lts/*") goes first when it names an installed version.
+      let wanted = default.trim().trim_start_matches('v');
+      if !wanted.is_empty() {
+        if let Some(i) = versions.iter().position(|v| v.file_name().is_some_and(|n| n.to_string_lossy().trim_start_matches('v').starts_with(wanted))) {
+          let v = versions.remove(i);
+          versions.insert(0, v);
+        }
+      }
+      dirs.extend(versions.into_iter().map(|v| ("nvm", v.join("bin"))));
+    }
+    for fnm in &self.fnm_dirs {
+      dirs.push(("fnm", fnm.join("aliases/default/bin")));
+      dirs.extend(version_dirs(&fnm.join("node-versions")).into_iter().map(|v| ("fnm", v.join("installation/bin"))));
+    }
+    if let Some(volta) = &self.volta_home {
+      dirs.push(("volta", volta.join("bin")));
+      if tool == Tool::Node {
+        dirs.extend(version_dirs(&volta.join("tools/image/node")).into_iter().map(|v| ("volta", v.join("bin"))));
+      }
+    }
+    if let Some(asdf) = &self.asdf_dir {
+      dirs.push(("asdf", asdf.join("shims")));
+      dirs.extend(version_dirs(&asdf.join("installs/nodejs")).into_iter().map(|v| ("asdf", v.join("bin"))));
+    }
+    dirs.extend(self.brew_bins.iter().map(|p| ("homebrew", p.clone())));
+    dirs.extend(self.npm_prefixes.iter().map(|p| ("npm-prefix", p.join("bin"))));
+    dirs
+  }
+}
+
+// `prefix=~/.npm-global` from an .npmrc.
+fn npmrc_prefix(path: &Path) -> Option<PathBuf> {
+  let raw = fs::read_to_string(path).ok()?;
+  let value = raw.lines().find_map(|l| l.trim().strip_prefix("prefix")?.trim_start().strip_prefix('=').map(str::trim))?;
+  let home = path.parent()?;
+  Some(match value.strip_prefix("~/") {
+    Some(rest) => home.join(rest),
+    None => PathBuf::from(value),
+  })
+}
+
+// Numeric parts of "v22.3.0" / "22.3.0".
+pub fn version_key(s: &str) -> Vec<u64> {
+  s.trim_start_matches('v').split('.').map_while(|p| p.parse().ok()).collect()
+}
+
+// Subdirectories of `dir` named like versions, newest first.
+fn version_dirs(dir: &Path) -> Vec<PathBuf> {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return vec![];
+  };
+  let mut out: Vec<(Vec<u64>, PathBuf)> = entries
+    .flatten()
+    .map(|e| e.path())
+    .filter_map(|p| {
+      let key = version_key(&p.file_name()?.to_string_lossy());
+      (!key.is_empty()).then_some((key, p))
+    })
+    .collect();
+  out.sort_by(|a, b| b.0.cmp(&a.0));
+  out.into_iter().map(|(_, p)| p).collect()
+}
+
+// The version in `--version` output ("v22.3.0", "openclaw 2026.2.14", "2026.2.14 (abc123)").
+pub fn parse_version(out: &str) -> Option<String> {
+  out.split_whitespace().map(|t| t.trim_start_matches('v')).find(|t| t.starts_with(|c: char| c.is_ascii_digit()) && t.contains('.')).map(String::from)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+  pub tool: Tool,
+  pub path: String,
+  // nvm, fnm, volta, asdf, homebrew, npm-prefix, path or pinned.
+  pub source: String,
+  pub version: Option<String>,
+  // Runs and (for node) is new enough.
+  pub usable: bool,
+  pub error: Option<String>,
+}
+
+type ProbeKey = (PathBuf, Option<SystemTime>, Option<PathBuf>);
+
+// Versions found by path, modification time and node, so each binary is run once per process.
+// Failures aren't kept; the user may be fixing the install.
+static VERSIONS: Lazy<Mutex<HashMap<ProbeKey, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn probe(tool: Tool, path: &Path, node: Option<&Path>) -> Result<String, String> {
+  let key = (path.to_path_buf(), fs::metadata(path).and_then(|m| m.modified()).ok(), node.map(Path::to_path_buf));
+  if let Some(v) = VERSIONS.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+    return Ok(v.clone());
+  }
+  let opts = RunOpts { timeout: PROBE_TIMEOUT, node: node.map(Path::to_path_buf), ..RunOpts::default() };
+  let out = match tool {
+    Tool::Node => {
+      let mut cmd = Command::new(path);
+      cmd.arg("--version");
+      crate::openclaw_exec::run_command(cmd, path, &opts)
+    }
+    Tool::Openclaw => crate::openclaw_exec::run(path.to_path_buf(), vec!["--version".into()], &opts),
+  };
+  let result = match out.and_then(|o| o.success()) {
+    Ok(o) => parse_version(&o.stdout).or_else(|| parse_version(&o.stderr)).ok_or_else(|| format!("unrecognized version output: {}", o.stdout.trim())),
+    Err(e) => Err(e.to_string()),
+  };
+  if let Ok(v) = &result {
+    VERSIONS.lock().unwrap_or_else(|e| e.into_inner()).insert(key, v.clone());
+  }
+  result
+}
+
+fn check(tool: Tool, path: &Path, source: &str, node: Option<&Path>) -> Candidate {
+  let (version, mut error) = match probe(tool, path, node) {
+    Ok(v) => (Some(v), None),
+    Err(e) => (None, Some(e)),
+  };
+  if tool == Tool::Node {
+    if let Some(v) = &version {
+      if version_key(v).first().is_none_or(|major| *major < MIN_NODE_MAJOR) {
+        error = Some(format!("node {v} is too old; OpenClaw needs {MIN_NODE_MAJOR} or newer"));
+      }
+    }
+  }
+  Candidate { tool, path: path.to_string_lossy().to_string(), source: source.to_string(), usable: error.is_none(), version, error }
+}
+
+fn is_executable(path: &Path) -> bool {
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+  }
+  #[cfg(not(unix))]
+  {
+    path.is_file()
+  }
+}
+
+// Existing `tool` binaries in search order, without duplicates (symlinks to the same file count once).
+fn locate(env: &SearchEnv, tool: Tool) -> Vec<(&'static str, PathBuf)> {
+  let mut seen = HashSet::new();
+  env
+    .bin_dirs(tool)
+    .into_iter()
+    .map(|(source, dir)| (source, dir.join(tool.bin_name())))
+    .filter(|(_, p)| is_executable(p))
+    .filter(|(_, p)| seen.insert(fs::canonicalize(p).unwrap_or_else(|_| p.clone())))
+    .collect()
+}
+
+// Every install found, each with its version; for the settings screen.
+pub fn scan(env: &SearchEnv, tool: Tool, node: Option<&Path>) -> Vec<Candidate> {
+  locate(env, tool).into_iter().map(|(source, p)| check(tool, &p, source, node)).collect()
+}
+
+// The first usable install. Stops probing at the first hit.
+pub fn find(env: &SearchEnv, tool: Tool, node: Option<&Path>) -> Option<Candidate> {
+  locate(env, tool).into_iter().map(|(source, p)| check(tool, &p, source, node)).find(|c| c.usable)
+}
+
+fn searched() -> &'static str {
+  "PATH, nvm, fnm, volta, asdf, Homebrew and the npm global prefix"
+}
+
+pub fn not_found(tool: Tool) -> anyhow::Error {
+  match tool {
+    Tool::Node => anyhow!("node {MIN_NODE_MAJOR}+ not found (searched {}). Install Node.js or set its path in Settings.", searched()),
+    Tool::Openclaw => anyhow!("openclaw not found (searched {}). Install it with `npm install -g openclaw` or set its path in Settings.", searched()),
+  }
+}
+
+fn pinned(path: &str, tool: Tool) -> Result<PathBuf> {
+  let p = PathBuf::from(path);
+  if !p.exists() {
+    return Err(anyhow!("pinned {} path {} does not exist", tool.bin_name(), p.display()));
+  }
+  Ok(p)
+}
+
+// The profile's pinned node, if any.
+pub fn pinned_node(st: &Storage, profile_id: &str) -> Result<Option<PathBuf>> {
+  let s = crate::settings::load_settings(st, profile_id).unwrap_or_default();
+  s.node_path.as_deref().map(|p| pinned(p, Tool::Node)).transpose()
+}
+
+pub fn resolve_node(env: &SearchEnv) -> Result<PathBuf> {
+  find(env, Tool::Node, None).map(|c| PathBuf::from(c.path)).ok_or_else(|| not_found(Tool::Node))
+}
+
+pub fn resolve_openclaw(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  let s = crate::settings::load_settings(st, profile_id).unwrap_or_default();
+  if let Some(p) = s.openclaw_path.as_deref() {
+    return pinned(p, Tool::Openclaw);
+  }
+  let node = pinned_node(st, profile_id)?;
+  find(&SearchEnv::from_process(st.home()), Tool::Openclaw, node.as_deref()).map(|c| PathBuf::from(c.path)).ok_or_else(|| not_found(Tool::Openclaw))
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiscoveryReport {
+  pub node: Vec<Candidate>,
+  pub openclaw: Vec<Candidate>,
+  // What the profile would use now: the pinned path or the first usable candidate.
+  pub selected_node: Option<String>,
+  pub selected_openclaw: Option<String>,
+  pub error: Option<String>,
+}
+
+pub fn report(st: &Storage, env: &SearchEnv, profile_id: Option<&str>) -> DiscoveryReport {
+  let settings = profile_id.and_then(|pid| crate::settings::load_settings(st, pid).ok()).unwrap_or_default();
+  let mut node = scan(env, Tool::Node, None);
+  let pinned_node = settings.node_path.as_deref().map(Path::new);
+  if let Some(p) = pinned_node {
+    node.insert(0, check(Tool::Node, p, "pinned", None));
+  }
+  let mut openclaw = scan(env, Tool::Openclaw, pinned_node);
+  if let Some(p) = settings.openclaw_path.as_deref() {
+    openclaw.insert(0, check(Tool::Openclaw, Path::new(p), "pinned", pinned_node));
+  }
+
+  let pick = |list: &[Candidate], pin: Option<&str>| pin.map(String::from).or_else(|| list.iter().find(|c| c.usable).map(|c| c.path.clone()));
+  let selected_node = pick(&node, settings.node_path.as_deref());
+  let selected_openclaw = pick(&openclaw, settings.openclaw_path.as_deref());
+  let error = if selected_openclaw.is_none() {
+    Some(not_found(Tool::Openclaw).to_string())
+  } else if selected_node.is_none() {
+    Some(not_found(Tool::Node).to_string())
+  } else {
+    None
+  };
+  DiscoveryReport { node, openclaw, selected_node, selected_openclaw, error }
+}
+
+// Lists node and openclaw installs with versions, and which ones the profile uses.
+#[tauri::command(async)]
+pub fn discovery_scan(storage: State<'_, Storage>, profile_id: Option<String>) -> Result<DiscoveryReport, String> {
+  Ok(report(&storage, &SearchEnv::from_process(storage.home()), profile_id.as_deref()))
+}
+
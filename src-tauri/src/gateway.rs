@@ -1,36 +1,23 @@
-use std::path::PathBuf;
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use serde::Serialize;
-use tauri::{AppHandle, Manager};
+use serde_json::Value;
+use tauri::{AppHandle, State};
 
-fn run_openclaw(app: &AppHandle, bin: PathBuf, args: Vec<String>) -> Result<(i32, String, String)> {
-  let out = crate::openclaw_exec::run_openclaw(app, bin, args.clone())
-    .with_context(|| format!("failed to run openclaw {}", args.join(" ")))?;
+use crate::cli_output::{as_text, as_u64, find, json_document, number_after, parse_duration, port_in};
+use crate::core::{EventSink, Storage};
+use crate::openclaw_exec::RunOpts;
 
-  let code = out.status.code().unwrap_or(-1);
-  let stdout = String::from_utf8_lossy(&out.stdout).to_string();
-  let stderr = String::from_utf8_lossy(&out.stderr).to_string();
-  Ok((code, stdout, stderr))
-}
-
-fn logs_dir(app: &AppHandle) -> Result<PathBuf> {
-  // Default OpenClaw logs live in ~/.openclaw/logs
-  let home = app.path().home_dir().context("home_dir not available")?;
-  Ok(home.join(".openclaw").join("logs"))
-}
+// `gateway status` should answer quickly; start/stop/restart may wait on a service manager.
+const STATUS_TIMEOUT_SECS: u64 = 20;
+pub const ACTION_TIMEOUT_SECS: u64 = 90;
 
-fn tail_file(path: PathBuf, max_lines: usize) -> Result<String> {
-  if !path.exists() {
-    return Ok(String::new());
-  }
-  let raw = std::fs::read_to_string(&path).context("failed to read log file")?;
-  if max_lines == 0 {
-    return Ok(raw);
-  }
-  let lines: Vec<&str> = raw.lines().collect();
-  let start = lines.len().saturating_sub(max_lines);
-  Ok(lines[start..].join("\n"))
+fn run_openclaw(st: &Storage, profile_id: &str, args: Vec<String>, opts: &RunOpts) -> Result<(i32, String, String)> {
+  crate::openclaw_compat::caps(st, profile_id)?;
+  let bin = crate::settings::resolve_openclaw_bin(st, profile_id)?;
+  let opts = RunOpts { node: crate::discovery::pinned_node(st, profile_id)?, ..opts.clone() };
+  let out = crate::openclaw_exec::run(bin, args, &opts)?;
+  Ok((out.code, out.stdout, out.stderr))
 }
 
 #[derive(Debug, Serialize)]
@@ -38,58 +25,194 @@ pub struct GatewayStatus {
   pub exit_code: i32,
   pub stdout: String,
   pub stderr: String,
+  // Parsed `gateway status` output; None for start/stop/restart.
+  pub info: Option<GatewayInfo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+  Running,
+  Stopped,
+  Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GatewayInfo {
+  pub state: RunState,
+  pub pid: Option<u32>,
+  pub port: Option<u16>,
+  pub uptime_secs: Option<u64>,
+  pub version: Option<String>,
+  // "json" or "text": which parser produced this.
+  pub source: String,
+}
+
+impl GatewayInfo {
+  fn empty(source: &str) -> Self {
+    Self { state: RunState::Unknown, pid: None, port: None, uptime_secs: None, version: None, source: source.to_string() }
+  }
+}
+
+fn state_word(s: &str) -> RunState {
+  let s = s.to_lowercase();
+  if ["not running", "stopped", "inactive", "not loaded", "dead", "exited", "down"].iter().any(|w| s.contains(w)) {
+    RunState::Stopped
+  } else if ["running", "active", "started", "listening"].iter().any(|w| s.contains(w)) {
+    RunState::Running
+  } else {
+    RunState::Unknown
+  }
+}
+
+// `gateway status --json`. Field names vary by release, so they're looked up anywhere in the document.
+pub fn parse_status_json(v: &Value) -> GatewayInfo {
+  let mut info = GatewayInfo::empty("json");
+
+  info.state = match find(v, &["running", "isRunning", "active"]) {
+    Some(Value::Bool(b)) => if *b { RunState::Running } else { RunState::Stopped },
+    _ => find(v, &["status", "state"]).and_then(as_text).map(|s| state_word(&s)).unwrap_or(RunState::Unknown),
+  };
+  info.pid = find(v, &["pid"]).and_then(as_u64).and_then(|n| u32::try_from(n).ok()).filter(|n| *n > 0);
+  info.port = find(v, &["port"])
+    .and_then(as_u64)
+    .and_then(|n| u16::try_from(n).ok())
+    .or_else(|| find(v, &["url", "address", "listen", "wsUrl"]).and_then(as_text).and_then(|u| port_in(&u)));
+  info.uptime_secs = find(v, &["uptimeSecs", "uptimeSeconds", "uptime_s"])
+    .and_then(as_u64)
+    .or_else(|| find(v, &["uptimeMs"]).and_then(as_u64).map(|ms| ms / 1000))
+    .or_else(|| match find(v, &["uptime"]) {
+      Some(Value::String(s)) => parse_duration(s),
+      Some(n) => as_u64(n),
+      None => None,
+    })
+    .or_else(|| {
+      // Epoch ms start time.
+      let started = find(v, &["startedAtMs", "startedAt", "startTime"]).and_then(as_u64)?;
+      let now = crate::chats::now_ms() as u64;
+      (started > 1_000_000_000_000 && started <= now).then(|| (now - started) / 1000)
+    });
+  info.version = find(v, &["version", "gatewayVersion"]).and_then(as_text).map(|v| v.trim_start_matches('v').to_string());
+
+  if info.state == RunState::Unknown && info.pid.is_some() {
+    info.state = RunState::Running;
+  }
+  info
 }
 
-fn args_with_profile(app: &AppHandle, profile_id: &str, rest: &[&str]) -> Result<Vec<String>> {
-  let prof = crate::settings::resolve_openclaw_profile(app, profile_id)?;
+// Plain `gateway status` output, e.g. "Gateway: running (pid 4242, port 18789)" / "Uptime: 2h 3m".
+pub fn parse_status_text(text: &str) -> GatewayInfo {
+  let mut info = GatewayInfo::empty("text");
+  for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+    let lower = line.to_lowercase();
+
+    if info.state == RunState::Unknown && (lower.contains("gateway") || lower.starts_with("status") || lower.starts_with("state") || lower.starts_with("active") || lower.contains("runtime")) {
+      info.state = state_word(&lower);
+    }
+    if info.pid.is_none() {
+      info.pid = number_after(&lower, "pid").and_then(|n| u32::try_from(n).ok()).filter(|n| *n > 0);
+    }
+    if info.port.is_none() {
+      info.port = number_after(&lower, "port").and_then(|n| u16::try_from(n).ok()).or_else(|| {
+        let has_addr = ["://", "127.0.0.1", "localhost", "0.0.0.0", "listen"].iter().any(|w| lower.contains(w));
+        has_addr.then(|| port_in(line)).flatten()
+      });
+    }
+    if info.uptime_secs.is_none() {
+      if let Some(i) = lower.find("uptime").or_else(|| lower.find("up for")) {
+        let rest = &lower[i..];
+        let rest = rest.split_once([':', ' ']).map(|(_, r)| r).unwrap_or_default();
+        info.uptime_secs = parse_duration(rest.trim_start_matches("for ").split([')', ';']).next().unwrap_or_default());
+      }
+    }
+    if info.version.is_none() {
+      // "Version: v2026.2.14", or a banner like "openclaw 2026.2.1 gateway ...".
+      let after = lower.find("version").map(|i| i + "version".len()).or_else(|| lower.find("openclaw ").map(|i| i + "openclaw ".len()));
+      if let Some(i) = after {
+        info.version = lower[i..]
+          .trim_start_matches(':')
+          .split_whitespace()
+          .next()
+          .map(|t| t.trim_matches([',', ')', '(']).trim_start_matches('v'))
+          .filter(|t| t.starts_with(|c: char| c.is_ascii_digit()))
+          .map(String::from);
+      }
+    }
+  }
+  if info.state == RunState::Unknown && info.pid.is_some() {
+    info.state = RunState::Running;
+  }
+  info
+}
+
+fn args_with_profile(st: &Storage, profile_id: &str, rest: &[&str]) -> Result<Vec<String>> {
+  let prof = crate::settings::resolve_openclaw_profile(st, profile_id)?;
   let mut args: Vec<String> = vec!["--profile".into(), prof];
   args.extend(rest.iter().map(|s| s.to_string()));
   Ok(args)
 }
 
-#[tauri::command]
-pub fn gateway_status(app: AppHandle, profile_id: String) -> Result<GatewayStatus, String> {
-  let bin = crate::settings::resolve_openclaw_bin(&app, &profile_id).map_err(|e| e.to_string())?;
-  let args = args_with_profile(&app, &profile_id, &["gateway", "status"]).map_err(|e| e.to_string())?;
-  let (code, stdout, stderr) = run_openclaw(&app, bin, args).map_err(|e| e.to_string())?;
-  Ok(GatewayStatus { exit_code: code, stdout, stderr })
+// Runs `openclaw gateway <action>` for a profile. `opts` carries the cancel handle and output sink;
+// its timeout is used as given.
+pub fn run_action(st: &Storage, profile_id: &str, action: &str, opts: &RunOpts) -> Result<GatewayStatus> {
+  let args = args_with_profile(st, profile_id, &["gateway", action])?;
+  // A gateway it starts runs the profile's models, so it gets the provider keys.
+  let env = if action == "stop" { vec![] } else { crate::secrets::openclaw_env(st, profile_id) };
+  let (code, stdout, stderr) = run_openclaw(st, profile_id, args, &RunOpts { env, ..opts.clone() })?;
+  Ok(GatewayStatus { exit_code: code, stdout, stderr, info: None })
 }
 
-#[tauri::command]
-pub fn gateway_start(app: AppHandle, profile_id: String) -> Result<GatewayStatus, String> {
-  let bin = crate::settings::resolve_openclaw_bin(&app, &profile_id).map_err(|e| e.to_string())?;
-  let args = args_with_profile(&app, &profile_id, &["gateway", "start"]).map_err(|e| e.to_string())?;
-  let (code, stdout, stderr) = run_openclaw(&app, bin, args).map_err(|e| e.to_string())?;
-  Ok(GatewayStatus { exit_code: code, stdout, stderr })
+// `gateway status`, parsed. Asks for `--json` on releases that have it; older ones (or ones that
+// print text anyway) get the text parser.
+pub fn status(st: &Storage, profile_id: &str) -> Result<GatewayStatus> {
+  if crate::openclaw_compat::caps(st, profile_id)?.gateway_status_json {
+    let args = args_with_profile(st, profile_id, &["gateway", "status", "--json"])?;
+    let (code, stdout, stderr) = run_openclaw(st, profile_id, args, &RunOpts::timeout(STATUS_TIMEOUT_SECS))?;
+    if let Some(v) = json_document(&stdout) {
+      let info = parse_status_json(&v);
+      return Ok(GatewayStatus { exit_code: code, stdout, stderr, info: Some(info) });
+    }
+  }
+
+  let mut out = run_action(st, profile_id, "status", &RunOpts::timeout(STATUS_TIMEOUT_SECS))?;
+  let mut info = parse_status_text(&format!("{}\n{}", out.stdout, out.stderr));
+  if info.state == RunState::Unknown && out.exit_code != 0 {
+    info.state = RunState::Stopped;
+  }
+  out.info = Some(info);
+  Ok(out)
 }
 
-#[tauri::command]
-pub fn gateway_stop(app: AppHandle, profile_id: String) -> Result<GatewayStatus, String> {
-  let bin = crate::settings::resolve_openclaw_bin(&app, &profile_id).map_err(|e| e.to_string())?;
-  let args = args_with_profile(&app, &profile_id, &["gateway", "stop"]).map_err(|e| e.to_string())?;
-  let (code, stdout, stderr) = run_openclaw(&app, bin, args).map_err(|e| e.to_string())?;
-  Ok(GatewayStatus { exit_code: code, stdout, stderr })
+// Runs a user-requested gateway action (status/start/stop/restart). Start/stop/restart are
+// recorded as the intended state so the supervisor knows a stop was deliberate.
+// `handle` lets the caller cancel the run with `openclaw_cancel`; `sink` gets its output lines.
+pub fn gateway_cmd(st: &Storage, profile_id: &str, action: &str, handle: Option<&str>, sink: Option<&dyn EventSink>) -> Result<GatewayStatus> {
+  if action != "status" {
+    let desired = action != "stop";
+    crate::gateway_supervisor::set_desired_in(&st.profile_dir(profile_id)?, desired)?;
+  }
+  let opts = RunOpts { handle, sink, ..RunOpts::timeout(ACTION_TIMEOUT_SECS) };
+  let out = if action == "status" { status(st, profile_id) } else { run_action(st, profile_id, action, &opts) };
+  crate::gateway_supervisor::poke(profile_id);
+  out
 }
 
-#[tauri::command]
-pub fn gateway_restart(app: AppHandle, profile_id: String) -> Result<GatewayStatus, String> {
-  let bin = crate::settings::resolve_openclaw_bin(&app, &profile_id).map_err(|e| e.to_string())?;
-  let args = args_with_profile(&app, &profile_id, &["gateway", "restart"]).map_err(|e| e.to_string())?;
-  let (code, stdout, stderr) = run_openclaw(&app, bin, args).map_err(|e| e.to_string())?;
-  Ok(GatewayStatus { exit_code: code, stdout, stderr })
+#[tauri::command(async)]
+pub fn gateway_status(storage: State<'_, Storage>, profile_id: String) -> Result<GatewayStatus, String> {
+  status(&storage, &profile_id).map_err(|e| e.to_string())
 }
 
-#[derive(Debug, Serialize)]
-pub struct GatewayLogs {
-  pub out: String,
-  pub err: String,
-}
-
-#[tauri::command]
-pub fn gateway_logs(app: AppHandle, lines: Option<u32>) -> Result<GatewayLogs, String> {
-  let dir = logs_dir(&app).map_err(|e| e.to_string())?;
-  let n = lines.unwrap_or(200) as usize;
-  let out = tail_file(dir.join("gateway.log"), n).map_err(|e| e.to_string())?;
-  let err = tail_file(dir.join("gateway.err.log"), n).map_err(|e| e.to_string())?;
-  Ok(GatewayLogs { out, err })
+#[tauri::command(async)]
+pub fn gateway_start(app: AppHandle, storage: State<'_, Storage>, profile_id: String, handle: Option<String>) -> Result<GatewayStatus, String> {
+  gateway_cmd(&storage, &profile_id, "start", handle.as_deref(), Some(&app)).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn gateway_stop(app: AppHandle, storage: State<'_, Storage>, profile_id: String, handle: Option<String>) -> Result<GatewayStatus, String> {
+  gateway_cmd(&storage, &profile_id, "stop", handle.as_deref(), Some(&app)).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn gateway_restart(app: AppHandle, storage: State<'_, Storage>, profile_id: String, handle: Option<String>) -> Result<GatewayStatus, String> {
+  gateway_cmd(&storage, &profile_id, "restart", handle.as_deref(), Some(&app)).map_err(|e| e.to_string())
 }
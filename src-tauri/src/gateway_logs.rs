@@ -0,0 +1,405 @@
+use std::{
+  collections::HashMap,
+  fs::{self, File},
+  io::{Read, Seek, SeekFrom},
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  },
+  thread::JoinHandle,
+  time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::core::{EventSink, Storage};
+
+// Gateway log files: tail reads that seek from the end instead of loading whole files, a follow
+// mode that polls the files and pushes new lines as `gateway:log` events, level/regex filters,
+// and snapshots. Rotation (the path now points at a new file) and truncation (the file got
+// shorter) are detected while following.
+
+pub const LOG_EVENT: &str = "gateway:log";
+
+const CHUNK: u64 = 64 * 1024;
+// Stop scanning backwards after this much when a filter matches little.
+const MAX_SCAN_BYTES: u64 = 32 * 1024 * 1024;
+const FOLLOW_POLL: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+  Trace,
+  Debug,
+  Info,
+  Warn,
+  Error,
+}
+
+impl LogLevel {
+  // Level named in a log line: `ERROR`, `[warn]`, `level=debug`, `"level":"info"`.
+  pub fn detect(line: &str) -> Option<Self> {
+    let head: String = line.chars().take(160).collect::<String>().to_lowercase();
+    let words = head.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty());
+    for w in words {
+      let lvl = match w {
+        "trace" | "trc" => Self::Trace,
+        "debug" | "dbg" => Self::Debug,
+        "info" | "inf" | "notice" => Self::Info,
+        "warn" | "warning" | "wrn" => Self::Warn,
+        "error" | "err" | "fatal" | "panic" | "critical" => Self::Error,
+        _ => continue,
+      };
+      return Some(lvl);
+    }
+    None
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+  Out,
+  Err,
+}
+
+impl LogStream {
+  fn file_name(self) -> &'static str {
+    match self {
+      Self::Out => "gateway.log",
+      Self::Err => "gateway.err.log",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogFilter {
+  // Minimum level. Lines without a recognizable level count as info (warn in gateway.err.log).
+  #[serde(default)]
+  pub level: Option<LogLevel>,
+  // Regex matched against the whole line.
+  #[serde(default)]
+  pub pattern: Option<String>,
+}
+
+// A filter ready to apply.
+pub struct LineFilter {
+  level: Option<LogLevel>,
+  pattern: Option<Regex>,
+}
+
+impl LineFilter {
+  pub fn new(f: &LogFilter) -> Result<Self> {
+    let pattern = match f.pattern.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+      Some(p) => Some(Regex::new(p).map_err(|e| anyhow!("invalid pattern: {e}"))?),
+      None => None,
+    };
+    Ok(Self { level: f.level, pattern })
+  }
+
+  pub fn level_of(stream: LogStream, line: &str) -> LogLevel {
+    LogLevel::detect(line).unwrap_or(match stream {
+      LogStream::Out => LogLevel::Info,
+      LogStream::Err => LogLevel::Warn,
+    })
+  }
+
+  pub fn matches(&self, stream: LogStream, line: &str) -> bool {
+    if let Some(min) = self.level {
+      if Self::level_of(stream, line) < min {
+        return false;
+      }
+    }
+    self.pattern.as_ref().is_none_or(|re| re.is_match(line))
+  }
+}
+
+// The last `n` lines of `path` that pass `keep`, oldest first, reading backwards in chunks.
+// `n == 0` returns every matching line (still bounded by MAX_SCAN_BYTES).
+pub fn tail_lines(path: &Path, n: usize, keep: impl Fn(&str) -> bool) -> Result<Vec<String>> {
+  let mut f = match File::open(path) {
+    Ok(f) => f,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+    Err(e) => return Err(anyhow!(e).context(format!("failed to open {}", path.display()))),
+  };
+  let len = f.metadata()?.len();
+  let stop_at = len.saturating_sub(MAX_SCAN_BYTES);
+
+  let mut out: Vec<String> = vec![];
+  // Bytes of a line whose start is in an earlier (not yet read) chunk.
+  let mut carry: Vec<u8> = vec![];
+  let mut pos = len;
+  while pos > stop_at && (n == 0 || out.len() < n) {
+    let start = pos.saturating_sub(CHUNK).max(stop_at);
+    let mut buf = vec![0; (pos - start) as usize];
+    f.seek(SeekFrom::Start(start))?;
+    f.read_exact(&mut buf)?;
+    buf.extend_from_slice(&carry);
+
+    // Everything after the first newline is whole lines; keep the head for the next chunk.
+    let split = if start == 0 { Some(0) } else { buf.iter().position(|b| *b == b'\n').map(|i| i + 1) };
+    let (head, body) = match split {
+      Some(i) => buf.split_at(i),
+      None => (&buf[..], &[][..]),
+    };
+    // `body` ends in a newline (except for an unterminated last line), which leaves an empty tail.
+    let mut lines: Vec<&[u8]> = body.split(|b| *b == b'\n').collect();
+    if lines.last().is_some_and(|l| l.is_empty()) {
+      lines.pop();
+    }
+    for l in lines.into_iter().rev() {
+      let s = String::from_utf8_lossy(l);
+      let s = s.trim_end_matches('\r');
+      if keep(s) {
+        out.push(s.to_string());
+        if n != 0 && out.len() == n {
+          break;
+        }
+      }
+    }
+    carry = if start == 0 { vec![] } else { head.to_vec() };
+    pos = start;
+  }
+  out.reverse();
+  Ok(out)
+}
+
+// OpenClaw keeps a `--profile X` install's state in ~/.openclaw-X; older releases log everything
+// to ~/.openclaw/logs.
+pub fn logs_dir(st: &Storage, profile_id: Option<&str>) -> PathBuf {
+  if let Some(pid) = profile_id {
+    if let Ok(prof) = crate::settings::resolve_openclaw_profile(st, pid) {
+      let dir = st.home().join(format!(".openclaw-{prof}")).join("logs");
+      if dir.is_dir() {
+        return dir;
+      }
+    }
+  }
+  st.openclaw_logs_dir()
+}
+
+#[derive(Debug, Serialize)]
+pub struct GatewayLogs {
+  pub out: String,
+  pub err: String,
+  // Directory the logs were read from.
+  pub dir: String,
+}
+
+pub fn read_logs(st: &Storage, profile_id: Option<&str>, lines: Option<u32>, filter: &LogFilter) -> Result<GatewayLogs> {
+  let dir = logs_dir(st, profile_id);
+  let f = LineFilter::new(filter)?;
+  let n = lines.unwrap_or(200) as usize;
+  let read = |stream: LogStream| -> Result<String> {
+    Ok(tail_lines(&dir.join(stream.file_name()), n, |l| f.matches(stream, l))?.join("\n"))
+  };
+  Ok(GatewayLogs { out: read(LogStream::Out)?, err: read(LogStream::Err)?, dir: dir.to_string_lossy().to_string() })
+}
+
+// Writes the filtered tail of both logs to `dest` (default: profiles/{id}/log_snapshots/).
+// Returns the written path.
+pub fn save_snapshot(st: &Storage, profile_id: &str, dest: Option<PathBuf>, lines: Option<u32>, filter: &LogFilter) -> Result<PathBuf> {
+  let logs = read_logs(st, Some(profile_id), Some(lines.unwrap_or(0)), filter)?;
+  let dest = match dest {
+    Some(p) => p,
+    None => {
+      let dir = st.profile_dir(profile_id)?.join("log_snapshots");
+      fs::create_dir_all(&dir).context("failed to create snapshot dir")?;
+      dir.join(format!("gateway-{}.log", crate::chats::now_ms()))
+    }
+  };
+  let body = format!(
+    "# gateway logs from {}\n\n## gateway.log\n{}\n\n## gateway.err.log\n{}\n",
+    logs.dir, logs.out, logs.err
+  );
+  crate::core::write_atomic(&dest, body)?;
+  Ok(dest)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+  pub stream: LogStream,
+  pub level: LogLevel,
+  pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEvent {
+  pub follow_id: String,
+  pub profile_id: Option<String>,
+  pub lines: Vec<LogLine>,
+  // Set when a file was rotated ("rotated") or truncated ("truncated") since the last event.
+  pub reset: Option<String>,
+}
+
+// Identity of the file behind a path, to notice when it's been replaced.
+#[cfg(unix)]
+fn file_id(meta: &fs::Metadata) -> u64 {
+  use std::os::unix::fs::MetadataExt;
+  meta.ino()
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &fs::Metadata) -> u64 {
+  0
+}
+
+// Follow state for one log file.
+struct Tail {
+  stream: LogStream,
+  path: PathBuf,
+  file: Option<File>,
+  id: u64,
+  offset: u64,
+  // Bytes after the last newline, waiting for the rest of the line.
+  partial: Vec<u8>,
+}
+
+impl Tail {
+  // Starts at the current end of the file; only lines written from now on are reported.
+  fn at_end(stream: LogStream, path: PathBuf) -> Self {
+    let mut t = Self { stream, path, file: None, id: 0, offset: 0, partial: vec![] };
+    if let Ok(f) = File::open(&t.path) {
+      if let Ok(meta) = f.metadata() {
+        t.id = file_id(&meta);
+        t.offset = meta.len();
+      }
+      t.file = Some(f);
+    }
+    t
+  }
+
+  fn read_new(&mut self, out: &mut Vec<u8>) -> std::io::Result<()> {
+    if let Some(f) = self.file.as_mut() {
+      f.seek(SeekFrom::Start(self.offset))?;
+      let n = f.read_to_end(out)?;
+      self.offset += n as u64;
+    }
+    Ok(())
+  }
+
+  // New complete lines since the last poll, and a reset note if the file was replaced or cut.
+  fn poll(&mut self) -> (Vec<String>, Option<&'static str>) {
+    let mut bytes = std::mem::take(&mut self.partial);
+    let mut reset = None;
+
+    match fs::metadata(&self.path) {
+      Ok(meta) if self.file.is_none() || file_id(&meta) != self.id => {
+        // New or rotated file: finish the old one, then read the new one from the start.
+        if self.file.is_some() {
+          let _ = self.read_new(&mut bytes);
+          reset = Some("rotated");
+        }
+        self.file = File::open(&self.path).ok();
+        self.id = file_id(&meta);
+        self.offset = 0;
+      }
+      Ok(meta) if meta.len() < self.offset => {
+        reset = Some("truncated");
+        bytes.clear();
+        self.offset = 0;
+      }
+      Ok(_) => {}
+      // Deleted (mid-rotation); keep the handle until a new file shows up.
+      Err(_) => {}
+    }
+    let _ = self.read_new(&mut bytes);
+
+    let mut lines = vec![];
+    let complete = bytes.iter().rposition(|b| *b == b'\n').map(|i| i + 1).unwrap_or(0);
+    for l in bytes[..complete].split(|b| *b == b'\n') {
+      lines.push(String::from_utf8_lossy(l).trim_end_matches('\r').to_string());
+    }
+    lines.pop();
+    self.partial = bytes[complete..].to_vec();
+    (lines, reset)
+  }
+}
+
+pub struct Follower {
+  stop: Arc<AtomicBool>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Follower {
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::SeqCst);
+    if let Some(t) = self.thread.take() {
+      let _ = t.join();
+    }
+  }
+}
+
+// Polls `dir`'s gateway logs and emits matching new lines as LOG_EVENT until dropped.
+pub fn follow(dir: &Path, follow_id: &str, profile_id: Option<&str>, filter: &LogFilter, sink: Arc<dyn EventSink>, poll: Duration) -> Result<Follower> {
+  let f = LineFilter::new(filter)?;
+  let stop = Arc::new(AtomicBool::new(false));
+  let flag = stop.clone();
+  let mut tails = [
+    Tail::at_end(LogStream::Out, dir.join(LogStream::Out.file_name())),
+    Tail::at_end(LogStream::Err, dir.join(LogStream::Err.file_name())),
+  ];
+  let (follow_id, profile_id) = (follow_id.to_string(), profile_id.map(String::from));
+
+  let thread = std::thread::spawn(move || {
+    while !flag.load(Ordering::SeqCst) {
+      let mut lines = vec![];
+      let mut reset = None;
+      for t in tails.iter_mut() {
+        let (new, r) = t.poll();
+        reset = reset.or(r);
+        lines.extend(new.into_iter().filter(|l| f.matches(t.stream, l)).map(|text| LogLine {
+          stream: t.stream,
+          level: LineFilter::level_of(t.stream, &text),
+          text,
+        }));
+      }
+      if !lines.is_empty() || reset.is_some() {
+        let ev = LogEvent { follow_id: follow_id.clone(), profile_id: profile_id.clone(), lines, reset: reset.map(String::from) };
+        crate::core::emit(&*sink, LOG_EVENT, &ev);
+      }
+      std::thread::sleep(poll);
+    }
+  });
+
+  Ok(Follower { stop, thread: Some(thread) })
+}
+
+// Active follows, by follow id.
+static FOLLOWERS: Lazy<Mutex<HashMap<String, Follower>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[tauri::command]
+pub fn gateway_logs(storage: State<'_, Storage>, profile_id: Option<String>, lines: Option<u32>, filter: Option<LogFilter>) -> Result<GatewayLogs, String> {
+  read_logs(&storage, profile_id.as_deref(), lines, &filter.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+// Starts pushing new log lines as `gateway:log` events; returns the id for gateway_logs_unfollow.
+#[tauri::command]
+pub fn gateway_logs_follow(app: AppHandle, storage: State<'_, Storage>, profile_id: Option<String>, filter: Option<LogFilter>) -> Result<String, String> {
+  let dir = logs_dir(&storage, profile_id.as_deref());
+  let id = crate::core::new_id("follow");
+  let follower = follow(&dir, &id, profile_id.as_deref(), &filter.unwrap_or_default(), Arc::new(app), FOLLOW_POLL).map_err(|e| e.to_string())?;
+  FOLLOWERS.lock().map_err(|_| "follow lock poisoned".to_string())?.insert(id.clone(), follower);
+  Ok(id)
+}
+
+#[tauri::command]
+pub fn gateway_logs_unfollow(follow_id: String) -> Result<(), String> {
+  let removed = FOLLOWERS.lock().map_err(|_| "follow lock poisoned".to_string())?.remove(&follow_id);
+  // Join outside the registry lock.
+  drop(removed);
+  Ok(())
+}
+
+#[tauri::command]
+pub fn gateway_logs_save(storage: State<'_, Storage>, profile_id: String, path: Option<String>, lines: Option<u32>, filter: Option<LogFilter>) -> Result<String, String> {
+  let dest = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).map(PathBuf::from);
+  save_snapshot(&storage, &profile_id, dest, lines, &filter.unwrap_or_default())
+    .map(|p| p.to_string_lossy().to_string())
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,448 @@
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  sync::{Arc, Condvar, Mutex},
+  thread::JoinHandle,
+  time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::core::{write_atomic, DirLock, EventSink, Storage};
+use crate::settings::GatewaySupervisorConfig;
+
+// Watches a profile's gateway in the background: polls its health, emits `gateway:state` on
+// every change and restarts it with exponential backoff when it goes down while it's meant to be
+// running. Whether it's meant to be running is recorded in profiles/{id}/gateway.json by the
+// start/stop/restart commands (GUI or CLI), so a deliberate stop is never treated as a crash.
+
+pub const STATE_EVENT: &str = "gateway:state";
+
+const MAX_HISTORY: usize = 50;
+
+// How the supervisor talks to a gateway. `OpenclawGateway` is the real one; tests use fakes.
+pub trait GatewayControl: Send + Sync {
+  // Ok(true) when the gateway is up.
+  fn check(&self) -> Result<bool>;
+  fn start(&self) -> Result<()>;
+}
+
+pub struct OpenclawGateway {
+  st: Storage,
+  profile_id: String,
+  health_url: Option<String>,
+}
+
+impl OpenclawGateway {
+  pub fn new(st: Storage, profile_id: &str, health_url: Option<String>) -> Self {
+    Self { st, profile_id: profile_id.to_string(), health_url }
+  }
+}
+
+impl GatewayControl for OpenclawGateway {
+  fn check(&self) -> Result<bool> {
+    if let Some(url) = &self.health_url {
+      let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(5)).build()?;
+      return Ok(client.get(url).send().is_ok_and(|r| r.status().is_success()));
+    }
+    let out = crate::gateway::status(&self.st, &self.profile_id)?;
+    Ok(out.info.is_some_and(|i| i.state == crate::gateway::RunState::Running))
+  }
+
+  fn start(&self) -> Result<()> {
+    let opts = crate::openclaw_exec::RunOpts::timeout(crate::gateway::ACTION_TIMEOUT_SECS);
+    let out = crate::gateway::run_action(&self.st, &self.profile_id, "start", &opts)?;
+    if out.exit_code != 0 {
+      return Err(anyhow!("openclaw gateway start exited with {}: {}", out.exit_code, out.stderr.trim()));
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GatewayHealth {
+  // Not checked yet.
+  Unknown,
+  Running,
+  // Down and meant to be (stopped by the user, or never started).
+  Stopped,
+  // Down while meant to be running; a restart is scheduled.
+  Down,
+  Restarting,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartRecord {
+  pub at_ms: i64,
+  // 1 for the first restart after a crash, 2 for the next if that one didn't stick, ...
+  pub attempt: u32,
+  pub ok: bool,
+  pub error: Option<String>,
+}
+
+// profiles/{id}/gateway.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GatewayRecord {
+  // What the user last asked for. None until the first start/stop; the supervisor then
+  // takes the gateway's state at launch as intended.
+  #[serde(default)]
+  pub desired_running: Option<bool>,
+  #[serde(default)]
+  pub restarts: Vec<RestartRecord>,
+}
+
+fn record_path(dir: &Path) -> PathBuf {
+  dir.join("gateway.json")
+}
+
+pub fn load_record(dir: &Path) -> Result<GatewayRecord> {
+  let path = record_path(dir);
+  if !path.exists() {
+    return Ok(GatewayRecord::default());
+  }
+  let raw = fs::read_to_string(&path).context("failed to read gateway.json")?;
+  serde_json::from_str(&raw).context("failed to parse gateway.json")
+}
+
+fn update_record<F: FnOnce(&mut GatewayRecord)>(dir: &Path, f: F) -> Result<GatewayRecord> {
+  let _lock = DirLock::acquire(dir)?;
+  let mut r = load_record(dir)?;
+  f(&mut r);
+  let raw = serde_json::to_string_pretty(&r).context("failed to serialize gateway record")?;
+  write_atomic(&record_path(dir), raw).context("failed to write gateway.json")?;
+  Ok(r)
+}
+
+pub fn set_desired_in(dir: &Path, running: bool) -> Result<()> {
+  update_record(dir, |r| r.desired_running = Some(running)).map(|_| ())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewayState {
+  pub profile_id: String,
+  pub supervised: bool,
+  pub health: GatewayHealth,
+  pub since_ms: i64,
+  pub last_check_ms: Option<i64>,
+  // Last failed check or restart.
+  pub last_error: Option<String>,
+  pub next_restart_ms: Option<i64>,
+  pub restarts: Vec<RestartRecord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Timing {
+  pub poll: Duration,
+  pub backoff_base: Duration,
+  pub backoff_max: Duration,
+  // A gateway that stays up this long after a restart resets the backoff.
+  pub stable: Duration,
+}
+
+impl From<&GatewaySupervisorConfig> for Timing {
+  fn from(c: &GatewaySupervisorConfig) -> Self {
+    Self {
+      poll: Duration::from_secs(c.poll_secs),
+      backoff_base: Duration::from_secs(c.backoff_base_secs),
+      backoff_max: Duration::from_secs(c.backoff_max_secs),
+      stable: Duration::from_secs(60),
+    }
+  }
+}
+
+impl Timing {
+  fn backoff(&self, attempt: u32) -> Duration {
+    self.backoff_base.saturating_mul(2u32.saturating_pow(attempt)).min(self.backoff_max)
+  }
+}
+
+struct Inner {
+  state: GatewayState,
+  stop: bool,
+  // Set by `poke`; makes the loop check again without waiting for the poll interval.
+  poked: bool,
+}
+
+struct Shared {
+  inner: Mutex<Inner>,
+  wake: Condvar,
+}
+
+pub struct Supervisor {
+  shared: Arc<Shared>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl Supervisor {
+  // `dir` holds the profile's gateway.json.
+  pub fn spawn(profile_id: &str, dir: PathBuf, control: Arc<dyn GatewayControl>, sink: Arc<dyn EventSink>, timing: Timing) -> Self {
+    let restarts = load_record(&dir).map(|r| r.restarts).unwrap_or_default();
+    let shared = Arc::new(Shared {
+      inner: Mutex::new(Inner {
+        state: GatewayState {
+          profile_id: profile_id.to_string(),
+          supervised: true,
+          health: GatewayHealth::Unknown,
+          since_ms: crate::chats::now_ms(),
+          last_check_ms: None,
+          last_error: None,
+          next_restart_ms: None,
+          restarts,
+        },
+        stop: false,
+        poked: false,
+      }),
+      wake: Condvar::new(),
+    });
+    let worker = Worker { shared: shared.clone(), dir, control, sink, timing, attempt: 0, healthy_since: None, seen_up: false, next_restart: None };
+    let thread = std::thread::spawn(move || worker.run());
+    Self { shared, thread: Some(thread) }
+  }
+
+  pub fn state(&self) -> GatewayState {
+    self.shared.inner.lock().map(|i| i.state.clone()).unwrap_or_else(|p| p.into_inner().state.clone())
+  }
+
+  // Check now instead of at the next poll (after a start/stop).
+  pub fn poke(&self) {
+    if let Ok(mut i) = self.shared.inner.lock() {
+      i.poked = true;
+    }
+    self.shared.wake.notify_all();
+  }
+}
+
+impl Drop for Supervisor {
+  fn drop(&mut self) {
+    if let Ok(mut i) = self.shared.inner.lock() {
+      i.stop = true;
+    }
+    self.shared.wake.notify_all();
+    if let Some(t) = self.thread.take() {
+      let _ = t.join();
+    }
+  }
+}
+
+struct Worker {
+  shared: Arc<Shared>,
+  dir: PathBuf,
+  control: Arc<dyn GatewayControl>,
+  sink: Arc<dyn EventSink>,
+  timing: Timing,
+  // Restarts since the gateway was last stable.
+  attempt: u32,
+  healthy_since: Option<Instant>,
+  seen_up: bool,
+  next_restart: Option<Instant>,
+}
+
+impl Worker {
+  fn run(mut self) {
+    loop {
+      if self.shared.inner.lock().map(|i| i.stop).unwrap_or(true) {
+        return;
+      }
+      self.tick();
+
+      // Sleep until the next poll or the scheduled restart, whichever is first.
+      let mut wait = self.timing.poll;
+      if let Some(at) = self.next_restart {
+        wait = wait.min(at.saturating_duration_since(Instant::now()));
+      }
+      let Ok(guard) = self.shared.inner.lock() else { return };
+      let Ok((mut guard, _)) = self.shared.wake.wait_timeout_while(guard, wait, |i| !i.stop && !i.poked) else { return };
+      guard.poked = false;
+    }
+  }
+
+  // Applies `f` to the published state and emits it if anything changed. The event goes out after
+  // the lock is released: listeners run on this thread and may wait on things (the root lock, the
+  // main thread) whose holders wait on `inner` to stop this worker.
+  fn publish<F: FnOnce(&mut GatewayState)>(&self, f: F) {
+    let Ok(mut inner) = self.shared.inner.lock() else { return };
+    let before = serde_json::to_value(&inner.state).ok();
+    let prev_health = inner.state.health;
+    f(&mut inner.state);
+    if inner.state.health != prev_health {
+      inner.state.since_ms = crate::chats::now_ms();
+    }
+    let after = serde_json::to_value(&inner.state).ok();
+    // last_check_ms alone isn't worth an event.
+    let strip = |v: Option<serde_json::Value>| {
+      v.map(|mut v| {
+        v["last_check_ms"] = serde_json::Value::Null;
+        v["since_ms"] = serde_json::Value::Null;
+        v
+      })
+    };
+    if strip(before) != strip(after) {
+      let state = inner.state.clone();
+      drop(inner);
+      crate::core::emit(&*self.sink, STATE_EVENT, &state);
+    }
+  }
+
+  fn tick(&mut self) {
+    let checked = self.control.check();
+    let now_ms = crate::chats::now_ms();
+    let desired = load_record(&self.dir).ok().and_then(|r| r.desired_running);
+
+    match checked {
+      Ok(true) => {
+        self.next_restart = None;
+        self.seen_up = true;
+        let since = *self.healthy_since.get_or_insert_with(Instant::now);
+        if since.elapsed() >= self.timing.stable {
+          self.attempt = 0;
+        }
+        self.publish(|s| {
+          s.health = GatewayHealth::Running;
+          s.last_check_ms = Some(now_ms);
+          s.last_error = None;
+          s.next_restart_ms = None;
+        });
+      }
+      Ok(false) | Err(_) => {
+        let err = checked.err().map(|e| format!("{e:#}"));
+        self.healthy_since = None;
+        // Without a recorded intent, a gateway we've seen up is meant to be.
+        let meant_to_run = desired.unwrap_or(self.seen_up);
+        if !meant_to_run {
+          self.next_restart = None;
+          self.attempt = 0;
+          self.publish(|s| {
+            s.health = GatewayHealth::Stopped;
+            s.last_check_ms = Some(now_ms);
+            s.last_error = err;
+            s.next_restart_ms = None;
+          });
+          return;
+        }
+
+        let due = *self.next_restart.get_or_insert_with(|| Instant::now() + self.timing.backoff(self.attempt));
+        if Instant::now() < due {
+          let next_ms = now_ms + due.saturating_duration_since(Instant::now()).as_millis() as i64;
+          self.publish(|s| {
+            s.health = GatewayHealth::Down;
+            s.last_check_ms = Some(now_ms);
+            s.last_error = err;
+            s.next_restart_ms = Some(next_ms);
+          });
+          return;
+        }
+        self.restart();
+      }
+    }
+  }
+
+  fn restart(&mut self) {
+    self.next_restart = None;
+    self.attempt += 1;
+    self.publish(|s| {
+      s.health = GatewayHealth::Restarting;
+      s.next_restart_ms = None;
+    });
+
+    let res = self.control.start();
+    let record = RestartRecord {
+      at_ms: crate::chats::now_ms(),
+      attempt: self.attempt,
+      ok: res.is_ok(),
+      error: res.err().map(|e| format!("{e:#}")),
+    };
+    update_record(&self.dir, |r| {
+      r.restarts.push(record.clone());
+      let excess = r.restarts.len().saturating_sub(MAX_HISTORY);
+      r.restarts.drain(..excess);
+    })
+    .ok();
+
+    self.publish(|s| {
+      s.last_error = record.error.clone();
+      s.restarts.push(record);
+      let excess = s.restarts.len().saturating_sub(MAX_HISTORY);
+      s.restarts.drain(..excess);
+    });
+  }
+}
+
+// Running supervisors, by profile id. Dropping a Supervisor joins its worker, which can take as
+// long as a gateway action, so supervisors leave the map under the lock and are dropped after.
+static SUPERVISORS: Lazy<Mutex<HashMap<String, Supervisor>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Starts, restarts or stops the profile's supervisor to match its settings.
+pub fn supervise(st: &Storage, sink: Arc<dyn EventSink>, profile_id: &str) -> Result<()> {
+  let config = crate::settings::load_settings(st, profile_id)?.gateway_supervisor;
+  let old = SUPERVISORS.lock().map_err(|_| anyhow!("supervisor lock poisoned"))?.remove(profile_id);
+  drop(old);
+  if let Some(c) = config {
+    let control = Arc::new(OpenclawGateway::new(st.clone(), profile_id, c.health_url.clone()));
+    let sup = Supervisor::spawn(profile_id, st.profile_dir(profile_id)?, control, sink.clone(), Timing::from(&c));
+    // Another call may have started one meanwhile.
+    let replaced = SUPERVISORS.lock().map_err(|_| anyhow!("supervisor lock poisoned"))?.insert(profile_id.to_string(), sup);
+    drop(replaced);
+  } else {
+    crate::core::emit(&*sink, STATE_EVENT, &state(st, profile_id)?);
+  }
+  Ok(())
+}
+
+// App startup: supervises every profile that has it enabled. A profile that fails to start gets
+// a STATE_EVENT carrying the error.
+pub fn start_enabled(st: &Storage, sink: Arc<dyn EventSink>) {
+  let Ok(store) = crate::profiles::ensure_default_profile(st) else { return };
+  for p in store.profiles {
+    let enabled = crate::settings::load_settings(st, &p.id).is_ok_and(|s| s.gateway_supervisor.is_some());
+    if enabled {
+      if let Err(e) = supervise(st, sink.clone(), &p.id) {
+        if let Ok(mut s) = state(st, &p.id) {
+          s.last_error = Some(format!("supervisor didn't start: {e:#}"));
+          crate::core::emit(&*sink, STATE_EVENT, &s);
+        }
+      }
+    }
+  }
+}
+
+// Stops the profile's supervisor (the profile is going away); the gateway itself keeps running.
+pub fn stop(profile_id: &str) {
+  let old = SUPERVISORS.lock().ok().and_then(|mut sups| sups.remove(profile_id));
+  drop(old);
+}
+
+// Wakes the profile's supervisor, if one runs in this process.
+pub fn poke(profile_id: &str) {
+  if let Ok(sups) = SUPERVISORS.lock() {
+    if let Some(s) = sups.get(profile_id) {
+      s.poke();
+    }
+  }
+}
+
+pub fn state(st: &Storage, profile_id: &str) -> Result<GatewayState> {
+  if let Some(s) = SUPERVISORS.lock().ok().and_then(|sups| sups.get(profile_id).map(|s| s.state())) {
+    return Ok(s);
+  }
+  let record = load_record(&st.profile_dir(profile_id)?)?;
+  Ok(GatewayState {
+    profile_id: profile_id.to_string(),
+    supervised: false,
+    health: GatewayHealth::Unknown,
+    since_ms: crate::chats::now_ms(),
+    last_check_ms: None,
+    last_error: None,
+    next_restart_ms: None,
+    restarts: record.restarts,
+  })
+}
+
+#[tauri::command]
+pub fn gateway_state(storage: State<'_, Storage>, profile_id: String) -> Result<GatewayState, String> {
+  state(&storage, &profile_id).map_err(|e| e.to_string())
+}
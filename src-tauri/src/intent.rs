@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ollama::{OllamaChatReq, OllamaMessage, OllamaRole};
+
+// Decides whether auto-do mode should force tool use for a user message.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentMode {
+  Keywords,
+  Model,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentRule {
+  // Matched on word boundaries, case-insensitive. Multi-word phrases must appear contiguously.
+  pub phrase: String,
+  // Negation rules veto tool mode when they match (e.g. "what's the" in "what's the update on X").
+  #[serde(default)]
+  pub negate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentConfig {
+  pub mode: IntentMode,
+  #[serde(default)]
+  pub rules: Vec<IntentRule>,
+  // Model used in `model` mode; falls back to the profile's Ollama model.
+  #[serde(default)]
+  pub model: Option<String>,
+}
+
+impl Default for IntentConfig {
+  fn default() -> Self {
+    Self { mode: IntentMode::Keywords, rules: default_rules(), model: None }
+  }
+}
+
+pub fn default_rules() -> Vec<IntentRule> {
+  let action = [
+    "do it", "get it done", "fix", "install", "set up", "setup", "run", "execute", "create", "delete", "remove", "update",
+    "build", "deploy",
+  ];
+  let negate = [
+    "what's the", "what is the", "what are", "how does", "how do i", "why", "explain", "any update", "status of", "don't",
+    "dont", "do not",
+  ];
+  action
+    .iter()
+    .map(|p| IntentRule { phrase: p.to_string(), negate: false })
+    .chain(negate.iter().map(|p| IntentRule { phrase: p.to_string(), negate: true }))
+    .collect()
+}
+
+// Recorded on the user message so the UI can show why tools were (or weren't) forced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentDecision {
+  pub force_tools: bool,
+  pub method: String,
+  pub reason: String,
+}
+
+fn words(s: &str) -> Vec<String> {
+  s.to_lowercase()
+    .replace('\u{2019}', "'")
+    .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+    .filter(|w| !w.is_empty())
+    .map(|w| w.to_string())
+    .collect()
+}
+
+fn phrase_matches(text_words: &[String], phrase: &str) -> bool {
+  let p = words(phrase);
+  if p.is_empty() || p.len() > text_words.len() {
+    return false;
+  }
+  text_words.windows(p.len()).any(|w| w == p.as_slice())
+}
+
+pub fn classify_keywords(text: &str, rules: &[IntentRule]) -> IntentDecision {
+  let tw = words(text);
+
+  if let Some(r) = rules.iter().find(|r| r.negate && phrase_matches(&tw, &r.phrase)) {
+    return IntentDecision {
+      force_tools: false,
+      method: "keywords".to_string(),
+      reason: format!("negation matched: \"{}\"", r.phrase),
+    };
+  }
+
+  match rules.iter().find(|r| !r.negate && phrase_matches(&tw, &r.phrase)) {
+    Some(r) => IntentDecision {
+      force_tools: true,
+      method: "keywords".to_string(),
+      reason: format!("action phrase matched: \"{}\"", r.phrase),
+    },
+    None => IntentDecision {
+      force_tools: false,
+      method: "keywords".to_string(),
+      reason: "no action phrase matched".to_string(),
+    },
+  }
+}
+
+const CLASSIFIER_PROMPT: &str = "Classify the user's message. Reply with exactly one word:\nACTION - the user wants something done on their machine or the web (run, change, install, fetch).\nCHAT - the user asks a question, wants an explanation, or is chatting.";
+
+fn classify_model(base_url: &str, api_key: Option<&str>, model: &str, text: &str) -> anyhow::Result<IntentDecision> {
+  let resp = crate::ollama::chat(
+    base_url,
+    api_key,
+    OllamaChatReq {
+      model: model.to_string(),
+      messages: vec![
+        OllamaMessage { role: OllamaRole::System, content: CLASSIFIER_PROMPT.to_string() },
+        OllamaMessage { role: OllamaRole::User, content: text.to_string() },
+      ],
+      stream: false,
+      options: None,
+    },
+  )?;
+
+  let answer = resp.message.content.trim().to_uppercase();
+  let force_tools = if answer.contains("ACTION") {
+    true
+  } else if answer.contains("CHAT") {
+    false
+  } else {
+    return Err(anyhow::anyhow!("unexpected classifier answer: {}", resp.message.content.trim()));
+  };
+
+  Ok(IntentDecision {
+    force_tools,
+    method: "model".to_string(),
+    reason: format!("{model} answered {}", if force_tools { "ACTION" } else { "CHAT" }),
+  })
+}
+
+// Model mode degrades to keyword rules if the classifier call fails.
+pub fn classify(cfg: &IntentConfig, base_url: &str, api_key: Option<&str>, default_model: &str, text: &str) -> IntentDecision {
+  let rules = if cfg.rules.is_empty() { default_rules() } else { cfg.rules.clone() };
+  match cfg.mode {
+    IntentMode::Keywords => classify_keywords(text, &rules),
+    IntentMode::Model => {
+      let model = cfg.model.clone().unwrap_or_else(|| default_model.to_string());
+      let model = model.strip_prefix("ollama/").unwrap_or(&model).to_string();
+      match classify_model(base_url, api_key, &model, text) {
+        Ok(d) => d,
+        Err(e) => {
+          let mut d = classify_keywords(text, &rules);
+          d.method = "keywords_fallback".to_string();
+          d.reason = format!("{} (classifier failed: {e})", d.reason);
+          d
+        }
+      }
+    }
+  }
+}
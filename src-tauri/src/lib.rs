@@ -1,17 +1,39 @@
-mod chats;
-mod chat_stream;
-mod gateway;
-mod desktop_agent;
-mod models;
-mod ollama;
-mod tools;
+pub mod agent;
+pub mod api_server;
+pub mod chats;
+pub mod chat_crypto;
+pub mod chat_stream;
+pub mod cli_output;
+pub mod core;
+pub mod crypto;
+pub mod discovery;
+pub mod gateway;
+pub mod gateway_logs;
+pub mod gateway_supervisor;
+pub mod intent;
+pub mod memory;
+pub mod desktop_agent;
+pub mod models;
+pub mod ollama;
+pub mod tools;
+pub mod trace;
 mod openclaw;
-mod openclaw_exec;
-mod profiles;
-mod settings;
-mod state;
+pub mod openclaw_agents;
+pub mod openclaw_compat;
+pub mod openclaw_exec;
+pub mod openclaw_sessions;
+pub mod profile_bundle;
+pub mod profile_templates;
+pub mod profiles;
+pub mod prompts;
+pub mod secrets;
+pub mod settings;
+pub mod state;
+pub mod vault;
 mod tray;
 
+use tauri::Manager;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -21,29 +43,69 @@ pub fn run() {
             None,
         ))
         .setup(|app| {
+            let storage = core::Storage::from_app(app.handle())?;
             tray::init_tray(&app.handle())?;
+            api_server::start_enabled(&storage);
+            gateway_supervisor::start_enabled(&storage, std::sync::Arc::new(app.handle().clone()));
+            app.manage(storage);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             profiles::profiles_list,
             profiles::profiles_create,
+            profiles::profiles_clone,
+            profile_templates::profile_templates_list,
+            profile_templates::profile_templates_save,
+            profile_templates::profile_templates_delete,
             profiles::profiles_set_active,
             profiles::profiles_rename,
+            profiles::profiles_dismiss_migration_notes,
             profiles::profiles_delete,
-            profiles::secret_set,
-            profiles::secret_get,
-            profiles::secret_delete,
+            profiles::profiles_trash_list,
+            profiles::profiles_restore,
+            profiles::profiles_trash_purge,
+            profile_bundle::profile_export,
+            profile_bundle::profile_import,
+            secrets::secrets_list,
+            secrets::secret_set,
+            secrets::secret_set_meta,
+            secrets::secret_get,
+            secrets::secret_delete,
+            secrets::secret_providers,
+            secrets::secrets_backend_status,
+            secrets::secrets_migrate,
+            vault::secrets_vault_unlock,
+            vault::secrets_vault_lock,
             settings::settings_get,
             settings::settings_set_openclaw_path,
+            settings::settings_set_node_path,
+            settings::settings_set_chat_backend,
+            discovery::discovery_scan,
             settings::settings_set_ollama_base_url,
             settings::settings_set_ollama_model,
+            settings::settings_set_ollama_num_ctx,
             settings::settings_set_dev_full_exec_auto,
             settings::settings_set_auto_do_mode,
+            settings::settings_set_memory_inject,
+            settings::settings_set_system_prompt_template,
+            settings::settings_set_intent,
+            settings::settings_set_agent_limits,
+            settings::settings_set_api_server,
+            settings::settings_set_gateway_supervisor,
+            api_server::api_server_status,
+            api_server::api_server_start,
+            api_server::api_server_stop,
             gateway::gateway_status,
             gateway::gateway_start,
             gateway::gateway_stop,
             gateway::gateway_restart,
-            gateway::gateway_logs,
+            gateway_logs::gateway_logs,
+            gateway_logs::gateway_logs_follow,
+            gateway_logs::gateway_logs_unfollow,
+            gateway_logs::gateway_logs_save,
+            gateway_supervisor::gateway_state,
+            openclaw_exec::openclaw_cancel,
+            openclaw_compat::openclaw_compat,
             chats::chats_list,
             chats::chats_create,
             chats::chats_rename,
@@ -52,9 +114,36 @@ pub fn run() {
             chats::chat_reset,
             chats::chat_send,
             chats::chat_send_stream,
+            chats::chat_continue,
+            trace::chat_trace,
             chats::chats_update,
+            chats::chats_set_prompt_preset,
+            chats::chats_set_backend,
+            chats::chats_set_agent,
+            chats::chats_attach_session,
+            chat_crypto::chats_encryption_status,
+            chat_crypto::chats_encryption_enable,
+            chat_crypto::chats_encryption_disable,
+            chat_crypto::chats_encryption_rotate,
+            chat_crypto::chats_encryption_unlock,
+            chat_crypto::chats_encryption_lock,
+            openclaw_sessions::openclaw_sessions_list,
+            openclaw_sessions::openclaw_sessions_prune,
+            openclaw_sessions::openclaw_sessions_clear_locks,
+            openclaw_agents::openclaw_agents_list,
+            openclaw_agents::openclaw_agents_create,
+            openclaw_agents::openclaw_agents_update,
+            openclaw_agents::openclaw_agents_delete,
             models::models_status,
             models::models_set_default,
+            memory::memory_list,
+            memory::memory_add,
+            memory::memory_update,
+            memory::memory_delete,
+            prompts::prompts_list,
+            prompts::prompts_save,
+            prompts::prompts_delete,
+            prompts::prompts_preview,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
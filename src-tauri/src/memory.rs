@@ -0,0 +1,260 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::core::{write_atomic, DirLock, Storage};
+
+// Memories live as dated bullet lines in the profile workspace's MEMORY.md:
+//
+//   - [2026-02-08] (mem_1770000000000) prefers pnpm over npm
+//
+// Everything that isn't an entry line (the heading, free-form notes) is kept as-is.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryEntry {
+  pub id: String,
+  pub date: String,
+  pub text: String,
+}
+
+fn now_ms() -> i64 {
+  let dur = std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .unwrap_or_default();
+  dur.as_millis() as i64
+}
+
+// Civil date (UTC) from unix millis, without pulling in a date crate.
+pub fn date_from_ms(ms: i64) -> String {
+  let days = ms.div_euclid(86_400_000);
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z - era * 146_097;
+  let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let d = doy - (153 * mp + 2) / 5 + 1;
+  let m = if mp < 10 { mp + 3 } else { mp - 9 };
+  let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+  format!("{y:04}-{m:02}-{d:02}")
+}
+
+pub fn today() -> String {
+  date_from_ms(now_ms())
+}
+
+pub fn memory_path(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  let dir = crate::desktop_agent::ensure_minimal_workspace(st, profile_id)?;
+  Ok(dir.join("MEMORY.md"))
+}
+
+fn parse_entry(line: &str) -> Option<MemoryEntry> {
+  let rest = line.trim_start().strip_prefix("- [")?;
+  let (date, rest) = rest.split_once(']')?;
+  let rest = rest.trim_start().strip_prefix('(')?;
+  let (id, text) = rest.split_once(')')?;
+  if !id.starts_with("mem_") {
+    return None;
+  }
+  Some(MemoryEntry {
+    id: id.to_string(),
+    date: date.trim().to_string(),
+    text: text.trim().to_string(),
+  })
+}
+
+fn format_entry(e: &MemoryEntry) -> String {
+  format!("- [{}] ({}) {}", e.date, e.id, e.text)
+}
+
+// Memory text must stay on a single line to round-trip through MEMORY.md.
+fn clean_text(text: &str) -> Result<String> {
+  let t = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  if t.is_empty() {
+    return Err(anyhow!("memory text required"));
+  }
+  Ok(t)
+}
+
+fn read_raw(path: &Path) -> Result<String> {
+  if !path.exists() {
+    return Ok("# MEMORY\n".to_string());
+  }
+  fs::read_to_string(path).context("failed to read MEMORY.md")
+}
+
+pub fn load_entries(path: &Path) -> Result<Vec<MemoryEntry>> {
+  let raw = read_raw(path)?;
+  Ok(raw.lines().filter_map(parse_entry).collect())
+}
+
+// Rewrites MEMORY.md, keeping non-entry lines and applying `f` to the entry list.
+fn rewrite<F>(path: &Path, f: F) -> Result<Vec<MemoryEntry>>
+where
+  F: FnOnce(&mut Vec<MemoryEntry>) -> Result<()>,
+{
+  let _lock = match path.parent() {
+    Some(dir) => Some(DirLock::acquire(dir)?),
+    None => None,
+  };
+  let raw = read_raw(path)?;
+  let mut preamble: Vec<&str> = vec![];
+  let mut entries: Vec<MemoryEntry> = vec![];
+  for line in raw.lines() {
+    match parse_entry(line) {
+      Some(e) => entries.push(e),
+      None => preamble.push(line),
+    }
+  }
+  while preamble.last().is_some_and(|l| l.trim().is_empty()) {
+    preamble.pop();
+  }
+
+  f(&mut entries)?;
+
+  let mut out = preamble.join("\n");
+  out.push_str("\n\n");
+  for e in &entries {
+    out.push_str(&format_entry(e));
+    out.push('\n');
+  }
+  write_atomic(path, out).context("failed to write MEMORY.md")?;
+  Ok(entries)
+}
+
+pub fn remember(path: &Path, text: &str) -> Result<MemoryEntry> {
+  let text = clean_text(text)?;
+  let mut added: Option<MemoryEntry> = None;
+  rewrite(path, |entries| {
+    let mut ms = now_ms();
+    while entries.iter().any(|e| e.id == format!("mem_{ms}")) {
+      ms += 1;
+    }
+    let e = MemoryEntry { id: format!("mem_{ms}"), date: today(), text };
+    entries.push(e.clone());
+    added = Some(e);
+    Ok(())
+  })?;
+  added.ok_or_else(|| anyhow!("memory not saved"))
+}
+
+pub fn recall(path: &Path, query: &str, limit: usize) -> Result<Vec<MemoryEntry>> {
+  let entries = load_entries(path)?;
+  let words: Vec<String> = query
+    .split_whitespace()
+    .map(|w| w.to_lowercase())
+    .collect();
+
+  if words.is_empty() {
+    return Ok(entries.into_iter().rev().take(limit).collect());
+  }
+
+  let mut scored: Vec<(usize, MemoryEntry)> = entries
+    .into_iter()
+    .filter_map(|e| {
+      let t = e.text.to_lowercase();
+      let score = words.iter().filter(|w| t.contains(w.as_str())).count();
+      if score > 0 { Some((score, e)) } else { None }
+    })
+    .collect();
+  // Best match first; newer entries win ties.
+  scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.id.cmp(&a.1.id)));
+  Ok(scored.into_iter().take(limit).map(|(_, e)| e).collect())
+}
+
+pub fn update(path: &Path, memory_id: &str, text: &str) -> Result<Vec<MemoryEntry>> {
+  let text = clean_text(text)?;
+  rewrite(path, |entries| {
+    let e = entries
+      .iter_mut()
+      .find(|e| e.id == memory_id)
+      .ok_or_else(|| anyhow!("memory not found"))?;
+    e.text = text;
+    Ok(())
+  })
+}
+
+pub fn forget(path: &Path, memory_id: &str) -> Result<Vec<MemoryEntry>> {
+  rewrite(path, |entries| {
+    let before = entries.len();
+    entries.retain(|e| e.id != memory_id);
+    if entries.len() == before {
+      return Err(anyhow!("memory not found"));
+    }
+    Ok(())
+  })
+}
+
+// Block injected into the system prompt when memory injection is enabled.
+pub fn prompt_block(path: &Path) -> Option<String> {
+  let entries = load_entries(path).ok()?;
+  if entries.is_empty() {
+    return None;
+  }
+  let mut s = String::from("MEMORY (facts saved in earlier chats):\n");
+  for e in entries.iter().rev().take(50).rev() {
+    s.push_str(&format_entry(e));
+    s.push('\n');
+  }
+  Some(s)
+}
+
+fn describe(entries: &[MemoryEntry]) -> String {
+  if entries.is_empty() {
+    return "(none)".to_string();
+  }
+  entries.iter().map(format_entry).collect::<Vec<_>>().join("\n")
+}
+
+// Executes a memory tool call on behalf of the model and returns the tool result text.
+pub fn run_tool(path: &Path, call: &crate::tools::ToolCall) -> String {
+  use crate::tools::ToolCall;
+
+  let res = match call {
+    ToolCall::Remember { text } => remember(path, text).map(|e| format!("saved:\n{}", format_entry(&e))),
+    ToolCall::Recall { query } => recall(path, query, 10).map(|es| describe(&es)),
+    ToolCall::Forget { id } if id.trim().is_empty() => Err(anyhow!("forget needs a memory id (use recall to find it)")),
+    ToolCall::Forget { id } => forget(path, id.trim()).map(|_| format!("removed {}", id.trim())),
+    _ => Err(anyhow!("not a memory tool")),
+  };
+
+  res.unwrap_or_else(|e| format!("[tool_error] {e}"))
+}
+
+// Prompt block for a profile, honoring the memory_inject setting.
+pub fn prompt_block_for(st: &Storage, profile_id: &str, settings: &crate::settings::ProfileSettings) -> Option<String> {
+  if !settings.memory_inject.unwrap_or(false) {
+    return None;
+  }
+  memory_path(st, profile_id).ok().and_then(|p| prompt_block(&p))
+}
+
+#[tauri::command]
+pub fn memory_list(storage: State<'_, Storage>, profile_id: String) -> Result<Vec<MemoryEntry>, String> {
+  let path = memory_path(&storage, &profile_id).map_err(|e| e.to_string())?;
+  load_entries(&path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn memory_add(storage: State<'_, Storage>, profile_id: String, text: String) -> Result<Vec<MemoryEntry>, String> {
+  let path = memory_path(&storage, &profile_id).map_err(|e| e.to_string())?;
+  remember(&path, &text).map_err(|e| e.to_string())?;
+  load_entries(&path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn memory_update(storage: State<'_, Storage>, profile_id: String, memory_id: String, text: String) -> Result<Vec<MemoryEntry>, String> {
+  let path = memory_path(&storage, &profile_id).map_err(|e| e.to_string())?;
+  update(&path, &memory_id, &text).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn memory_delete(storage: State<'_, Storage>, profile_id: String, memory_id: String) -> Result<Vec<MemoryEntry>, String> {
+  let path = memory_path(&storage, &profile_id).map_err(|e| e.to_string())?;
+  forget(&path, &memory_id).map_err(|e| e.to_string())
+}
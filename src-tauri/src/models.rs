@@ -1,14 +1,25 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Result};
 use serde::Serialize;
-use tauri::AppHandle;
+use serde_json::Value;
+use tauri::State;
 
-fn run_openclaw(app: &AppHandle, bin: std::path::PathBuf, args: Vec<String>) -> Result<(i32, String, String)> {
-  let out = crate::openclaw_exec::run_openclaw(app, bin, args.clone())
-    .with_context(|| format!("failed to run openclaw {}", args.join(" ")))?;
-  let code = out.status.code().unwrap_or(-1);
-  let stdout = String::from_utf8_lossy(&out.stdout).to_string();
-  let stderr = String::from_utf8_lossy(&out.stderr).to_string();
-  Ok((code, stdout, stderr))
+use crate::cli_output::{as_text, find, json_document};
+use crate::core::Storage;
+
+// `models status` may probe providers over the network.
+const TIMEOUT_SECS: u64 = 45;
+
+fn run_openclaw(st: &Storage, profile_id: &str, args: Vec<String>) -> Result<(i32, String, String)> {
+  crate::openclaw_compat::caps(st, profile_id)?;
+  let bin = crate::settings::resolve_openclaw_bin(st, profile_id)?;
+  let opts = crate::openclaw_exec::RunOpts {
+    node: crate::discovery::pinned_node(st, profile_id)?,
+    // `models status` checks the providers' keys.
+    env: crate::secrets::openclaw_env(st, profile_id),
+    ..crate::openclaw_exec::RunOpts::timeout(TIMEOUT_SECS)
+  };
+  let out = crate::openclaw_exec::run(bin, args, &opts)?;
+  Ok((out.code, out.stdout, out.stderr))
 }
 
 #[derive(Debug, Serialize)]
@@ -16,22 +27,196 @@ pub struct ModelsStatus {
   pub exit_code: i32,
   pub stdout: String,
   pub stderr: String,
+  // Parsed `models status` output; None for `models set`.
+  pub info: Option<ModelsInfo>,
 }
 
-fn prefix_args(app: &AppHandle, profile_id: &str) -> Result<Vec<String>> {
-  let bin = crate::settings::resolve_openclaw_bin(app, profile_id)?;
-  let prof = crate::settings::resolve_openclaw_profile(app, profile_id)?;
-  Ok(vec![
-    bin.to_string_lossy().to_string(),
-    "--profile".into(),
-    prof,
-  ])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthState {
+  Ok,
+  Missing,
+  Expired,
+  Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderStatus {
+  pub id: String,
+  pub auth: AuthState,
+  // e.g. "api key", "oauth", "env: OPENAI_API_KEY"
+  pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelsInfo {
+  pub default_model: Option<String>,
+  pub fallbacks: Vec<String>,
+  pub providers: Vec<ProviderStatus>,
+  // "json" or "text": which parser produced this.
+  pub source: String,
+}
+
+fn auth_word(s: &str) -> AuthState {
+  let s = s.to_lowercase();
+  if s.contains("expired") {
+    AuthState::Expired
+  } else if ["missing", "none", "unset", "not configured", "no key", "unauthenticated", "invalid", "no auth", "not set"].iter().any(|w| s.contains(w)) {
+    AuthState::Missing
+  } else if ["ok", "valid", "configured", "authenticated", "set", "present", "api key", "oauth", "token", "env", "✓"].iter().any(|w| s.contains(w)) {
+    AuthState::Ok
+  } else {
+    AuthState::Unknown
+  }
+}
+
+fn provider_from(id: &str, v: &Value) -> ProviderStatus {
+  let detail = find(v, &["source", "method", "kind", "type", "detail"]).and_then(as_text);
+  let auth = match v {
+    Value::Bool(b) => if *b { AuthState::Ok } else { AuthState::Missing },
+    Value::String(s) => auth_word(s),
+    Value::Object(_) => {
+      if find(v, &["expired"]) == Some(&Value::Bool(true)) {
+        AuthState::Expired
+      } else if let Some(Value::Bool(b)) = find(v, &["ok", "authenticated", "configured", "hasKey", "hasAuth", "valid"]) {
+        if *b { AuthState::Ok } else { AuthState::Missing }
+      } else {
+        find(v, &["auth", "status", "state", "authStatus"]).and_then(as_text).map(|s| auth_word(&s)).unwrap_or(AuthState::Unknown)
+      }
+    }
+    _ => AuthState::Unknown,
+  };
+  let detail = detail.or_else(|| v.as_str().filter(|_| auth == AuthState::Ok).map(String::from));
+  ProviderStatus { id: id.to_string(), auth, detail }
+}
+
+// `models status --json`.
+pub fn parse_status_json(v: &Value) -> ModelsInfo {
+  let default_model = find(v, &["defaultModel", "default", "primary", "model"]).and_then(|d| match d {
+    Value::Object(_) => find(d, &["primary", "id", "model", "name"]).and_then(as_text),
+    other => as_text(other),
+  });
+  let fallbacks = find(v, &["fallbacks", "fallbackModels"])
+    .and_then(Value::as_array)
+    .map(|a| a.iter().filter_map(as_text).collect())
+    .unwrap_or_default();
+
+  let providers = match find(v, &["providers", "auth", "authProviders", "providerAuth"]) {
+    Some(Value::Array(items)) => items
+      .iter()
+      .filter_map(|item| match item {
+        Value::String(id) => Some(ProviderStatus { id: id.clone(), auth: AuthState::Unknown, detail: None }),
+        Value::Object(_) => find(item, &["provider", "id", "name"]).and_then(as_text).map(|id| provider_from(&id, item)),
+        _ => None,
+      })
+      .collect(),
+    Some(Value::Object(map)) => map.iter().map(|(id, x)| provider_from(id, x)).collect(),
+    _ => vec![],
+  };
+
+  ModelsInfo { default_model, fallbacks, providers, source: "json".to_string() }
 }
 
-#[tauri::command]
-pub fn models_status(app: AppHandle, profile_id: String) -> Result<ModelsStatus, String> {
-  let bin = crate::settings::resolve_openclaw_bin(&app, &profile_id).map_err(|e| e.to_string())?;
-  let prof = crate::settings::resolve_openclaw_profile(&app, &profile_id).map_err(|e| e.to_string())?;
+// Plain `models status` output: "Default model: ...", "Fallbacks: a, b", an "Auth:" section with one
+// "provider: state" line per provider, or `key=value` pairs (default=..., auth.openai=ok).
+pub fn parse_status_text(text: &str) -> ModelsInfo {
+  let mut info = ModelsInfo { default_model: None, fallbacks: vec![], providers: vec![], source: "text".to_string() };
+  let upsert = |providers: &mut Vec<ProviderStatus>, id: &str, status: &str| {
+    let id = id.trim().trim_matches(['-', '*', '•', ' ']).to_string();
+    if id.is_empty() || id.contains(' ') {
+      return;
+    }
+    let status = status.trim();
+    let p = ProviderStatus {
+      id: id.clone(),
+      auth: if status.is_empty() { AuthState::Unknown } else { auth_word(status) },
+      detail: status.split_once(['(', '—', '-']).map(|(_, d)| d.trim().trim_end_matches(')').to_string()).filter(|d| !d.is_empty()),
+    };
+    match providers.iter_mut().find(|x| x.id == id) {
+      Some(existing) if p.auth != AuthState::Unknown => *existing = p,
+      Some(_) => {}
+      None => providers.push(p),
+    }
+  };
+
+  let mut in_section = false;
+  for raw in text.lines() {
+    let line = raw.trim();
+    if line.is_empty() {
+      continue;
+    }
+    let indented = raw.starts_with([' ', '\t']) || line.starts_with(['-', '*', '•']);
+
+    if line.contains('=') && !line.contains(": ") {
+      for (k, v) in line.split_whitespace().filter_map(|t| t.split_once('=')) {
+        let k = k.to_lowercase();
+        if k.starts_with("default") {
+          info.default_model = Some(v.to_string()).filter(|v| !v.is_empty());
+        } else if k.starts_with("fallback") {
+          info.fallbacks = v.split(',').map(str::trim).filter(|x| !x.is_empty()).map(String::from).collect();
+        } else if let Some(id) = k.strip_prefix("auth.").or_else(|| k.strip_prefix("provider.")) {
+          upsert(&mut info.providers, id, v);
+        }
+      }
+      continue;
+    }
+
+    if in_section && indented {
+      match line.split_once(':') {
+        Some((id, status)) => upsert(&mut info.providers, id, status),
+        None => {
+          let mut parts = line.trim_start_matches(['-', '*', '•', ' ']).splitn(2, char::is_whitespace);
+          let id = parts.next().unwrap_or_default().to_string();
+          upsert(&mut info.providers, &id, parts.next().unwrap_or_default());
+        }
+      }
+      continue;
+    }
+    in_section = false;
+
+    let Some((key, value)) = line.split_once(':') else { continue };
+    let key = key.to_lowercase();
+    let value = value.trim();
+    if key.contains("default") {
+      info.default_model = value.split_whitespace().next().map(String::from);
+    } else if key.contains("fallback") {
+      info.fallbacks = value
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|x| !x.is_empty() && *x != "(none)" && *x != "none")
+        .map(String::from)
+        .collect();
+    } else if key.contains("auth") || key.contains("provider") {
+      if value.is_empty() {
+        in_section = true;
+      } else {
+        for id in value.split(',') {
+          upsert(&mut info.providers, id, "");
+        }
+      }
+    }
+  }
+  info
+}
+
+pub fn status(st: &Storage, profile_id: &str) -> Result<ModelsStatus> {
+  let prof = crate::settings::resolve_openclaw_profile(st, profile_id)?;
+  if crate::openclaw_compat::caps(st, profile_id)?.models_status_json {
+    let args: Vec<String> = vec![
+      "--profile".into(),
+      prof.clone(),
+      "models".into(),
+      "status".into(),
+      "--json".into(),
+    ];
+    let (code, stdout, stderr) = run_openclaw(st, profile_id, args)?;
+    if let Some(v) = json_document(&stdout) {
+      let info = parse_status_json(&v);
+      return Ok(ModelsStatus { exit_code: code, stdout, stderr, info: Some(info) });
+    }
+  }
+
+  // Releases without `--json`.
   let args: Vec<String> = vec![
     "--profile".into(),
     prof,
@@ -39,17 +224,16 @@ pub fn models_status(app: AppHandle, profile_id: String) -> Result<ModelsStatus,
     "status".into(),
     "--status-plain".into(),
   ];
-  let (code, stdout, stderr) = run_openclaw(&app, bin, args).map_err(|e| e.to_string())?;
-  Ok(ModelsStatus { exit_code: code, stdout, stderr })
+  let (code, stdout, stderr) = run_openclaw(st, profile_id, args)?;
+  let info = parse_status_text(&stdout);
+  Ok(ModelsStatus { exit_code: code, stdout, stderr, info: Some(info) })
 }
 
-#[tauri::command]
-pub fn models_set_default(app: AppHandle, profile_id: String, model: String) -> Result<ModelsStatus, String> {
-  let bin = crate::settings::resolve_openclaw_bin(&app, &profile_id).map_err(|e| e.to_string())?;
-  let prof = crate::settings::resolve_openclaw_profile(&app, &profile_id).map_err(|e| e.to_string())?;
+pub fn set_default(st: &Storage, profile_id: &str, model: &str) -> Result<ModelsStatus> {
+  let prof = crate::settings::resolve_openclaw_profile(st, profile_id)?;
   let model = model.trim().to_string();
   if model.is_empty() {
-    return Err("model required".into());
+    return Err(anyhow!("model required"));
   }
   let args: Vec<String> = vec![
     "--profile".into(),
@@ -58,6 +242,16 @@ pub fn models_set_default(app: AppHandle, profile_id: String, model: String) ->
     "set".into(),
     model,
   ];
-  let (code, stdout, stderr) = run_openclaw(&app, bin, args).map_err(|e| e.to_string())?;
-  Ok(ModelsStatus { exit_code: code, stdout, stderr })
+  let (code, stdout, stderr) = run_openclaw(st, profile_id, args)?;
+  Ok(ModelsStatus { exit_code: code, stdout, stderr, info: None })
+}
+
+#[tauri::command(async)]
+pub fn models_status(storage: State<'_, Storage>, profile_id: String) -> Result<ModelsStatus, String> {
+  status(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn models_set_default(storage: State<'_, Storage>, profile_id: String, model: String) -> Result<ModelsStatus, String> {
+  set_default(&storage, &profile_id, &model).map_err(|e| e.to_string())
 }
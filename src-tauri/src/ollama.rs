@@ -17,12 +17,22 @@ pub struct OllamaMessage {
   pub content: String,
 }
 
+// Model parameters; unset ones keep the model's defaults.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OllamaOptions {
+  // Context window, in tokens.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub num_ctx: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct OllamaChatReq {
   pub model: String,
   pub messages: Vec<OllamaMessage>,
   #[serde(default)]
   pub stream: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub options: Option<OllamaOptions>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -36,16 +46,20 @@ pub struct OllamaChatStreamResp {
   pub done: Option<bool>,
 }
 
-pub fn chat(base_url: &str, req: OllamaChatReq) -> Result<OllamaChatResp> {
+// `api_key` is sent as a bearer token, for Ollama servers behind auth (the profile's "ollama"
+// provider secret).
+pub fn chat(base_url: &str, api_key: Option<&str>, req: OllamaChatReq) -> Result<OllamaChatResp> {
   let url = format!("{}/api/chat", base_url.trim_end_matches('/'));
   let client = Client::builder()
     .timeout(std::time::Duration::from_secs(600))
     .build()
     .context("failed to build http client")?;
 
-  let resp = client
-    .post(&url)
-    .json(&req)
+  let mut post = client.post(&url).json(&req);
+  if let Some(key) = api_key {
+    post = post.bearer_auth(key);
+  }
+  let resp = post
     .send()
     .with_context(|| format!("ollama /api/chat request failed ({url})"))?;
 
@@ -58,7 +72,7 @@ pub fn chat(base_url: &str, req: OllamaChatReq) -> Result<OllamaChatResp> {
   resp.json::<OllamaChatResp>().context("failed to parse ollama response")
 }
 
-pub fn chat_stream<F>(base_url: &str, req: OllamaChatReq, mut on_event: F) -> Result<()>
+pub fn chat_stream<F>(base_url: &str, api_key: Option<&str>, req: OllamaChatReq, mut on_event: F) -> Result<()>
 where
   F: FnMut(String, bool) -> Result<()>,
 {
@@ -68,9 +82,11 @@ where
     .build()
     .context("failed to build http client")?;
 
-  let resp = client
-    .post(&url)
-    .json(&req)
+  let mut post = client.post(&url).json(&req);
+  if let Some(key) = api_key {
+    post = post.bearer_auth(key);
+  }
+  let resp = post
     .send()
     .with_context(|| format!("ollama /api/chat request failed ({url})"))?;
 
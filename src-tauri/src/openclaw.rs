@@ -1,16 +1,17 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use tauri::AppHandle;
+
+use crate::core::Storage;
 
 pub struct Openclaw {
   pub bin: PathBuf,
   pub profile: String,
 }
 
-pub fn resolve(app: &AppHandle, profile_id: &str) -> Result<Openclaw> {
-  let bin = crate::settings::resolve_openclaw_bin(app, profile_id)?;
-  let profile = crate::settings::resolve_openclaw_profile(app, profile_id)?;
+pub fn resolve(st: &Storage, profile_id: &str) -> Result<Openclaw> {
+  let bin = crate::settings::resolve_openclaw_bin(st, profile_id)?;
+  let profile = crate::settings::resolve_openclaw_profile(st, profile_id)?;
   Ok(Openclaw { bin, profile })
 }
 
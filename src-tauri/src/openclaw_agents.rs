@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::core::Storage;
+use crate::openclaw_compat::Caps;
+use crate::openclaw_exec::{ExecOutput, RunOpts};
+
+// OpenClaw agents of a profile's OpenClaw profile (`--profile ocd-...`): listing, creating,
+// updating and deleting them, and the default agent chats fall back to.
+
+const TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct AgentInfo {
+  pub id: String,
+  pub name: Option<String>,
+  pub workspace: Option<String>,
+  pub model: Option<String>,
+  // The agent OpenClaw uses when none is named.
+  pub is_default: bool,
+}
+
+// Agent ids as OpenClaw accepts them: 1-64 of lowercase letters, digits, `-` and `_`, starting with
+// a letter or digit.
+pub fn validate_id(id: &str) -> Result<()> {
+  let ok = !id.is_empty()
+    && id.len() <= 64
+    && id.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+    && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+  if ok {
+    Ok(())
+  } else {
+    Err(anyhow!("invalid agent id {id:?}: use 1-64 lowercase letters, digits, '-' or '_', starting with a letter or digit"))
+  }
+}
+
+// `model` is a string in older releases and `{ "primary": ... }` in newer ones.
+fn model_name(v: &Value) -> Option<String> {
+  match v {
+    Value::String(s) => Some(s.clone()),
+    Value::Object(_) => crate::cli_output::find(v, &["primary"]).and_then(|x| x.as_str()).map(String::from),
+    _ => None,
+  }
+}
+
+// Agents from `agents list --json`: a bare array or `{ "agents": [...] }`.
+pub fn parse_list(stdout: &str) -> Result<Vec<AgentInfo>> {
+  let doc = crate::cli_output::json_document(stdout).ok_or_else(|| anyhow!("openclaw agents list printed no JSON"))?;
+  let items = match &doc {
+    Value::Array(items) => items,
+    _ => doc.get("agents").and_then(|a| a.as_array()).ok_or_else(|| anyhow!("unexpected openclaw agents list output"))?,
+  };
+  let field = |a: &Value, keys: &[&str]| crate::cli_output::find(a, keys).and_then(|x| x.as_str()).map(String::from);
+  Ok(
+    items
+      .iter()
+      .filter_map(|a| {
+        Some(AgentInfo {
+          id: a.get("id")?.as_str()?.to_string(),
+          name: a.get("name").and_then(|x| x.as_str()).map(String::from),
+          workspace: field(a, &["workspace", "workspaceDir"]),
+          model: a.get("model").and_then(model_name),
+          is_default: a.get("isDefault").or_else(|| a.get("default")).and_then(|x| x.as_bool()).unwrap_or(false),
+        })
+      })
+      .collect(),
+  )
+}
+
+// The openclaw binary, OpenClaw profile and node a profile's agent commands run with.
+pub(crate) struct AgentCli {
+  pub bin: PathBuf,
+  pub openclaw_profile: String,
+  pub caps: Caps,
+  pub node: Option<PathBuf>,
+  // Provider keys and other mapped secrets (secrets::openclaw_env).
+  pub env: Vec<(String, String)>,
+}
+
+impl AgentCli {
+  pub fn for_profile(st: &Storage, profile_id: &str) -> Result<Self> {
+    Ok(Self {
+      bin: crate::settings::resolve_openclaw_bin(st, profile_id)?,
+      openclaw_profile: crate::settings::resolve_openclaw_profile(st, profile_id)?,
+      caps: crate::openclaw_compat::caps(st, profile_id)?,
+      node: crate::discovery::pinned_node(st, profile_id)?,
+      env: crate::secrets::openclaw_env(st, profile_id),
+    })
+  }
+
+  fn run(&self, args: &[&str]) -> Result<ExecOutput> {
+    let mut full: Vec<String> = vec!["--profile".into(), self.openclaw_profile.clone()];
+    full.extend(args.iter().map(|a| a.to_string()));
+    let opts = RunOpts { node: self.node.clone(), env: self.env.clone(), ..RunOpts::timeout(TIMEOUT_SECS) };
+    Ok(crate::openclaw_exec::run(self.bin.clone(), full, &opts)?.success()?)
+  }
+
+  pub fn list(&self) -> Result<Vec<AgentInfo>> {
+    parse_list(&self.run(&["agents", "list", "--json"])?.stdout)
+  }
+
+  pub fn add(&self, id: &str, workspace: &Path, model: &str) -> Result<()> {
+    if !self.caps.agents_non_interactive {
+      return Err(anyhow!(
+        "creating agent {id} needs OpenClaw {} or newer. {}",
+        crate::openclaw_compat::AGENTS_NON_INTERACTIVE,
+        crate::openclaw_compat::UPGRADE_HINT
+      ));
+    }
+    let ws = workspace.to_string_lossy();
+    self.run(&["agents", "add", "--non-interactive", "--workspace", &ws, "--model", model, id]).with_context(|| format!("failed to create agent {id}"))?;
+    Ok(())
+  }
+
+  // Creates `id` with `model` in the profile's minimal workspace unless it already exists.
+  pub fn ensure(&self, st: &Storage, profile_id: &str, id: &str, model: &str) -> Result<()> {
+    if self.list()?.iter().any(|a| a.id == id) {
+      return Ok(());
+    }
+    let ws = crate::desktop_agent::ensure_minimal_workspace(st, profile_id)?;
+    self.add(id, &ws, model)
+  }
+}
+
+// A model from the request, else the profile's Ollama model.
+fn model_or_default(st: &Storage, profile_id: &str, model: Option<String>) -> Result<String> {
+  model
+    .map(|m| m.trim().to_string())
+    .filter(|m| !m.is_empty())
+    .or_else(|| crate::settings::load_settings(st, profile_id).ok().and_then(|s| s.ollama_model))
+    .ok_or_else(|| anyhow!("no model given and none set for this profile"))
+}
+
+pub fn list(st: &Storage, profile_id: &str) -> Result<Vec<AgentInfo>> {
+  AgentCli::for_profile(st, profile_id)?.list()
+}
+
+// Creates an agent. `workspace` defaults to the profile's minimal workspace and `model` to the
+// profile's model.
+pub fn create(st: &Storage, profile_id: &str, id: &str, workspace: Option<String>, model: Option<String>) -> Result<Vec<AgentInfo>> {
+  validate_id(id)?;
+  let cli = AgentCli::for_profile(st, profile_id)?;
+  if cli.list()?.iter().any(|a| a.id == id) {
+    return Err(anyhow!("agent {id} already exists"));
+  }
+  let ws = match workspace.map(|w| w.trim().to_string()).filter(|w| !w.is_empty()) {
+    Some(w) => PathBuf::from(w),
+    None => crate::desktop_agent::ensure_minimal_workspace(st, profile_id)?,
+  };
+  cli.add(id, &ws, &model_or_default(st, profile_id, model)?)?;
+  cli.list()
+}
+
+// Changes an agent's workspace and/or model in the OpenClaw config.
+pub fn update(st: &Storage, profile_id: &str, id: &str, workspace: Option<String>, model: Option<String>) -> Result<Vec<AgentInfo>> {
+  validate_id(id)?;
+  let cli = AgentCli::for_profile(st, profile_id)?;
+  // `config set` addresses agents by their position in `agents.list`.
+  let out = cli.run(&["config", "get", "agents.list", "--json"])?;
+  let entries = crate::cli_output::json_document(&out.stdout).and_then(|v| v.as_array().cloned()).unwrap_or_default();
+  let idx = entries
+    .iter()
+    .position(|a| a.get("id").and_then(|x| x.as_str()) == Some(id))
+    .ok_or_else(|| anyhow!("agent {id} not found in the OpenClaw config"))?;
+  let changes = [("workspace", workspace), ("model", model)];
+  for (key, value) in changes {
+    if let Some(v) = value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+      cli.run(&["config", "set", &format!("agents.list[{idx}].{key}"), &v]).with_context(|| format!("failed to set {key} of agent {id}"))?;
+    }
+  }
+  cli.list()
+}
+
+// Deletes an agent and points chats that used it back at the default agent.
+pub fn delete(st: &Storage, profile_id: &str, id: &str) -> Result<Vec<AgentInfo>> {
+  validate_id(id)?;
+  let cli = AgentCli::for_profile(st, profile_id)?;
+  cli.run(&["agents", "delete", id, "--force"]).with_context(|| format!("failed to delete agent {id}"))?;
+  crate::chats::clear_agent(st, profile_id, id)?;
+  cli.list()
+}
+
+#[tauri::command(async)]
+pub fn openclaw_agents_list(storage: State<'_, Storage>, profile_id: String) -> Result<Vec<AgentInfo>, String> {
+  list(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn openclaw_agents_create(
+  storage: State<'_, Storage>,
+  profile_id: String,
+  agent_id: String,
+  workspace: Option<String>,
+  model: Option<String>,
+) -> Result<Vec<AgentInfo>, String> {
+  create(&storage, &profile_id, agent_id.trim(), workspace, model).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn openclaw_agents_update(
+  storage: State<'_, Storage>,
+  profile_id: String,
+  agent_id: String,
+  workspace: Option<String>,
+  model: Option<String>,
+) -> Result<Vec<AgentInfo>, String> {
+  update(&storage, &profile_id, agent_id.trim(), workspace, model).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn openclaw_agents_delete(storage: State<'_, Storage>, profile_id: String, agent_id: String) -> Result<Vec<AgentInfo>, String> {
+  delete(&storage, &profile_id, agent_id.trim()).map_err(|e| e.to_string())
+}
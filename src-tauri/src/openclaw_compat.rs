@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use tauri::State;
+
+use crate::core::Storage;
+use crate::discovery::{version_key, Tool};
+
+// Which OpenClaw releases the app works with, and which CLI flags each one understands. The
+// version comes from `openclaw --version`, run once per binary (discovery.rs caches it); flags are
+// only passed to releases that have them.
+
+// Oldest release the app supports.
+pub const MIN_SUPPORTED: &str = "2026.1.5";
+// Newest release series tested. Later ones are allowed but flagged as untested.
+pub const MAX_TESTED: &str = "2026.2";
+
+pub const UPGRADE_HINT: &str = "Upgrade with `npm install -g openclaw@latest` (or your version manager), then reopen Settings.";
+
+// First release with each flag.
+const GATEWAY_STATUS_JSON: &str = "2026.2.0";
+const MODELS_STATUS_JSON: &str = "2026.2.0";
+pub const AGENTS_NON_INTERACTIVE: &str = "2026.1.10";
+const AGENT_CHANNEL: &str = "2026.1.20";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Support {
+  Supported,
+  // Newer than MAX_TESTED; everything is enabled.
+  Untested,
+  // Older than MIN_SUPPORTED; commands refuse to run.
+  Unsupported,
+  // `--version` failed or printed something unrecognized; everything is enabled.
+  Unknown,
+}
+
+// CLI features to use. All on when the version is unknown, so a newer release with changed
+// `--version` output keeps working.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Caps {
+  // `gateway status --json`
+  pub gateway_status_json: bool,
+  // `models status --json`; otherwise `--status-plain`.
+  pub models_status_json: bool,
+  // `agents add --non-interactive`
+  pub agents_non_interactive: bool,
+  // `agent --channel last`
+  pub agent_channel: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Compat {
+  pub version: Option<String>,
+  pub support: Support,
+  // Shown in Settings for anything but Supported.
+  pub message: Option<String>,
+  pub caps: Caps,
+}
+
+fn at_least(version: &[u64], min: &str) -> bool {
+  version >= version_key(min).as_slice()
+}
+
+impl Compat {
+  pub fn for_version(version: Option<&str>) -> Self {
+    let key = version.map(version_key).filter(|k| !k.is_empty());
+    let Some(key) = key else {
+      let all = Caps { gateway_status_json: true, models_status_json: true, agents_non_interactive: true, agent_channel: true };
+      let message = Some("Couldn't read the OpenClaw version; assuming a current release.".to_string());
+      return Self { version: version.map(String::from), support: Support::Unknown, message, caps: all };
+    };
+    let caps = Caps {
+      gateway_status_json: at_least(&key, GATEWAY_STATUS_JSON),
+      models_status_json: at_least(&key, MODELS_STATUS_JSON),
+      agents_non_interactive: at_least(&key, AGENTS_NON_INTERACTIVE),
+      agent_channel: at_least(&key, AGENT_CHANNEL),
+    };
+    let v = version.unwrap_or_default();
+    let tested = version_key(MAX_TESTED);
+    let (support, message) = if !at_least(&key, MIN_SUPPORTED) {
+      (Support::Unsupported, Some(format!("OpenClaw {v} is not supported; this app needs {MIN_SUPPORTED} or newer. {UPGRADE_HINT}")))
+    } else if key[..tested.len().min(key.len())] > tested[..] {
+      (Support::Untested, Some(format!("OpenClaw {v} is newer than the releases this app was tested with ({MAX_TESTED}.x); some features may not work.")))
+    } else {
+      (Support::Supported, None)
+    };
+    Self { version: Some(v.to_string()), support, message, caps }
+  }
+
+  // Error for commands run against an unsupported release.
+  pub fn require_supported(&self) -> Result<()> {
+    match (self.support, &self.message) {
+      (Support::Unsupported, Some(m)) => Err(anyhow!("{m}")),
+      _ => Ok(()),
+    }
+  }
+}
+
+// The profile's openclaw version and capabilities.
+pub fn detect(st: &Storage, profile_id: &str) -> Result<Compat> {
+  let bin = crate::settings::resolve_openclaw_bin(st, profile_id)?;
+  let node = crate::discovery::pinned_node(st, profile_id)?;
+  let version = crate::discovery::probe(Tool::Openclaw, &bin, node.as_deref()).ok();
+  Ok(Compat::for_version(version.as_deref()))
+}
+
+// Capabilities for a command about to run; fails on unsupported releases.
+pub fn caps(st: &Storage, profile_id: &str) -> Result<Caps> {
+  let c = detect(st, profile_id)?;
+  c.require_supported()?;
+  Ok(c.caps)
+}
+
+#[tauri::command(async)]
+pub fn openclaw_compat(storage: State<'_, Storage>, profile_id: String) -> Result<Compat, String> {
+  detect(&storage, &profile_id).map_err(|e| e.to_string())
+}
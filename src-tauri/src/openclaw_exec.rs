@@ -1,41 +1,310 @@
-use std::{path::PathBuf, process::Command};
+use std::{
+  collections::HashMap,
+  fmt,
+  io::{BufRead, BufReader, Read},
+  path::{Path, PathBuf},
+  process::{Command, Stdio},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc, Mutex,
+  },
+  time::{Duration, Instant},
+};
 
-use anyhow::{Context, Result};
-use tauri::AppHandle;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::core::EventSink;
+
+// Runs `openclaw` without blocking forever: output is read line by line on helper threads
+// (and optionally pushed as `openclaw:output` events), every call has a timeout, and a call
+// started with a handle can be cancelled from elsewhere (`openclaw_cancel`).
+
+pub const OUTPUT_EVENT: &str = "openclaw:output";
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const TICK: Duration = Duration::from_millis(50);
+// After the process exits, how long to wait for its pipes to close. A daemon spawned by
+// `gateway start` can inherit them and keep them open indefinitely.
+const DRAIN_GRACE: Duration = Duration::from_millis(250);
 
 pub fn canonical(path: &PathBuf) -> PathBuf {
   std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())
 }
 
-pub fn run_openclaw(_app: &AppHandle, openclaw_bin: PathBuf, args: Vec<String>) -> Result<std::process::Output> {
-  let oc = canonical(&openclaw_bin);
+#[derive(Debug)]
+pub enum ExecError {
+  // The openclaw binary (or script) doesn't exist.
+  NotFound(PathBuf),
+  // openclaw is a node script or shim and no node binary could be found.
+  NodeMissing(PathBuf),
+  Timeout { after: Duration, stdout: String, stderr: String },
+  Cancelled,
+  // Exited with a non-zero code; only from `ExecOutput::success`.
+  Exit { code: i32, stdout: String, stderr: String },
+  Io(std::io::Error),
+}
+
+impl fmt::Display for ExecError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::NotFound(p) => write!(f, "openclaw not found at {}", p.display()),
+      Self::NodeMissing(p) => write!(f, "{} (needed to run {})", crate::discovery::not_found(crate::discovery::Tool::Node), p.display()),
+      Self::Timeout { after, .. } => write!(f, "openclaw timed out after {}s", after.as_secs()),
+      Self::Cancelled => write!(f, "openclaw was cancelled"),
+      Self::Exit { code, stdout, stderr } => {
+        let msg = if stderr.trim().is_empty() { stdout.trim() } else { stderr.trim() };
+        if msg.is_empty() {
+          write!(f, "openclaw exited with code {code}")
+        } else {
+          write!(f, "{msg}")
+        }
+      }
+      Self::Io(e) => write!(f, "failed to run openclaw: {e}"),
+    }
+  }
+}
+
+impl std::error::Error for ExecError {}
+
+#[derive(Debug, Clone)]
+pub struct ExecOutput {
+  // -1 when the process was killed by a signal.
+  pub code: i32,
+  pub stdout: String,
+  pub stderr: String,
+}
+
+impl ExecOutput {
+  pub fn success(self) -> Result<Self, ExecError> {
+    if self.code == 0 {
+      Ok(self)
+    } else {
+      Err(ExecError::Exit { code: self.code, stdout: self.stdout, stderr: self.stderr })
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+  Stdout,
+  Stderr,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputLine<'a> {
+  pub handle: Option<&'a str>,
+  pub stream: OutputStream,
+  pub line: &'a str,
+}
+
+#[derive(Clone)]
+pub struct RunOpts<'a> {
+  pub timeout: Duration,
+  // Node to run openclaw with (a profile's pinned node). None looks next to openclaw, then
+  // searches (see discovery.rs).
+  pub node: Option<PathBuf>,
+  // Registers the run for `cancel(handle)` and tags its output events.
+  pub handle: Option<&'a str>,
+  // Receives each output line as OUTPUT_EVENT.
+  pub sink: Option<&'a dyn EventSink>,
+  // Extra environment, e.g. provider keys from the profile's secrets (secrets::openclaw_env).
+  pub env: Vec<(String, String)>,
+}
+
+impl Default for RunOpts<'_> {
+  fn default() -> Self {
+    Self { timeout: DEFAULT_TIMEOUT, node: None, handle: None, sink: None, env: vec![] }
+  }
+}
+
+impl RunOpts<'_> {
+  pub fn timeout(secs: u64) -> Self {
+    Self { timeout: Duration::from_secs(secs), ..Self::default() }
+  }
+}
+
+// Cancel flags of runs started with a handle.
+static RUNNING: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct Registration(Option<String>);
+
+impl Registration {
+  fn new(handle: Option<&str>) -> Result<(Self, Arc<AtomicBool>), ExecError> {
+    let flag = Arc::new(AtomicBool::new(false));
+    let Some(h) = handle else {
+      return Ok((Self(None), flag));
+    };
+    let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    if running.contains_key(h) {
+      let msg = format!("process handle {h} is already in use");
+      return Err(ExecError::Io(std::io::Error::new(std::io::ErrorKind::AlreadyExists, msg)));
+    }
+    running.insert(h.to_string(), flag.clone());
+    Ok((Self(Some(h.to_string())), flag))
+  }
+}
+
+impl Drop for Registration {
+  fn drop(&mut self) {
+    if let Some(h) = &self.0 {
+      RUNNING.lock().unwrap_or_else(|e| e.into_inner()).remove(h);
+    }
+  }
+}
+
+// Asks the run registered under `handle` to stop. False if nothing is running under it.
+pub fn cancel(handle: &str) -> bool {
+  match RUNNING.lock().unwrap_or_else(|e| e.into_inner()).get(handle) {
+    Some(flag) => {
+      flag.store(true, Ordering::SeqCst);
+      true
+    }
+    None => false,
+  }
+}
+
+// The node to run `openclaw_bin` with: the pinned one, the one installed alongside it (nvm,
+// fnm and asdf keep global packages next to node), or the first found.
+fn node_for(openclaw_bin: &Path, pinned: Option<&PathBuf>) -> Result<PathBuf, ExecError> {
+  if let Some(p) = pinned {
+    return if p.exists() { Ok(p.clone()) } else { Err(ExecError::NodeMissing(openclaw_bin.to_path_buf())) };
+  }
+  let sibling = openclaw_bin.parent().map(|d| d.join("node")).filter(|p| p.exists());
+  match sibling {
+    Some(p) => Ok(p),
+    None => {
+      let home = dirs::home_dir().unwrap_or_default();
+      crate::discovery::resolve_node(&crate::discovery::SearchEnv::from_process(&home)).map_err(|_| ExecError::NodeMissing(openclaw_bin.to_path_buf()))
+    }
+  }
+}
+
+fn command_for(openclaw_bin: &PathBuf, node: Option<&PathBuf>) -> Result<Command, ExecError> {
+  let oc = canonical(openclaw_bin);
+  // A bare name is left to the PATH lookup in spawn.
+  if openclaw_bin.components().count() > 1 && !oc.exists() {
+    return Err(ExecError::NotFound(openclaw_bin.clone()));
+  }
 
   // If we resolved to a node script (e.g. openclaw.mjs), run it via an explicit node binary.
   if crate::settings::is_node_script(&oc) {
-    let node = crate::settings::resolve_node_bin().context("failed to resolve node binary")?;
-    return Command::new(node)
-      .env("NODE_NO_WARNINGS", "1")
-      .env("NODE_OPTIONS", "--no-deprecation")
-      .arg(oc)
-      .args(args)
-      .output()
-      .context("failed to run openclaw via node");
+    let mut cmd = Command::new(node_for(openclaw_bin, node)?);
+    cmd.arg(oc);
+    return Ok(cmd);
   }
 
   // Otherwise execute the resolved binary, but ensure PATH contains node in case the binary is a shim.
-  let node = crate::settings::resolve_node_bin().ok();
   let mut cmd = Command::new(oc);
+  if let Some(node_dir) = node_for(openclaw_bin, node).ok().as_deref().and_then(Path::parent) {
+    let path = std::env::var("PATH").unwrap_or_default();
+    cmd.env("PATH", format!("{}:{}", node_dir.to_string_lossy(), path));
+  }
+  Ok(cmd)
+}
+
+fn read_lines(pipe: impl Read + Send + 'static, stream: OutputStream, tx: mpsc::Sender<(OutputStream, String)>) {
+  std::thread::spawn(move || {
+    let mut reader = BufReader::new(pipe);
+    let mut buf = vec![];
+    while let Ok(n) = reader.read_until(b'\n', &mut buf) {
+      if n == 0 {
+        break;
+      }
+      let line = String::from_utf8_lossy(&buf).trim_end_matches(['\n', '\r']).to_string();
+      if tx.send((stream, line)).is_err() {
+        break;
+      }
+      buf.clear();
+    }
+  });
+}
+
+pub fn run(openclaw_bin: PathBuf, args: Vec<String>, opts: &RunOpts) -> Result<ExecOutput, ExecError> {
+  let mut cmd = command_for(&openclaw_bin, opts.node.as_ref())?;
   cmd.env("NODE_NO_WARNINGS", "1");
   cmd.env("NODE_OPTIONS", "--no-deprecation");
   cmd.args(args);
+  run_command(cmd, &openclaw_bin, opts)
+}
+
+// Runs a prepared command under `opts`. `bin` is the program named in errors.
+pub fn run_command(mut cmd: Command, bin: &Path, opts: &RunOpts) -> Result<ExecOutput, ExecError> {
+  cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+  cmd.envs(opts.env.iter().map(|(k, v)| (k, v)));
 
-  if let Some(node) = node {
-    if let Some(node_dir) = node.parent() {
-      let path = std::env::var("PATH").unwrap_or_default();
-      let new_path = format!("{}:{}", node_dir.to_string_lossy(), path);
-      cmd.env("PATH", new_path);
+  let (_registration, cancelled) = Registration::new(opts.handle)?;
+  let mut child = match cmd.spawn() {
+    Ok(c) => c,
+    // If the binary exists, what's missing is the interpreter in its `#!/usr/bin/env node`.
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+      let bin = bin.to_path_buf();
+      return Err(if canonical(&bin).exists() { ExecError::NodeMissing(bin) } else { ExecError::NotFound(bin) });
     }
+    Err(e) => return Err(ExecError::Io(e)),
+  };
+
+  let (tx, rx) = mpsc::channel();
+  if let Some(out) = child.stdout.take() {
+    read_lines(out, OutputStream::Stdout, tx.clone());
+  }
+  if let Some(err) = child.stderr.take() {
+    read_lines(err, OutputStream::Stderr, tx);
   }
 
-  cmd.output().context("failed to run openclaw")
+  let (mut stdout, mut stderr) = (String::new(), String::new());
+  let deadline = Instant::now() + opts.timeout;
+  let mut exited: Option<(std::process::ExitStatus, Instant)> = None;
+  // Both pipes closed; the child may still be running.
+  let mut closed = false;
+  loop {
+    let next = if closed {
+      std::thread::sleep(TICK);
+      Err(mpsc::RecvTimeoutError::Timeout)
+    } else {
+      rx.recv_timeout(TICK)
+    };
+    match next {
+      Ok((stream, line)) => {
+        if let Some(sink) = opts.sink {
+          crate::core::emit(sink, OUTPUT_EVENT, &OutputLine { handle: opts.handle, stream, line: &line });
+        }
+        let buf = if stream == OutputStream::Stdout { &mut stdout } else { &mut stderr };
+        buf.push_str(&line);
+        buf.push('\n');
+      }
+      Err(mpsc::RecvTimeoutError::Disconnected) => closed = true,
+      Err(mpsc::RecvTimeoutError::Timeout) => {}
+    }
+
+    if exited.is_none() {
+      if let Some(status) = child.try_wait().map_err(ExecError::Io)? {
+        exited = Some((status, Instant::now()));
+      }
+    }
+    if let Some((status, at)) = exited {
+      if closed || at.elapsed() >= DRAIN_GRACE {
+        return Ok(ExecOutput { code: status.code().unwrap_or(-1), stdout, stderr });
+      }
+      continue;
+    }
+
+    if cancelled.load(Ordering::SeqCst) {
+      let _ = child.kill();
+      let _ = child.wait();
+      return Err(ExecError::Cancelled);
+    }
+    if Instant::now() >= deadline {
+      let _ = child.kill();
+      let _ = child.wait();
+      return Err(ExecError::Timeout { after: opts.timeout, stdout, stderr });
+    }
+  }
+}
+
+// Cancels a run started with `handle`; returns whether one was running.
+#[tauri::command]
+pub fn openclaw_cancel(handle: String) -> bool {
+  cancel(&handle)
 }
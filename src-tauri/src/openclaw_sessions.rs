@@ -0,0 +1,223 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::core::{write_atomic, Storage};
+
+// OpenClaw's session files for a profile's OpenClaw profile, kept in step with desktop chats.
+// OpenClaw stores them as ~/.openclaw-{profile}/agents/{agent}/sessions/{session}.jsonl with an
+// index in sessions.json; a `{session}.jsonl.lock` next to the transcript marks a run in progress.
+
+// Prefix of the session ids desktop chats use; other sessions belong to other OpenClaw channels.
+pub const DESKTOP_PREFIX: &str = "desktop-";
+
+// Older than any agent run (see chats::run_agent), so a lock this old was left by a crashed run.
+const STALE_LOCK_AFTER: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+  pub session_id: String,
+  pub agent_id: String,
+  pub path: String,
+  pub size: u64,
+  pub modified_ms: i64,
+  pub locked: bool,
+  // The lock's process is gone or the lock is older than STALE_LOCK_AFTER.
+  pub lock_stale: bool,
+  // Title of the desktop chat using the session; another profile's gets its name appended.
+  pub chat_title: Option<String>,
+  // A desktop session no chat uses any more, in any profile sharing the OpenClaw profile.
+  pub orphaned: bool,
+}
+
+pub fn state_dir(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  let prof = crate::settings::resolve_openclaw_profile(st, profile_id)?;
+  Ok(st.home().join(format!(".openclaw-{prof}")))
+}
+
+fn lock_path(transcript: &Path) -> PathBuf {
+  let mut p = transcript.as_os_str().to_owned();
+  p.push(".lock");
+  PathBuf::from(p)
+}
+
+fn pid_alive(pid: u32) -> bool {
+  #[cfg(unix)]
+  {
+    std::process::Command::new("kill")
+      .args(["-0", &pid.to_string()])
+      .stderr(std::process::Stdio::null())
+      .status()
+      .map(|s| s.success())
+      .unwrap_or(true)
+  }
+  #[cfg(not(unix))]
+  {
+    let _ = pid;
+    true
+  }
+}
+
+fn lock_is_stale(lock: &Path) -> bool {
+  let old = fs::metadata(lock)
+    .and_then(|m| m.modified())
+    .ok()
+    .and_then(|t| SystemTime::now().duration_since(t).ok())
+    .is_some_and(|age| age > STALE_LOCK_AFTER);
+  let dead = fs::read_to_string(lock)
+    .ok()
+    .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+    .and_then(|v| v.get("pid").and_then(|p| p.as_u64()))
+    .is_some_and(|pid| !pid_alive(pid as u32));
+  old || dead
+}
+
+// (session id, agent id, transcript) across all agents. `session_id` limits it to one session.
+fn scan(state: &Path, session_id: Option<&str>) -> Vec<(String, String, PathBuf)> {
+  let mut found = vec![];
+  let Ok(agents) = fs::read_dir(state.join("agents")) else {
+    return found;
+  };
+  for agent in agents.flatten() {
+    let agent_id = agent.file_name().to_string_lossy().to_string();
+    let Ok(files) = fs::read_dir(agent.path().join("sessions")) else {
+      continue;
+    };
+    for f in files.flatten() {
+      let name = f.file_name().to_string_lossy().to_string();
+      let Some(id) = name.strip_suffix(".jsonl") else {
+        continue;
+      };
+      if session_id.is_none_or(|s| s == id) {
+        found.push((id.to_string(), agent_id.clone(), f.path()));
+      }
+    }
+  }
+  found
+}
+
+// (session id, chat title) of the chats of this profile and of every other profile using the same
+// OpenClaw profile (see settings.openclaw_profile), this profile's first. The flag is false when
+// some of those profiles' chats couldn't be read (e.g. locked), so any session may be in use.
+fn chat_sessions(st: &Storage, profile_id: &str) -> Result<(Vec<(String, String)>, bool)> {
+  let mut sessions: Vec<(String, String)> = crate::chats::load_index(st, profile_id)?.chats.into_iter().map(|c| (c.session_id, c.title)).collect();
+  let mut complete = true;
+  let prof = crate::settings::resolve_openclaw_profile(st, profile_id)?;
+  for p in crate::profiles::ensure_default_profile(st)?.profiles.into_iter().filter(|p| p.id != profile_id) {
+    if crate::settings::resolve_openclaw_profile(st, &p.id).ok().as_deref() != Some(prof.as_str()) {
+      continue;
+    }
+    match crate::chats::load_index(st, &p.id) {
+      Ok(idx) => sessions.extend(idx.chats.into_iter().map(|c| (c.session_id, format!("{} ({})", c.title, p.name)))),
+      Err(_) => complete = false,
+    }
+  }
+  Ok((sessions, complete))
+}
+
+pub fn list(st: &Storage, profile_id: &str) -> Result<Vec<SessionInfo>> {
+  let (chats, complete) = chat_sessions(st, profile_id)?;
+  let mut out: Vec<SessionInfo> = scan(&state_dir(st, profile_id)?, None)
+    .into_iter()
+    .map(|(session_id, agent_id, path)| {
+      let meta = fs::metadata(&path).ok();
+      let lock = lock_path(&path);
+      let locked = lock.exists();
+      let chat_title = chats.iter().find(|(s, _)| *s == session_id).map(|(_, title)| title.clone());
+      SessionInfo {
+        orphaned: complete && chat_title.is_none() && session_id.starts_with(DESKTOP_PREFIX),
+        chat_title,
+        lock_stale: locked && lock_is_stale(&lock),
+        locked,
+        size: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+        modified_ms: meta
+          .and_then(|m| m.modified().ok())
+          .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+          .map(|d| d.as_millis() as i64)
+          .unwrap_or(0),
+        path: path.to_string_lossy().to_string(),
+        session_id,
+        agent_id,
+      }
+    })
+    .collect();
+  out.sort_by_key(|s| std::cmp::Reverse(s.modified_ms));
+  Ok(out)
+}
+
+pub fn exists(st: &Storage, profile_id: &str, session_id: &str) -> Result<bool> {
+  Ok(!scan(&state_dir(st, profile_id)?, Some(session_id)).is_empty())
+}
+
+// Removes a session's transcript, lock and sessions.json entries under every agent. Returns how
+// many transcripts were removed.
+pub fn remove(st: &Storage, profile_id: &str, session_id: &str) -> Result<usize> {
+  let state = state_dir(st, profile_id)?;
+  let found = scan(&state, Some(session_id));
+  for (_, _, path) in &found {
+    let _ = fs::remove_file(lock_path(path));
+    fs::remove_file(path).map_err(|e| anyhow!("failed to remove {}: {e}", path.display()))?;
+  }
+  // sessions.json maps session keys to entries holding a `sessionId`.
+  if let Ok(agents) = fs::read_dir(state.join("agents")) {
+    for agent in agents.flatten() {
+      let store = agent.path().join("sessions").join("sessions.json");
+      let Some(mut map) = fs::read_to_string(&store).ok().and_then(|s| serde_json::from_str::<serde_json::Map<String, Value>>(&s).ok()) else {
+        continue;
+      };
+      let before = map.len();
+      map.retain(|_, v| v.get("sessionId").and_then(|x| x.as_str()) != Some(session_id));
+      if map.len() != before {
+        write_atomic(&store, serde_json::to_vec_pretty(&map)?)?;
+      }
+    }
+  }
+  Ok(found.len())
+}
+
+// Removes orphaned desktop sessions and returns their ids; sessions a chat of any profile sharing
+// the OpenClaw profile uses are kept.
+pub fn prune(st: &Storage, profile_id: &str) -> Result<Vec<String>> {
+  let mut removed = vec![];
+  for s in list(st, profile_id)?.into_iter().filter(|s| s.orphaned) {
+    remove(st, profile_id, &s.session_id)?;
+    removed.push(s.session_id);
+  }
+  Ok(removed)
+}
+
+// Deletes stale session locks (all of them with `force`), optionally only for one session.
+// Returns the removed lock paths.
+pub fn clear_locks(st: &Storage, profile_id: &str, session_id: Option<&str>, force: bool) -> Result<Vec<String>> {
+  let mut cleared = vec![];
+  for (_, _, path) in scan(&state_dir(st, profile_id)?, session_id) {
+    let lock = lock_path(&path);
+    if lock.exists() && (force || lock_is_stale(&lock)) {
+      fs::remove_file(&lock).map_err(|e| anyhow!("failed to remove {}: {e}", lock.display()))?;
+      cleared.push(lock.to_string_lossy().to_string());
+    }
+  }
+  Ok(cleared)
+}
+
+#[tauri::command]
+pub fn openclaw_sessions_list(storage: State<'_, Storage>, profile_id: String) -> Result<Vec<SessionInfo>, String> {
+  list(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn openclaw_sessions_prune(storage: State<'_, Storage>, profile_id: String) -> Result<Vec<String>, String> {
+  prune(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn openclaw_sessions_clear_locks(storage: State<'_, Storage>, profile_id: String, session_id: Option<String>, force: Option<bool>) -> Result<Vec<String>, String> {
+  clear_locks(&storage, &profile_id, session_id.as_deref(), force.unwrap_or(false)).map_err(|e| e.to_string())
+}
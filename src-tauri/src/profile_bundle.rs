@@ -0,0 +1,326 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs::{self, File},
+  io::Read,
+  path::{Component, Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::chats::{ChatIndex, ChatThread};
+use crate::core::{DirLock, Storage};
+use crate::state::{Profile, ProfilesStore};
+
+// Profile bundles: one .tar.gz holding a profile's settings, prompt presets, chats with their
+// threads and workspace files, for moving a profile to another machine or handing out a team
+// setup. Secrets are only included encrypted with a passphrase, and so are the chats of a
+// profile that encrypts them at rest. Importing always creates a new
+// profile with fresh chat and session ids, so a bundle can be imported next to its original.
+
+const FORMAT: &str = "openclaw-desktop-profile";
+// 2 adds chats sealed with the passphrase; bundles without them are still written as 1.
+const VERSION: i32 = 2;
+const MANIFEST: &str = "manifest.json";
+const SECRETS: &str = "secrets.sealed.json";
+// AEAD context for the sealed secrets.
+const SECRETS_CONTEXT: &str = "openclaw-desktop profile bundle secrets v1";
+// AEAD context for sealed chat entries, followed by `:{entry name}`.
+const CHATS_CONTEXT: &str = "openclaw-desktop profile bundle chats v1";
+// Largest bundle `import` reads, to keep a bad file from exhausting memory.
+const MAX_BUNDLE_BYTES: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+  pub format: String,
+  pub version: i32,
+  pub exported_at_ms: i64,
+  pub profile: Profile,
+  pub chats: usize,
+  pub secrets: bool,
+  // Chat entries are sealed with the passphrase (the profile encrypts its chats).
+  #[serde(default)]
+  pub chats_sealed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportResult {
+  pub path: String,
+  pub chats: usize,
+  pub files: usize,
+  // Secret keys included (encrypted).
+  pub secrets: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportResult {
+  pub store: ProfilesStore,
+  pub profile: Profile,
+  pub chats: usize,
+  pub files: usize,
+  pub secrets: Vec<String>,
+  // What didn't carry over as-is and why, for the user to review.
+  pub conflicts: Vec<String>,
+}
+
+// Top-level profile files that go into a bundle; `workspace/` goes in whole. Everything else
+// (locks, traces, gateway history, log snapshots, the secret index) is specific to this install.
+fn bundled(name: &str) -> bool {
+  matches!(name, "settings.json" | "prompts.json") || is_chat_entry(name)
+}
+
+fn is_chat_entry(name: &str) -> bool {
+  name == "chats.json" || (name.starts_with("chat_") && name.ends_with(".json"))
+}
+
+fn append_bytes<W: std::io::Write>(tar: &mut tar::Builder<W>, name: &str, data: &[u8]) -> Result<()> {
+  let mut header = tar::Header::new_gnu();
+  header.set_size(data.len() as u64);
+  header.set_mode(0o600);
+  header.set_mtime((crate::chats::now_ms() / 1000) as u64);
+  header.set_cksum();
+  tar.append_data(&mut header, name, data).with_context(|| format!("failed to add {name}"))
+}
+
+fn append_dir<W: std::io::Write>(tar: &mut tar::Builder<W>, dir: &Path, prefix: &str, files: &mut usize) -> Result<()> {
+  let Ok(rd) = fs::read_dir(dir) else { return Ok(()) };
+  for e in rd.flatten() {
+    let name = format!("{prefix}/{}", e.file_name().to_string_lossy());
+    let ft = e.file_type()?;
+    if ft.is_dir() {
+      append_dir(tar, &e.path(), &name, files)?;
+    } else if ft.is_file() {
+      tar.append_path_with_name(e.path(), &name).with_context(|| format!("failed to add {name}"))?;
+      *files += 1;
+    }
+  }
+  Ok(())
+}
+
+// Writes the profile to `dest`. With `passphrase`, the profile's secrets are included, encrypted.
+// A profile with encrypted chats needs `passphrase`: its chats are sealed with it rather than
+// written out in plaintext.
+pub fn export(st: &Storage, profile_id: &str, dest: &Path, passphrase: Option<&str>) -> Result<ExportResult> {
+  let store = crate::profiles::ensure_default_profile(st)?;
+  let profile = store.profiles.into_iter().find(|p| p.id == profile_id).ok_or_else(|| anyhow!("profile not found"))?;
+  let dir = st.profile_dir(profile_id)?;
+  let chat_key = match (crate::chat_crypto::status(st, profile_id)?.enabled, passphrase) {
+    (false, _) => None,
+    (true, Some(pass)) => Some(crate::crypto::derive_key(pass, &crate::crypto::new_kdf()?)?),
+    (true, None) => return Err(anyhow!("this profile's chats are encrypted; export it with a passphrase")),
+  };
+
+  let mut secrets = BTreeMap::new();
+  if passphrase.is_some() {
+    for key in crate::secrets::secret_keys(st, profile_id)? {
+      if let Some(v) = crate::secrets::get_secret(st, profile_id, &key)? {
+        secrets.insert(key, v);
+      }
+    }
+  }
+
+  let tmp = dest.with_extension("partial");
+  let mut tar = tar::Builder::new(GzEncoder::new(File::create(&tmp).with_context(|| format!("failed to create {}", tmp.display()))?, Compression::default()));
+  let mut files = 0;
+  let chats = {
+    // Settings, the chat index and threads are read together so they match.
+    let _lock = DirLock::acquire(&dir)?;
+    let chats = crate::chats::load_index_in(&dir)?.chats.len();
+    for e in fs::read_dir(&dir)?.flatten() {
+      let name = e.file_name().to_string_lossy().to_string();
+      if e.file_type()?.is_file() && bundled(&name) {
+        // Encrypted chats are opened with the profile's key (it must be unlocked) and sealed
+        // again with the bundle's.
+        let raw = fs::read_to_string(e.path()).with_context(|| format!("failed to read {name}"))?;
+        let plain = crate::chat_crypto::decode(&dir, &name, raw)?;
+        match chat_key.as_ref().filter(|_| is_chat_entry(&name)) {
+          Some(key) => append_bytes(&mut tar, &name, &serde_json::to_vec(&crate::crypto::seal_with(key, &format!("{CHATS_CONTEXT}:{name}"), plain.as_bytes())?)?)?,
+          None => append_bytes(&mut tar, &name, plain.as_bytes())?,
+        }
+        files += 1;
+      }
+    }
+    chats
+  };
+  append_dir(&mut tar, &dir.join("workspace"), "workspace", &mut files)?;
+
+  if let Some(pass) = passphrase.filter(|_| !secrets.is_empty()) {
+    let sealed = crate::crypto::seal(pass, SECRETS_CONTEXT, &serde_json::to_vec(&secrets)?)?;
+    append_bytes(&mut tar, SECRETS, &serde_json::to_vec_pretty(&sealed)?)?;
+  }
+  let manifest = Manifest {
+    format: FORMAT.into(),
+    version: if chat_key.is_some() { VERSION } else { 1 },
+    exported_at_ms: crate::chats::now_ms(),
+    profile,
+    chats,
+    secrets: passphrase.is_some() && !secrets.is_empty(),
+    chats_sealed: chat_key.is_some(),
+  };
+  append_bytes(&mut tar, MANIFEST, &serde_json::to_vec_pretty(&manifest)?)?;
+  tar.into_inner()?.finish()?;
+  fs::rename(&tmp, dest).with_context(|| format!("failed to write {}", dest.display()))?;
+
+  Ok(ExportResult { path: dest.to_string_lossy().to_string(), chats, files, secrets: secrets.into_keys().collect() })
+}
+
+// Bundle entries by path. Rejects paths that would escape the profile dir.
+fn read_bundle(path: &Path) -> Result<HashMap<String, Vec<u8>>> {
+  let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+  let mut archive = tar::Archive::new(GzDecoder::new(file).take(MAX_BUNDLE_BYTES));
+  let mut entries = HashMap::new();
+  for e in archive.entries().context("not a profile bundle")? {
+    let mut e = e.context("corrupt profile bundle")?;
+    if !e.header().entry_type().is_file() {
+      continue;
+    }
+    let p = e.path()?.into_owned();
+    if p.components().any(|c| !matches!(c, Component::Normal(_))) {
+      return Err(anyhow!("profile bundle has an unsafe path: {}", p.display()));
+    }
+    let mut data = vec![];
+    e.read_to_end(&mut data)?;
+    entries.insert(p.to_string_lossy().replace('\\', "/"), data);
+  }
+  Ok(entries)
+}
+
+// Replaces the sealed chat entries with their plaintext.
+fn open_chats(entries: &mut HashMap<String, Vec<u8>>, passphrase: &str) -> Result<()> {
+  let mut key: Option<crate::crypto::DerivedKey> = None;
+  for (name, data) in entries.iter_mut().filter(|(n, _)| is_chat_entry(n)) {
+    let sealed: crate::crypto::Sealed = serde_json::from_slice(data).with_context(|| format!("failed to parse {name} in bundle"))?;
+    // Every entry is sealed with one key; derive it once.
+    if key.as_ref().is_none_or(|k| k.kdf.salt != sealed.kdf.salt) {
+      key = Some(crate::crypto::derive_key(passphrase, &sealed.kdf)?);
+    }
+    let key = key.as_ref().ok_or_else(|| anyhow!("no key"))?;
+    *data = crate::crypto::open_with(key, &format!("{CHATS_CONTEXT}:{name}"), &sealed)?;
+  }
+  Ok(())
+}
+
+fn parse<T: serde::de::DeserializeOwned>(entries: &HashMap<String, Vec<u8>>, name: &str) -> Result<Option<T>> {
+  entries.get(name).map(|raw| serde_json::from_slice(raw).with_context(|| format!("failed to parse {name} in bundle"))).transpose()
+}
+
+// Creates a new profile from a bundle. Secrets are restored when the bundle has them and
+// `passphrase` opens them. A bundle with sealed chats can't be imported without its passphrase.
+pub fn import(st: &Storage, src: &Path, passphrase: Option<&str>) -> Result<ImportResult> {
+  let mut entries = read_bundle(src)?;
+  let manifest: Manifest = parse(&entries, MANIFEST)?.ok_or_else(|| anyhow!("not a profile bundle (no {MANIFEST})"))?;
+  if manifest.format != FORMAT {
+    return Err(anyhow!("not a profile bundle ({})", manifest.format));
+  }
+  if manifest.version > VERSION {
+    return Err(anyhow!("this bundle needs a newer version of the app (bundle format {})", manifest.version));
+  }
+  let mut conflicts = vec![];
+
+  if manifest.chats_sealed {
+    let pass = passphrase.ok_or_else(|| anyhow!("this bundle's chats are encrypted; its passphrase is needed to import it"))?;
+    open_chats(&mut entries, pass)?;
+    conflicts.push("chats were encrypted in the exported profile; turn on chat encryption here to keep them encrypted at rest".to_string());
+  }
+
+  // Open chats and secrets before creating anything, so a wrong passphrase leaves no half-imported profile.
+  let secrets: BTreeMap<String, String> = match (parse::<crate::crypto::Sealed>(&entries, SECRETS)?, passphrase) {
+    (Some(sealed), Some(pass)) => serde_json::from_slice(&crate::crypto::open(pass, SECRETS_CONTEXT, &sealed)?)?,
+    (Some(_), None) => {
+      conflicts.push("secrets skipped: the bundle's secrets need its passphrase".to_string());
+      BTreeMap::new()
+    }
+    (None, _) => BTreeMap::new(),
+  };
+
+  let existing = crate::profiles::ensure_default_profile(st)?;
+  let mut name = manifest.profile.name.clone();
+  if existing.profiles.iter().any(|p| p.name == name) {
+    name = format!("{name} (imported)");
+    conflicts.push(format!("a profile named \"{}\" exists; imported as \"{name}\"", manifest.profile.name));
+  }
+  let store = crate::profiles::create(st, &name)?;
+  let profile = store.profiles.iter().find(|p| Some(&p.id) == store.active_profile_id.as_ref()).cloned().ok_or_else(|| anyhow!("profile not created"))?;
+  let filled = fill(st, &profile.id, &entries, &existing, &secrets, &mut conflicts);
+  let (chats, files, secrets) = match filled {
+    Ok(counts) => counts,
+    Err(e) => {
+      let gone = crate::profiles::DeleteOptions { permanent: true, openclaw_profile: false };
+      let _ = crate::profiles::delete(st, &profile.id, gone);
+      return Err(e);
+    }
+  };
+  Ok(ImportResult { store, profile, chats, files, secrets, conflicts })
+}
+
+// Writes the bundle's contents into the new profile. Returns the chat, file and secret counts.
+fn fill(
+  st: &Storage,
+  profile_id: &str,
+  entries: &HashMap<String, Vec<u8>>,
+  existing: &ProfilesStore,
+  secrets: &BTreeMap<String, String>,
+  conflicts: &mut Vec<String>,
+) -> Result<(usize, usize, Vec<String>)> {
+  let dir = st.profile_dir(profile_id)?;
+
+  let mut idx: ChatIndex = parse(entries, "chats.json")?.unwrap_or_default();
+  let chat_ids = crate::chats::copy_chats_in(&dir, &mut idx, |old| parse::<ChatThread>(entries, &format!("chat_{old}.json")))?;
+  for chat in idx.chats.iter().filter(|c| c.agent_id.is_some()) {
+    conflicts.push(format!("chat \"{}\" uses OpenClaw agent {}; create it here or pick another", chat.title, chat.agent_id.as_deref().unwrap_or_default()));
+  }
+
+  if let Some(mut s) = parse::<crate::settings::ProfileSettings>(entries, "settings.json")? {
+    // Paths and OpenClaw profiles belong to the exporting machine.
+    for (what, value) in [("openclaw path", s.openclaw_path.take()), ("node path", s.node_path.take()), ("OpenClaw profile", s.openclaw_profile.take())] {
+      if let Some(v) = value {
+        conflicts.push(format!("dropped the pinned {what} {v}"));
+      }
+    }
+    if let Some(api) = s.api_server.as_mut() {
+      if let Some(c) = api.chat.as_ref().and_then(|c| chat_ids.get(c)) {
+        api.chat = Some(c.clone());
+      }
+      let taken = existing.profiles.iter().any(|p| crate::settings::load_settings(st, &p.id).ok().and_then(|o| o.api_server).is_some_and(|o| o.port == api.port));
+      if taken {
+        conflicts.push(format!("local API server disabled: port {} is used by another profile", api.port));
+        s.api_server = None;
+      }
+    }
+    crate::settings::save_settings(st, profile_id, &s)?;
+  }
+  if let Some(raw) = entries.get("prompts.json") {
+    crate::core::write_atomic(&dir.join("prompts.json"), raw)?;
+  }
+
+  let mut files = 0;
+  for (name, data) in entries.iter().filter(|(n, _)| n.starts_with("workspace/")) {
+    let path: PathBuf = dir.join(name);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, data).with_context(|| format!("failed to write {}", path.display()))?;
+    files += 1;
+  }
+
+  let mut restored = vec![];
+  for (key, value) in secrets {
+    match crate::secrets::set_secret(st, profile_id, key, value) {
+      Ok(()) => restored.push(key.clone()),
+      Err(e) => conflicts.push(format!("secret {key} not restored: {e:#}")),
+    }
+  }
+  Ok((idx.chats.len(), files, restored))
+}
+
+#[tauri::command(async)]
+pub fn profile_export(storage: State<'_, Storage>, profile_id: String, dest: String, passphrase: Option<String>) -> Result<ExportResult, String> {
+  export(&storage, &profile_id, Path::new(&dest), passphrase.as_deref().filter(|p| !p.is_empty())).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn profile_import(storage: State<'_, Storage>, src: String, passphrase: Option<String>) -> Result<ImportResult, String> {
+  import(&storage, Path::new(&src), passphrase.as_deref().filter(|p| !p.is_empty())).map_err(|e| e.to_string())
+}
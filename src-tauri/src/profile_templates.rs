@@ -0,0 +1,257 @@
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Component, Path},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::State;
+
+use crate::core::{write_atomic, DirLock, Storage};
+use crate::settings::ProfileSettings;
+use crate::state::ProfilesStore;
+
+// Profile templates: a ProfileSettings preset plus workspace seed files, applied when a profile is
+// created. A few are built in; users can save more from an existing profile into
+// profile_templates.json under the app data dir.
+
+const TEMPLATES_FILE: &str = "profile_templates.json";
+// Settings that belong to this machine or to one profile, never saved into a template.
+const MACHINE_SETTINGS: &[&str] = &["version", "openclaw_path", "openclaw_profile", "node_path", "api_server", "secret_backend"];
+// Workspace files larger than this aren't saved into a template.
+const MAX_SEED_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileTemplate {
+  pub id: String,
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(default)]
+  pub builtin: bool,
+  // ProfileSettings fields set over the defaults, by their JSON names.
+  #[serde(default)]
+  pub settings: Map<String, Value>,
+  // Workspace files by path relative to the workspace.
+  #[serde(default)]
+  pub workspace: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TemplateStore {
+  #[serde(default)]
+  templates: Vec<ProfileTemplate>,
+}
+
+fn builtin(id: &str, name: &str, description: &str, settings: Value, workspace: &[(&str, &str)]) -> ProfileTemplate {
+  ProfileTemplate {
+    id: id.to_string(),
+    name: name.to_string(),
+    description: description.to_string(),
+    builtin: true,
+    settings: settings.as_object().cloned().unwrap_or_default(),
+    workspace: workspace.iter().map(|(p, c)| (p.to_string(), c.to_string())).collect(),
+  }
+}
+
+pub fn builtins() -> Vec<ProfileTemplate> {
+  vec![
+    builtin(
+      "safe-research",
+      "Safe research",
+      "No command execution; the model can read the web and its memory only.",
+      json!({ "dev_full_exec_auto": false, "auto_do_mode": false }),
+      &[(
+        "AGENTS.md",
+        "# AGENTS\n\nResearch workspace. Commands are disabled: gather sources with web_get, cite the URLs you used, and say when you could not verify something.\n",
+      )],
+    ),
+    builtin(
+      "dev-box",
+      "Dev box",
+      "Full command execution in auto-do mode, with longer tool loops and a 32k context window.",
+      json!({
+        "dev_full_exec_auto": true,
+        "auto_do_mode": true,
+        "ollama_num_ctx": 32768,
+        "agent_limits": { "max_steps": 16, "max_wall_secs": 1800, "max_tool_output_bytes": 2 * 1024 * 1024 },
+      }),
+      &[(
+        "AGENTS.md",
+        "# AGENTS\n\nDevelopment workspace. Run commands to check your work instead of guessing, keep changes small, and report what you ran.\n",
+      )],
+    ),
+  ]
+}
+
+fn load_store(st: &Storage) -> Result<TemplateStore> {
+  let path = st.root().join(TEMPLATES_FILE);
+  if !path.exists() {
+    return Ok(TemplateStore::default());
+  }
+  let raw = fs::read_to_string(&path).context("failed to read profile_templates.json")?;
+  serde_json::from_str(&raw).context("failed to parse profile_templates.json")
+}
+
+fn save_store(st: &Storage, store: &TemplateStore) -> Result<()> {
+  let raw = serde_json::to_string_pretty(store).context("failed to serialize profile templates")?;
+  write_atomic(&st.root().join(TEMPLATES_FILE), raw).context("failed to write profile_templates.json")
+}
+
+// Built-ins first, then the user's templates.
+pub fn list(st: &Storage) -> Result<Vec<ProfileTemplate>> {
+  let mut all = builtins();
+  all.extend(load_store(st)?.templates);
+  Ok(all)
+}
+
+pub fn get(st: &Storage, template_id: &str) -> Result<ProfileTemplate> {
+  list(st)?.into_iter().find(|t| t.id == template_id).ok_or_else(|| anyhow!("profile template not found: {template_id}"))
+}
+
+fn safe_seed_path(rel: &str) -> Result<&Path> {
+  let p = Path::new(rel);
+  if rel.is_empty() || !p.components().all(|c| matches!(c, Component::Normal(_))) {
+    return Err(anyhow!("template has an unsafe workspace path: {rel}"));
+  }
+  Ok(p)
+}
+
+// Template settings over `base`. Unknown fields are rejected rather than silently dropped.
+fn overlay(base: &ProfileSettings, t: &ProfileTemplate) -> Result<ProfileSettings> {
+  let mut v = serde_json::to_value(base)?;
+  let obj = v.as_object_mut().ok_or_else(|| anyhow!("settings are not an object"))?;
+  for (k, val) in &t.settings {
+    if !obj.contains_key(k) || MACHINE_SETTINGS.contains(&k.as_str()) {
+      return Err(anyhow!("template {} sets unsupported setting {k}", t.name));
+    }
+    obj.insert(k.clone(), val.clone());
+  }
+  let s: ProfileSettings = serde_json::from_value(v).with_context(|| format!("template {} has invalid settings", t.name))?;
+  if let Some(l) = &s.agent_limits {
+    l.validate()?;
+  }
+  Ok(s)
+}
+
+fn validate(t: &ProfileTemplate) -> Result<()> {
+  overlay(&ProfileSettings::default(), t)?;
+  for rel in t.workspace.keys() {
+    safe_seed_path(rel)?;
+  }
+  Ok(())
+}
+
+// Applies the template's settings and seeds its workspace files, replacing same-named files.
+pub fn apply(st: &Storage, profile_id: &str, t: &ProfileTemplate) -> Result<()> {
+  let s = overlay(&crate::settings::load_settings(st, profile_id)?, t)?;
+  crate::settings::save_settings(st, profile_id, &s)?;
+  let dir = crate::desktop_agent::workspace_dir(st, profile_id)?;
+  for (rel, content) in &t.workspace {
+    let path = dir.join(safe_seed_path(rel)?);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    write_atomic(&path, content).with_context(|| format!("failed to write {}", path.display()))?;
+  }
+  Ok(())
+}
+
+// New active profile named `name`, set up from the template. A failed setup removes the profile
+// again.
+pub fn create(st: &Storage, name: &str, template_id: &str) -> Result<ProfilesStore> {
+  let t = get(st, template_id)?;
+  let store = crate::profiles::create(st, name)?;
+  let id = store.active_profile_id.clone().unwrap_or_default();
+  if let Err(e) = apply(st, &id, &t) {
+    let opts = crate::profiles::DeleteOptions { permanent: true, openclaw_profile: false };
+    let _ = crate::profiles::delete(st, &id, opts);
+    return Err(e.context(format!("failed to apply template {}", t.name)));
+  }
+  Ok(store)
+}
+
+fn seed_files(dir: &Path, prefix: &str, out: &mut BTreeMap<String, String>) {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return;
+  };
+  for entry in entries.flatten() {
+    let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+    let Ok(ty) = entry.file_type() else {
+      continue;
+    };
+    if ty.is_dir() {
+      seed_files(&entry.path(), &format!("{name}/"), out);
+    } else if ty.is_file() && entry.metadata().is_ok_and(|m| m.len() <= MAX_SEED_BYTES) {
+      // Text files only; templates are JSON.
+      if let Ok(text) = fs::read_to_string(entry.path()) {
+        out.insert(name, text);
+      }
+    }
+  }
+}
+
+// Saves a profile's settings (minus the machine-specific ones and unset fields) and its text
+// workspace files as a new user template.
+pub fn save_from_profile(st: &Storage, profile_id: &str, name: &str, description: Option<String>) -> Result<ProfileTemplate> {
+  let name = name.trim();
+  if name.is_empty() {
+    return Err(anyhow!("name required"));
+  }
+  let s = crate::settings::load_settings(st, profile_id)?;
+  let Value::Object(mut settings) = serde_json::to_value(&s)? else {
+    return Err(anyhow!("settings are not an object"));
+  };
+  settings.retain(|k, v| !v.is_null() && !MACHINE_SETTINGS.contains(&k.as_str()));
+  let mut workspace = BTreeMap::new();
+  seed_files(&st.profile_dir(profile_id)?.join("workspace"), "", &mut workspace);
+
+  let t = ProfileTemplate {
+    id: crate::core::new_id("t"),
+    name: name.to_string(),
+    description: description.map(|d| d.trim().to_string()).unwrap_or_default(),
+    builtin: false,
+    settings,
+    workspace,
+  };
+  validate(&t)?;
+  let _lock = DirLock::acquire(st.root())?;
+  let mut store = load_store(st)?;
+  store.templates.push(t.clone());
+  save_store(st, &store)?;
+  Ok(t)
+}
+
+pub fn delete(st: &Storage, template_id: &str) -> Result<Vec<ProfileTemplate>> {
+  if builtins().iter().any(|t| t.id == template_id) {
+    return Err(anyhow!("built-in templates can't be deleted"));
+  }
+  {
+    let _lock = DirLock::acquire(st.root())?;
+    let mut store = load_store(st)?;
+    let before = store.templates.len();
+    store.templates.retain(|t| t.id != template_id);
+    if store.templates.len() == before {
+      return Err(anyhow!("profile template not found: {template_id}"));
+    }
+    save_store(st, &store)?;
+  }
+  list(st)
+}
+
+#[tauri::command]
+pub fn profile_templates_list(storage: State<'_, Storage>) -> Result<Vec<ProfileTemplate>, String> {
+  list(&storage).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn profile_templates_save(storage: State<'_, Storage>, profile_id: String, name: String, description: Option<String>) -> Result<ProfileTemplate, String> {
+  save_from_profile(&storage, &profile_id, &name, description).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn profile_templates_delete(storage: State<'_, Storage>, template_id: String) -> Result<Vec<ProfileTemplate>, String> {
+  delete(&storage, &template_id).map_err(|e| e.to_string())
+}
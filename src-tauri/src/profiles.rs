@@ -1,9 +1,14 @@
-use std::{fs, path::PathBuf};
+use std::{
+  fs,
+  path::{Component, Path, PathBuf},
+};
 
 use anyhow::{anyhow, Context, Result};
-use tauri::{AppHandle, Manager};
+use serde::{Deserialize, Serialize};
+use tauri::State;
 
-use crate::state::{Profile, ProfilesStore};
+use crate::core::{ensure_unique_ids, write_atomic, DirLock, Storage};
+use crate::state::{Profile, ProfilesStore, PROFILES_STORE_VERSION};
 
 fn now_ms() -> i64 {
   let dur = std::time::SystemTime::now()
@@ -12,17 +17,8 @@ fn now_ms() -> i64 {
   dur.as_millis() as i64
 }
 
-fn profiles_path(app: &AppHandle) -> Result<PathBuf> {
-  let dir = app
-    .path()
-    .app_data_dir()
-    .context("app_data_dir not available")?;
-  fs::create_dir_all(&dir).context("failed to create app data dir")?;
-  Ok(dir.join("profiles.json"))
-}
-
-fn load_store(app: &AppHandle) -> Result<ProfilesStore> {
-  let path = profiles_path(app)?;
+fn load_store(st: &Storage) -> Result<ProfilesStore> {
+  let path = st.profiles_path()?;
   if !path.exists() {
     return Ok(ProfilesStore::default());
   }
@@ -31,20 +27,55 @@ fn load_store(app: &AppHandle) -> Result<ProfilesStore> {
   Ok(store)
 }
 
-fn save_store(app: &AppHandle, store: &ProfilesStore) -> Result<()> {
-  let path = profiles_path(app)?;
+fn save_store(st: &Storage, store: &ProfilesStore) -> Result<()> {
+  let path = st.profiles_path()?;
   let raw = serde_json::to_string_pretty(store).context("failed to serialize profiles store")?;
-  fs::write(&path, raw).context("failed to write profiles.json")?;
+  write_atomic(&path, raw).context("failed to write profiles.json")?;
   Ok(())
 }
 
 fn new_id() -> String {
-  // Good enough for local profiles.
-  format!("p_{}", now_ms())
+  crate::core::new_id("p")
+}
+
+// Brings a version 1 store up to PROFILES_STORE_VERSION: a profile whose id repeats an earlier one
+// gets a new id, a copy of the data dir they shared and of its keychain entries, then each
+// profile's chat and message ids are repaired (see chats::repair_ids_in). The OpenClaw state isn't
+// copied: the new id stays pinned to the OpenClaw profile they shared, as before. Existing unique
+// ids are kept as they are.
+fn migrate_ids(st: &Storage, store: &mut ProfilesStore) -> Result<Vec<String>> {
+  let mut notes = vec![];
+  let mut seen = std::collections::HashSet::new();
+  for p in store.profiles.iter_mut() {
+    if seen.insert(p.id.clone()) {
+      continue;
+    }
+    let id = new_id();
+    let dir = profile_data_dir(st, &id);
+    copy_dir(&profile_data_dir(st, &p.id), &dir)?;
+    notes.push(format!("profile \"{}\": duplicate id {} replaced by {id}", p.name, p.id));
+    notes.extend(crate::secrets::copy_keychain(&dir, &p.id, &id).into_iter().map(|e| format!("profile \"{}\", not copied: {e}", p.name)));
+    let shared = crate::settings::derived_openclaw_profile(&p.id);
+    crate::settings::update_settings(st, &id, |s| {
+      s.openclaw_profile.get_or_insert(shared);
+    })?;
+    p.id = id;
+  }
+  for p in &store.profiles {
+    notes.extend(crate::chats::repair_ids_in(&st.profile_dir(&p.id)?)?.into_iter().map(|n| format!("profile \"{}\", {n}", p.name)));
+  }
+  Ok(notes)
 }
 
-pub fn ensure_default_profile(app: &AppHandle) -> Result<ProfilesStore> {
-  let mut store = load_store(app)?;
+// Callers hold the root DirLock.
+fn load_or_init(st: &Storage) -> Result<ProfilesStore> {
+  let mut store = load_store(st)?;
+  if store.version < PROFILES_STORE_VERSION {
+    store.migration_notes = migrate_ids(st, &mut store)?;
+    store.version = PROFILES_STORE_VERSION;
+    save_store(st, &store)?;
+  }
+  ensure_unique_ids("profile", store.profiles.iter().map(|p| p.id.as_str())).context("invalid profiles.json")?;
   if store.profiles.is_empty() {
     let id = new_id();
     store.profiles.push(Profile {
@@ -53,22 +84,28 @@ pub fn ensure_default_profile(app: &AppHandle) -> Result<ProfilesStore> {
       created_at_ms: now_ms(),
     });
     store.active_profile_id = Some(id);
-    save_store(app, &store)?;
+    save_store(st, &store)?;
   }
   Ok(store)
 }
 
-#[tauri::command]
-pub fn profiles_list(app: AppHandle) -> Result<ProfilesStore, String> {
-  ensure_default_profile(&app).map_err(|e| e.to_string())
+// The active profile as last saved, read without the root lock or any migration, for callers
+// that mustn't wait on it (e.g. event listeners on a gateway supervisor's thread).
+pub fn active_profile_id(st: &Storage) -> Option<String> {
+  load_store(st).ok().and_then(|s| s.active_profile_id)
 }
 
-#[tauri::command]
-pub fn profiles_create(app: AppHandle, name: String) -> Result<ProfilesStore, String> {
-  let mut store = ensure_default_profile(&app).map_err(|e| e.to_string())?;
+pub fn ensure_default_profile(st: &Storage) -> Result<ProfilesStore> {
+  let _lock = DirLock::acquire(st.root())?;
+  load_or_init(st)
+}
+
+pub fn create(st: &Storage, name: &str) -> Result<ProfilesStore> {
+  let _lock = DirLock::acquire(st.root())?;
+  let mut store = load_or_init(st)?;
   let name = name.trim();
   if name.is_empty() {
-    return Err("name required".to_string());
+    return Err(anyhow!("name required"));
   }
   let id = new_id();
   store.profiles.push(Profile {
@@ -77,82 +114,375 @@ pub fn profiles_create(app: AppHandle, name: String) -> Result<ProfilesStore, St
     created_at_ms: now_ms(),
   });
   store.active_profile_id = Some(id);
-  save_store(&app, &store).map_err(|e| e.to_string())?;
+  save_store(st, &store)?;
   Ok(store)
 }
 
-#[tauri::command]
-pub fn profiles_set_active(app: AppHandle, profile_id: String) -> Result<ProfilesStore, String> {
-  let mut store = ensure_default_profile(&app).map_err(|e| e.to_string())?;
+fn copy_dir(from: &Path, to: &Path) -> Result<usize> {
+  let mut files = 0;
+  let Ok(entries) = fs::read_dir(from) else {
+    return Ok(0);
+  };
+  fs::create_dir_all(to).with_context(|| format!("failed to create {}", to.display()))?;
+  for entry in entries.flatten() {
+    let (path, dest) = (entry.path(), to.join(entry.file_name()));
+    let ty = entry.file_type()?;
+    if ty.is_dir() {
+      files += copy_dir(&path, &dest)?;
+    } else if ty.is_file() {
+      fs::copy(&path, &dest).with_context(|| format!("failed to copy {}", path.display()))?;
+      files += 1;
+    }
+  }
+  Ok(files)
+}
+
+fn copy_profile(st: &Storage, from: &str, to: &str, include_chats: bool) -> Result<()> {
+  let (src, dst) = (st.profile_dir(from)?, st.profile_dir(to)?);
+  let mut s = crate::settings::load_settings(st, from)?;
+  // The source profile's server already holds the port.
+  s.api_server = None;
+  crate::settings::save_settings(st, to, &s)?;
+  if src.join("prompts.json").exists() {
+    fs::copy(src.join("prompts.json"), dst.join("prompts.json")).context("failed to copy prompts.json")?;
+  }
+  copy_dir(&src.join("workspace"), &dst.join("workspace"))?;
+  if include_chats {
+    crate::chat_crypto::copy_key(&src, &dst)?;
+    let mut idx = crate::chats::load_index_in(&src)?;
+    crate::chats::copy_chats_in(&dst, &mut idx, |old| crate::chats::load_thread_in(&src, old).map(Some))?;
+  }
+  Ok(())
+}
+
+// New active profile with a copy of `source_id`'s settings, prompt presets and workspace files,
+// plus its chats when `include_chats`. Copied chats start new OpenClaw sessions, and the local
+// API server is left off.
+pub fn clone(st: &Storage, source_id: &str, name: &str, include_chats: bool) -> Result<ProfilesStore> {
+  if !ensure_default_profile(st)?.profiles.iter().any(|p| p.id == source_id) {
+    return Err(anyhow!("profile not found"));
+  }
+  let store = create(st, name)?;
+  let id = store.active_profile_id.clone().unwrap_or_default();
+  if let Err(e) = copy_profile(st, source_id, &id, include_chats) {
+    let _ = delete(st, &id, DeleteOptions { permanent: true, openclaw_profile: false });
+    return Err(e.context("failed to clone profile"));
+  }
+  Ok(store)
+}
+
+pub fn set_active(st: &Storage, profile_id: &str) -> Result<ProfilesStore> {
+  let _lock = DirLock::acquire(st.root())?;
+  let mut store = load_or_init(st)?;
   let exists = store.profiles.iter().any(|p| p.id == profile_id);
   if !exists {
-    return Err("profile not found".to_string());
+    return Err(anyhow!("profile not found"));
   }
-  store.active_profile_id = Some(profile_id);
-  save_store(&app, &store).map_err(|e| e.to_string())?;
+  store.active_profile_id = Some(profile_id.to_string());
+  save_store(st, &store)?;
   Ok(store)
 }
 
-#[tauri::command]
-pub fn profiles_rename(app: AppHandle, profile_id: String, name: String) -> Result<ProfilesStore, String> {
-  let mut store = ensure_default_profile(&app).map_err(|e| e.to_string())?;
+// Drops the notes the id migration left (see migrate_ids) once the user has seen them.
+pub fn dismiss_migration_notes(st: &Storage) -> Result<ProfilesStore> {
+  let _lock = DirLock::acquire(st.root())?;
+  let mut store = load_or_init(st)?;
+  if !store.migration_notes.is_empty() {
+    store.migration_notes.clear();
+    save_store(st, &store)?;
+  }
+  Ok(store)
+}
+
+pub fn rename(st: &Storage, profile_id: &str, name: &str) -> Result<ProfilesStore> {
+  let _lock = DirLock::acquire(st.root())?;
+  let mut store = load_or_init(st)?;
   let name = name.trim();
   if name.is_empty() {
-    return Err("name required".to_string());
+    return Err(anyhow!("name required"));
   }
   let p = store
     .profiles
     .iter_mut()
     .find(|p| p.id == profile_id)
-    .ok_or_else(|| "profile not found".to_string())?;
+    .ok_or_else(|| anyhow!("profile not found"))?;
   p.name = name.to_string();
-  save_store(&app, &store).map_err(|e| e.to_string())?;
+  save_store(st, &store)?;
   Ok(store)
 }
 
-#[tauri::command]
-pub fn profiles_delete(app: AppHandle, profile_id: String) -> Result<ProfilesStore, String> {
-  let mut store = ensure_default_profile(&app).map_err(|e| e.to_string())?;
+// Deleted profiles wait in trash/{trash_id}/ this long before they're purged for good.
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeleteOptions {
+  // Purge right away instead of keeping a restorable copy in the trash.
+  pub permanent: bool,
+  // Also remove the profile's own OpenClaw profile (~/.openclaw-ocd-{id}).
+  pub openclaw_profile: bool,
+}
+
+// trash/{trash_id}/trash.json. The profile's data sits next to it in `profile/`, its OpenClaw
+// state (if removed along with it) in `openclaw/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedProfile {
+  pub trash_id: String,
+  pub profile: Profile,
+  pub deleted_at_ms: i64,
+  pub expires_at_ms: i64,
+  // OpenClaw profile name, when its state was moved to the trash too.
+  pub openclaw_profile: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteResult {
+  pub store: ProfilesStore,
+  // None when deleted permanently.
+  pub trash: Option<TrashedProfile>,
+  // Keychain entries removed now. Trashed profiles keep theirs until they're purged.
+  pub removed_secrets: Vec<String>,
+  // Cleanup steps that failed; the profile is deleted regardless.
+  pub warnings: Vec<String>,
+}
+
+fn trash_root(st: &Storage) -> PathBuf {
+  st.root().join("trash")
+}
+
+// trash/{trash_id}. The id comes from the frontend, so anything but a plain name is rejected.
+fn trash_dir(st: &Storage, trash_id: &str) -> Result<PathBuf> {
+  let mut parts = Path::new(trash_id).components();
+  match (parts.next(), parts.next()) {
+    (Some(Component::Normal(_)), None) => Ok(trash_root(st).join(trash_id)),
+    _ => Err(anyhow!("invalid trash id {trash_id:?}")),
+  }
+}
+
+fn profile_data_dir(st: &Storage, profile_id: &str) -> PathBuf {
+  st.root().join("profiles").join(profile_id)
+}
+
+fn move_dir(from: &Path, to: &Path) -> Result<()> {
+  if let Some(parent) = to.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::rename(from, to).with_context(|| format!("failed to move {} to {}", from.display(), to.display()))
+}
+
+// The OpenClaw profile the app created for this profile, if the profile uses it. A profile pinned
+// to some other OpenClaw profile (settings.openclaw_profile) may share it, so that one stays.
+fn owned_openclaw_profile(st: &Storage, profile_id: &str) -> Option<String> {
+  let current = crate::settings::resolve_openclaw_profile(st, profile_id).ok()?;
+  (current == crate::settings::derived_openclaw_profile(profile_id)).then_some(current)
+}
+
+fn deletable(store: &ProfilesStore, profile_id: &str) -> Result<Profile> {
   if store.profiles.len() == 1 {
-    return Err("cannot delete last profile".to_string());
+    return Err(anyhow!("cannot delete last profile"));
+  }
+  store.profiles.iter().find(|p| p.id == profile_id).cloned().ok_or_else(|| anyhow!("profile not found"))
+}
+
+pub fn delete(st: &Storage, profile_id: &str, opts: DeleteOptions) -> Result<DeleteResult> {
+  // Stopping the supervisor (a join) and the gateway (up to the exec timeout) happens before the
+  // root lock is taken, so other profile operations don't wait on it.
+  deletable(&ensure_default_profile(st)?, profile_id)?;
+  let mut warnings = vec![];
+  crate::gateway_supervisor::stop(profile_id);
+  let openclaw = if opts.openclaw_profile { owned_openclaw_profile(st, profile_id) } else { None };
+  if opts.openclaw_profile && openclaw.is_none() {
+    warnings.push("kept the OpenClaw profile: this profile is pinned to one the app didn't create".to_string());
   }
+  let oc_dir = openclaw.as_ref().map(|p| st.home().join(format!(".openclaw-{p}"))).filter(|d| d.exists());
+  if oc_dir.is_some() {
+    // A running gateway would keep writing to the state dir.
+    if let Err(e) = crate::gateway::gateway_cmd(st, profile_id, "stop", None, None) {
+      warnings.push(format!("gateway stop: {e:#}"));
+    }
+  }
+
+  let _lock = DirLock::acquire(st.root())?;
+  let mut store = load_or_init(st)?;
+  // Checked again: the store may have changed meanwhile.
+  let profile = deletable(&store, profile_id)?;
   store.profiles.retain(|p| p.id != profile_id);
-  if store.active_profile_id.as_deref() == Some(&profile_id) {
+  if store.active_profile_id.as_deref() == Some(profile_id) {
     store.active_profile_id = store.profiles.first().map(|p| p.id.clone());
   }
-  save_store(&app, &store).map_err(|e| e.to_string())?;
+
+  let data = profile_data_dir(st, profile_id);
+  let mut result = DeleteResult { store, trash: None, removed_secrets: vec![], warnings };
+  if opts.permanent {
+    save_store(st, &result.store)?;
+    let (removed, errors) = crate::secrets::remove_secrets(&data, profile_id);
+    result.removed_secrets = removed;
+    result.warnings.extend(errors);
+    for dir in [Some(data), oc_dir].into_iter().flatten().filter(|d| d.exists()) {
+      if let Err(e) = fs::remove_dir_all(&dir) {
+        result.warnings.push(format!("failed to remove {}: {e}", dir.display()));
+      }
+    }
+    return Ok(result);
+  }
+
+  let deleted_at_ms = now_ms();
+  let mut entry = TrashedProfile {
+    trash_id: format!("{profile_id}-{deleted_at_ms}"),
+    profile,
+    deleted_at_ms,
+    expires_at_ms: deleted_at_ms + TRASH_RETENTION_DAYS * DAY_MS,
+    openclaw_profile: None,
+  };
+  let dir = trash_root(st).join(&entry.trash_id);
+  fs::create_dir_all(&dir).context("failed to create trash dir")?;
+  if data.exists() {
+    move_dir(&data, &dir.join("profile"))?;
+  }
+  if let (Some(name), Some(oc)) = (openclaw, oc_dir) {
+    match move_dir(&oc, &dir.join("openclaw")) {
+      Ok(()) => entry.openclaw_profile = Some(name),
+      Err(e) => result.warnings.push(format!("{e:#}")),
+    }
+  }
+  write_atomic(&dir.join("trash.json"), serde_json::to_string_pretty(&entry)?)?;
+  // Only drop the profile once its data is safely in the trash.
+  save_store(st, &result.store)?;
+  result.trash = Some(entry);
+  purge_expired_locked(st);
+  Ok(result)
+}
+
+fn load_trash_entry(dir: &Path) -> Result<TrashedProfile> {
+  let raw = fs::read_to_string(dir.join("trash.json")).context("failed to read trash.json")?;
+  serde_json::from_str(&raw).context("failed to parse trash.json")
+}
+
+// Profiles in the trash, most recently deleted first.
+pub fn list_trash(st: &Storage) -> Result<Vec<TrashedProfile>> {
+  let _lock = DirLock::acquire(st.root())?;
+  purge_expired_locked(st);
+  let mut out: Vec<TrashedProfile> = match fs::read_dir(trash_root(st)) {
+    Ok(rd) => rd.flatten().filter_map(|e| load_trash_entry(&e.path()).ok()).collect(),
+    Err(_) => vec![],
+  };
+  out.sort_by_key(|t| std::cmp::Reverse(t.deleted_at_ms));
+  Ok(out)
+}
+
+// Puts a trashed profile back, with its chats, settings, secrets and OpenClaw profile.
+pub fn restore(st: &Storage, trash_id: &str) -> Result<ProfilesStore> {
+  let _lock = DirLock::acquire(st.root())?;
+  purge_expired_locked(st);
+  let dir = trash_dir(st, trash_id)?;
+  let entry = load_trash_entry(&dir).map_err(|_| anyhow!("nothing to restore: {trash_id} isn't in the trash (or has expired)"))?;
+  let mut store = load_or_init(st)?;
+  let id = &entry.profile.id;
+  if store.profiles.iter().any(|p| &p.id == id) || profile_data_dir(st, id).exists() {
+    return Err(anyhow!("a profile with id {id} already exists"));
+  }
+  if dir.join("profile").exists() {
+    move_dir(&dir.join("profile"), &profile_data_dir(st, id))?;
+  }
+  if let Some(name) = &entry.openclaw_profile {
+    let target = st.home().join(format!(".openclaw-{name}"));
+    if !target.exists() {
+      move_dir(&dir.join("openclaw"), &target)?;
+    }
+  }
+  store.profiles.push(entry.profile.clone());
+  save_store(st, &store)?;
+  let _ = fs::remove_dir_all(&dir);
   Ok(store)
 }
 
-fn keychain_entry(service: &str, key: &str) -> Result<keyring::Entry> {
-  keyring::Entry::new(service, key).map_err(|e| anyhow!(e))
+// Deletes a trashed profile for good, secrets included. Returns the secrets removed.
+pub fn purge(st: &Storage, trash_id: &str) -> Result<Vec<String>> {
+  let _lock = DirLock::acquire(st.root())?;
+  purge_locked(&trash_dir(st, trash_id)?)
+}
+
+fn purge_locked(dir: &Path) -> Result<Vec<String>> {
+  let entry = load_trash_entry(dir)?;
+  let (removed, errors) = crate::secrets::remove_secrets(&dir.join("profile"), &entry.profile.id);
+  if let Some(e) = errors.first() {
+    // Keep the entry so the secrets stay tracked and the purge can be retried.
+    return Err(anyhow!("{e}"));
+  }
+  fs::remove_dir_all(dir).with_context(|| format!("failed to remove {}", dir.display()))?;
+  Ok(removed)
+}
+
+// Purges trash entries past their retention window. Callers hold the root DirLock.
+fn purge_expired_locked(st: &Storage) {
+  let now = now_ms();
+  let Ok(rd) = fs::read_dir(trash_root(st)) else { return };
+  for e in rd.flatten() {
+    if load_trash_entry(&e.path()).is_ok_and(|t| t.expires_at_ms <= now) {
+      let _ = purge_locked(&e.path());
+    }
+  }
 }
 
 #[tauri::command]
-pub fn secret_set(profile_id: String, key: String, value: String) -> Result<(), String> {
-  let service = format!("openclaw-desktop:{}", profile_id);
-  let entry = keychain_entry(&service, &key).map_err(|e| e.to_string())?;
-  entry.set_password(&value).map_err(|e| e.to_string())
+pub fn profiles_list(storage: State<'_, Storage>) -> Result<ProfilesStore, String> {
+  ensure_default_profile(&storage).map_err(|e| e.to_string())
 }
 
+// `template_id` applies a profile template (see profile_templates.rs) to the new profile.
 #[tauri::command]
-pub fn secret_get(profile_id: String, key: String) -> Result<Option<String>, String> {
-  let service = format!("openclaw-desktop:{}", profile_id);
-  let entry = keychain_entry(&service, &key).map_err(|e| e.to_string())?;
-  match entry.get_password() {
-    Ok(v) => Ok(Some(v)),
-    Err(keyring::Error::NoEntry) => Ok(None),
-    Err(e) => Err(e.to_string()),
+pub fn profiles_create(storage: State<'_, Storage>, name: String, template_id: Option<String>) -> Result<ProfilesStore, String> {
+  match template_id {
+    Some(t) => crate::profile_templates::create(&storage, &name, &t),
+    None => create(&storage, &name),
   }
+  .map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn profiles_clone(storage: State<'_, Storage>, source_id: String, name: String, include_chats: Option<bool>) -> Result<ProfilesStore, String> {
+  clone(&storage, &source_id, &name, include_chats.unwrap_or(false)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn secret_delete(profile_id: String, key: String) -> Result<(), String> {
-  let service = format!("openclaw-desktop:{}", profile_id);
-  let entry = keychain_entry(&service, &key).map_err(|e| e.to_string())?;
-  match entry.delete_credential() {
-    Ok(()) => Ok(()),
-    Err(keyring::Error::NoEntry) => Ok(()),
-    Err(e) => Err(e.to_string()),
-  }
+pub fn profiles_set_active(storage: State<'_, Storage>, profile_id: String) -> Result<ProfilesStore, String> {
+  set_active(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn profiles_dismiss_migration_notes(storage: State<'_, Storage>) -> Result<ProfilesStore, String> {
+  dismiss_migration_notes(&storage).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn profiles_rename(storage: State<'_, Storage>, profile_id: String, name: String) -> Result<ProfilesStore, String> {
+  rename(&storage, &profile_id, &name).map_err(|e| e.to_string())
+}
+
+// Moves the profile to the trash unless `permanent`; `openclaw_profile` removes its OpenClaw
+// profile too.
+#[tauri::command(async)]
+pub fn profiles_delete(
+  storage: State<'_, Storage>,
+  profile_id: String,
+  permanent: Option<bool>,
+  openclaw_profile: Option<bool>,
+) -> Result<DeleteResult, String> {
+  let opts = DeleteOptions { permanent: permanent.unwrap_or(false), openclaw_profile: openclaw_profile.unwrap_or(false) };
+  delete(&storage, &profile_id, opts).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn profiles_trash_list(storage: State<'_, Storage>) -> Result<Vec<TrashedProfile>, String> {
+  list_trash(&storage).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn profiles_restore(storage: State<'_, Storage>, trash_id: String) -> Result<ProfilesStore, String> {
+  restore(&storage, &trash_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn profiles_trash_purge(storage: State<'_, Storage>, trash_id: String) -> Result<Vec<String>, String> {
+  purge(&storage, &trash_id).map_err(|e| e.to_string())
 }
@@ -0,0 +1,251 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::core::{write_atomic, DirLock, Storage};
+use crate::settings::ProfileSettings;
+
+// Used when a profile has no custom template and the chat has no preset.
+pub const DEFAULT_TEMPLATE: &str = "You are OpenClaw Desktop running locally. You can call tools when needed.\n\nToday is {{date}}. OS: {{os}}. Working directory: {{cwd}}.\n";
+
+fn now_ms() -> i64 {
+  let dur = std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .unwrap_or_default();
+  dur.as_millis() as i64
+}
+
+fn library_path(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  Ok(st.profile_dir(profile_id)?.join("prompts.json"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPreset {
+  pub id: String,
+  pub name: String,
+  pub template: String,
+  pub created_at_ms: i64,
+  pub updated_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptLibrary {
+  pub version: i32,
+  pub presets: Vec<PromptPreset>,
+}
+
+impl Default for PromptLibrary {
+  fn default() -> Self {
+    Self { version: 1, presets: vec![] }
+  }
+}
+
+pub fn load_library(st: &Storage, profile_id: &str) -> Result<PromptLibrary> {
+  let path = library_path(st, profile_id)?;
+  if !path.exists() {
+    return Ok(PromptLibrary::default());
+  }
+  let raw = fs::read_to_string(&path).context("failed to read prompts.json")?;
+  serde_json::from_str(&raw).context("failed to parse prompts.json")
+}
+
+fn save_library(st: &Storage, profile_id: &str, lib: &PromptLibrary) -> Result<()> {
+  let path = library_path(st, profile_id)?;
+  let raw = serde_json::to_string_pretty(lib).context("failed to serialize prompt library")?;
+  write_atomic(&path, raw).context("failed to write prompts.json")?;
+  Ok(())
+}
+
+// Values substituted for {{name}} placeholders in a template.
+pub struct PromptVars {
+  pub date: String,
+  pub os: String,
+  pub cwd: String,
+  pub tools: String,
+  pub workspace_files: String,
+}
+
+impl PromptVars {
+  pub fn collect(st: &Storage, profile_id: &str, settings: &ProfileSettings) -> Self {
+    let cwd = st.profile_dir(profile_id).unwrap_or_else(|_| std::env::temp_dir());
+
+    let workspace_files = crate::desktop_agent::workspace_dir(st, profile_id)
+      .ok()
+      .and_then(|d| fs::read_dir(d).ok())
+      .map(|rd| {
+        let mut names: Vec<String> = rd
+          .filter_map(|e| e.ok())
+          .filter(|e| e.path().is_file() && !e.file_name().to_string_lossy().starts_with('.'))
+          .map(|e| e.file_name().to_string_lossy().to_string())
+          .collect();
+        names.sort();
+        names.join(", ")
+      })
+      .unwrap_or_default();
+
+    Self {
+      date: crate::memory::today(),
+      os: format!("{} ({})", std::env::consts::OS, std::env::consts::ARCH),
+      cwd: cwd.to_string_lossy().to_string(),
+      tools: enabled_tools(settings).join(", "),
+      workspace_files,
+    }
+  }
+}
+
+fn enabled_tools(settings: &ProfileSettings) -> Vec<&'static str> {
+  let mut tools = vec!["web_get"];
+  if settings.dev_full_exec_auto.unwrap_or(false) {
+    tools.push("exec");
+  }
+  tools.extend(["remember", "recall", "forget"]);
+  tools
+}
+
+// Replaces known {{var}} placeholders. Unknown placeholders are left untouched.
+pub fn render(template: &str, vars: &PromptVars) -> String {
+  template
+    .replace("{{date}}", &vars.date)
+    .replace("{{os}}", &vars.os)
+    .replace("{{cwd}}", &vars.cwd)
+    .replace("{{tools}}", &vars.tools)
+    .replace("{{workspace_files}}", &vars.workspace_files)
+}
+
+// Tool-calling contract. Always appended so a custom template can't break tool use.
+pub fn tool_instructions(dev_full_exec_auto: bool, auto_do_mode: bool) -> String {
+  let mut s = String::new();
+  if auto_do_mode {
+    s.push_str("AUTO-DO MODE: Enabled. For action requests, you MUST use tools (exec/web_get) rather than giving plans.\n");
+    s.push_str("If you claim you did something, it must be backed by tool output.\n\n");
+  }
+
+  s.push_str("TOOL CALLS:\n");
+  s.push_str("When responding, you may return a single JSON object matching one of these shapes (no extra text):\n");
+  s.push_str("- {\"tool\":\"web_get\",\"url\":\"https://example.com\"}\n");
+  s.push_str("- {\"tool\":\"exec\",\"cmd\":\"<shell command>\"}\n");
+  s.push_str("- {\"tool\":\"remember\",\"text\":\"<fact worth keeping across chats>\"}\n");
+  s.push_str("- {\"tool\":\"recall\",\"query\":\"<words to search saved memories>\"}\n");
+  s.push_str("- {\"tool\":\"forget\",\"id\":\"<mem_... id from recall>\"}\n");
+  s.push_str("- {\"tool\":\"final\",\"text\":\"<final answer>\"}\n\n");
+  if dev_full_exec_auto {
+    s.push_str("EXEC MODE: FULL EXEC AUTO is ENABLED. You may run any shell command you deem necessary. Be careful and prefer read-only commands.\n");
+  } else {
+    s.push_str("EXEC MODE: restricted. Prefer web_get; avoid exec unless explicitly requested.\n");
+  }
+  s
+}
+
+// Picks the template for a chat: chat preset, then the profile template, then the default.
+pub fn resolve_template(st: &Storage, profile_id: &str, settings: &ProfileSettings, preset_id: Option<&str>) -> String {
+  if let Some(pid) = preset_id {
+    if let Ok(lib) = load_library(st, profile_id) {
+      if let Some(p) = lib.presets.into_iter().find(|p| p.id == pid) {
+        return p.template;
+      }
+    }
+  }
+  settings
+    .system_prompt_template
+    .clone()
+    .filter(|t| !t.trim().is_empty())
+    .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string())
+}
+
+pub fn build_system_prompt(st: &Storage, profile_id: &str, settings: &ProfileSettings, preset_id: Option<&str>, memory: Option<&str>) -> String {
+  let template = resolve_template(st, profile_id, settings, preset_id);
+  let vars = PromptVars::collect(st, profile_id, settings);
+
+  let mut s = render(&template, &vars);
+  if !s.ends_with('\n') {
+    s.push('\n');
+  }
+  s.push('\n');
+  if let Some(m) = memory {
+    s.push_str(m);
+    s.push('\n');
+  }
+  s.push_str(&tool_instructions(
+    settings.dev_full_exec_auto.unwrap_or(false),
+    settings.auto_do_mode.unwrap_or(false),
+  ));
+  s
+}
+
+pub fn save_preset(st: &Storage, profile_id: &str, preset_id: Option<String>, name: &str, template: String) -> Result<PromptLibrary> {
+  let _lock = DirLock::acquire(&st.profile_dir(profile_id)?)?;
+  let mut lib = load_library(st, profile_id)?;
+  let name = name.trim();
+  if name.is_empty() {
+    return Err(anyhow!("name required"));
+  }
+  if template.trim().is_empty() {
+    return Err(anyhow!("template required"));
+  }
+
+  match preset_id {
+    Some(id) => {
+      let p = lib
+        .presets
+        .iter_mut()
+        .find(|p| p.id == id)
+        .ok_or_else(|| anyhow!("preset not found"))?;
+      p.name = name.to_string();
+      p.template = template;
+      p.updated_at_ms = now_ms();
+    }
+    None => {
+      let mut ms = now_ms();
+      while lib.presets.iter().any(|p| p.id == format!("pp_{ms}")) {
+        ms += 1;
+      }
+      lib.presets.push(PromptPreset {
+        id: format!("pp_{ms}"),
+        name: name.to_string(),
+        template,
+        created_at_ms: now_ms(),
+        updated_at_ms: now_ms(),
+      });
+    }
+  }
+
+  save_library(st, profile_id, &lib)?;
+  Ok(lib)
+}
+
+pub fn delete_preset(st: &Storage, profile_id: &str, preset_id: &str) -> Result<PromptLibrary> {
+  let _lock = DirLock::acquire(&st.profile_dir(profile_id)?)?;
+  let mut lib = load_library(st, profile_id)?;
+  lib.presets.retain(|p| p.id != preset_id);
+  save_library(st, profile_id, &lib)?;
+  Ok(lib)
+}
+
+// The full system prompt a chat would get right now (template + memory + tool contract).
+pub fn preview(st: &Storage, profile_id: &str, preset_id: Option<&str>) -> Result<String> {
+  let settings = crate::settings::load_settings(st, profile_id)?;
+  let memory = crate::memory::prompt_block_for(st, profile_id, &settings);
+  Ok(build_system_prompt(st, profile_id, &settings, preset_id, memory.as_deref()))
+}
+
+#[tauri::command]
+pub fn prompts_list(storage: State<'_, Storage>, profile_id: String) -> Result<PromptLibrary, String> {
+  load_library(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn prompts_save(storage: State<'_, Storage>, profile_id: String, preset_id: Option<String>, name: String, template: String) -> Result<PromptLibrary, String> {
+  save_preset(&storage, &profile_id, preset_id, &name, template).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn prompts_delete(storage: State<'_, Storage>, profile_id: String, preset_id: String) -> Result<PromptLibrary, String> {
+  delete_preset(&storage, &profile_id, &preset_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn prompts_preview(storage: State<'_, Storage>, profile_id: String, preset_id: Option<String>) -> Result<String, String> {
+  preview(&storage, &profile_id, preset_id.as_deref()).map_err(|e| e.to_string())
+}
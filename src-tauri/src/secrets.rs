@@ -0,0 +1,501 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use once_cell::sync::Lazy;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::core::{try_lock_file, write_atomic, DirLock, Storage};
+use crate::settings::SecretBackend;
+
+// Per-profile secrets. Values live in the OS keychain under `openclaw-desktop:{profile_id}`, or in
+// the profile's passphrase-encrypted vault (vault.rs) where there is no usable keychain. Neither
+// can be listed, so profiles/{id}/secrets.json keeps the inventory: each key with its metadata,
+// its backend and where its value is injected. Use times go to a separate secrets_used.json so
+// recording one never waits on (or races) an inventory change.
+
+const INDEX_FILE: &str = "secrets.json";
+const USED_FILE: &str = "secrets_used.json";
+// Use times are recorded at most this often per key.
+const USED_RESOLUTION_MS: i64 = 60_000;
+// Shorter values aren't redacted; they would match ordinary text.
+const MIN_REDACT_LEN: usize = 4;
+
+// Providers a secret can be the API key of, with the environment variable OpenClaw reads it from.
+// The "ollama" key is also sent to the profile's Ollama server as a bearer token.
+pub const PROVIDERS: &[(&str, &str)] = &[
+  ("anthropic", "ANTHROPIC_API_KEY"),
+  ("gemini", "GEMINI_API_KEY"),
+  ("groq", "GROQ_API_KEY"),
+  ("ollama", "OLLAMA_API_KEY"),
+  ("openai", "OPENAI_API_KEY"),
+  ("openrouter", "OPENROUTER_API_KEY"),
+];
+
+// Variables a secret can't replace: the subprocesses need the real ones.
+const RESERVED_ENV: &[&str] = &["PATH", "HOME", "SHELL", "USER", "LOGNAME", "TMPDIR", "PWD", "NODE_OPTIONS", "NODE_NO_WARNINGS"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretInfo {
+  pub key: String,
+  #[serde(default)]
+  pub purpose: Option<String>,
+  // 0 for secrets stored before the inventory kept metadata.
+  #[serde(default)]
+  pub created_at_ms: i64,
+  // Last time the value was set; rotating a secret updates it.
+  #[serde(default)]
+  pub updated_at_ms: i64,
+  // Last time the value was handed to a run (exec, OpenClaw, Ollama) or the API server.
+  #[serde(default)]
+  pub last_used_ms: Option<i64>,
+  // Provider this is the API key of (see PROVIDERS).
+  #[serde(default)]
+  pub provider: Option<String>,
+  // Environment variable the value is exported as to exec and to OpenClaw.
+  #[serde(default)]
+  pub env: Option<String>,
+  // Where the value is stored.
+  #[serde(default)]
+  pub backend: SecretBackend,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendStatus {
+  // The profile's setting; None is automatic.
+  pub configured: Option<SecretBackend>,
+  // Where a new secret would go.
+  pub effective: SecretBackend,
+  pub keychain_available: bool,
+  // Why the keychain isn't available.
+  pub keychain_error: Option<String>,
+  pub vault: crate::vault::VaultStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrateResult {
+  pub backend: SecretBackend,
+  pub moved: Vec<String>,
+  pub errors: Vec<String>,
+}
+
+// Editable metadata; `set_meta` replaces all three.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SecretMeta {
+  pub purpose: Option<String>,
+  pub provider: Option<String>,
+  pub env: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretIndex {
+  #[serde(default)]
+  secrets: Vec<SecretInfo>,
+  // The first version only listed keys.
+  #[serde(default, skip_serializing)]
+  keys: Vec<String>,
+}
+
+pub(crate) fn keychain_entry(service: &str, key: &str) -> Result<keyring::Entry> {
+  keyring::Entry::new(service, key).map_err(|e| anyhow!(e))
+}
+
+pub(crate) fn service(profile_id: &str) -> String {
+  format!("openclaw-desktop:{}", profile_id)
+}
+
+// A value written through one keychain entry has to read back through another; a store that
+// only answers (or only keeps values in memory) isn't usable.
+fn probe_keychain() -> Result<()> {
+  const SERVICE: &str = "openclaw-desktop:probe";
+  let value = crate::core::new_id("probe");
+  keychain_entry(SERVICE, "probe")?.set_password(&value).map_err(|e| anyhow!(e))?;
+  let read = keychain_entry(SERVICE, "probe")?.get_password();
+  let _ = keychain_entry(SERVICE, "probe")?.delete_credential();
+  match read {
+    Ok(v) if v == value => Ok(()),
+    Ok(_) => Err(anyhow!("the keychain returned a different value")),
+    Err(keyring::Error::NoEntry) => Err(anyhow!("the keychain doesn't keep values")),
+    Err(e) => Err(anyhow!(e)),
+  }
+}
+
+// Why the OS keychain can't be used, or None when it can. Probed once per process.
+fn keychain_problem() -> Option<String> {
+  static PROBLEM: Lazy<Option<String>> = Lazy::new(|| probe_keychain().err().map(|e| format!("{e:#}")));
+  PROBLEM.clone()
+}
+
+pub fn keychain_available() -> bool {
+  keychain_problem().is_none()
+}
+
+fn effective_backend(st: &Storage, profile_id: &str) -> SecretBackend {
+  let configured = crate::settings::load_settings(st, profile_id).ok().and_then(|s| s.secret_backend);
+  configured.unwrap_or(if keychain_available() { SecretBackend::Keychain } else { SecretBackend::Vault })
+}
+
+fn read_value(dir: &Path, profile_id: &str, backend: SecretBackend, key: &str) -> Result<Option<String>> {
+  match backend {
+    SecretBackend::Keychain => match keychain_entry(&service(profile_id), key)?.get_password() {
+      Ok(v) => Ok(Some(v)),
+      Err(keyring::Error::NoEntry) => Ok(None),
+      Err(e) => Err(anyhow!(e)),
+    },
+    SecretBackend::Vault => crate::vault::get(dir, key),
+  }
+}
+
+// Callers hold the profile dir lock.
+fn write_value(dir: &Path, profile_id: &str, backend: SecretBackend, key: &str, value: &str) -> Result<()> {
+  match backend {
+    SecretBackend::Keychain => keychain_entry(&service(profile_id), key)?.set_password(value).map_err(|e| anyhow!(e)),
+    SecretBackend::Vault => crate::vault::set(dir, key, value),
+  }
+}
+
+// Callers hold the profile dir lock.
+fn delete_value(dir: &Path, profile_id: &str, backend: SecretBackend, key: &str) -> Result<()> {
+  match backend {
+    SecretBackend::Keychain => match keychain_entry(&service(profile_id), key)?.delete_credential() {
+      Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+      Err(e) => Err(anyhow!(e)),
+    },
+    SecretBackend::Vault => crate::vault::delete(dir, key),
+  }
+}
+
+fn load_index(dir: &Path) -> SecretIndex {
+  let mut idx: SecretIndex = fs::read_to_string(dir.join(INDEX_FILE)).ok().and_then(|raw| serde_json::from_str(&raw).ok()).unwrap_or_default();
+  for key in std::mem::take(&mut idx.keys) {
+    if !idx.secrets.iter().any(|s| s.key == key) {
+      idx.secrets.push(SecretInfo { key, ..Default::default() });
+    }
+  }
+  idx
+}
+
+fn save_index(dir: &Path, idx: &SecretIndex) -> Result<()> {
+  write_atomic(&dir.join(INDEX_FILE), serde_json::to_string_pretty(idx)?)
+}
+
+fn load_used(dir: &Path) -> BTreeMap<String, i64> {
+  fs::read_to_string(dir.join(USED_FILE)).ok().and_then(|raw| serde_json::from_str(&raw).ok()).unwrap_or_default()
+}
+
+fn update_index<F: FnOnce(&mut SecretIndex) -> Result<()>>(st: &Storage, profile_id: &str, f: F) -> Result<()> {
+  let dir = st.profile_dir(profile_id)?;
+  let _lock = DirLock::acquire(&dir)?;
+  let mut idx = load_index(&dir);
+  f(&mut idx)?;
+  save_index(&dir, &idx)
+}
+
+// Records that these secrets were just used. Best effort: skipped if another writer holds the
+// file.
+pub fn mark_used(st: &Storage, profile_id: &str, keys: &[&str]) {
+  let Ok(dir) = st.profile_dir(profile_id) else { return };
+  let Ok(Some(_lock)) = try_lock_file(&dir.join("locks").join("secrets_used.lock")) else { return };
+  let now = crate::chats::now_ms();
+  let mut used = load_used(&dir);
+  let mut changed = false;
+  for key in keys {
+    if used.get(*key).is_none_or(|t| now - t >= USED_RESOLUTION_MS) {
+      used.insert(key.to_string(), now);
+      changed = true;
+    }
+  }
+  if changed {
+    if let Ok(raw) = serde_json::to_string_pretty(&used) {
+      let _ = write_atomic(&dir.join(USED_FILE), raw);
+    }
+  }
+}
+
+// The profile's secrets with metadata, oldest first. Values are never included.
+pub fn list(st: &Storage, profile_id: &str) -> Result<Vec<SecretInfo>> {
+  let dir = st.profile_dir(profile_id)?;
+  let used = load_used(&dir);
+  let mut secrets = load_index(&dir).secrets;
+  for s in secrets.iter_mut() {
+    s.last_used_ms = used.get(&s.key).copied();
+  }
+  Ok(secrets)
+}
+
+// Keys of the secrets a profile has stored.
+pub fn secret_keys(st: &Storage, profile_id: &str) -> Result<Vec<String>> {
+  Ok(load_index(&st.profile_dir(profile_id)?).secrets.into_iter().map(|s| s.key).collect())
+}
+
+// Stores a new secret in the profile's backend, or rotates an existing one's value where it is,
+// keeping its metadata.
+pub fn set_secret(st: &Storage, profile_id: &str, key: &str, value: &str) -> Result<()> {
+  if key.trim().is_empty() || key.chars().any(char::is_control) {
+    return Err(anyhow!("invalid secret name"));
+  }
+  let backend = effective_backend(st, profile_id);
+  let dir = st.profile_dir(profile_id)?;
+  let now = crate::chats::now_ms();
+  update_index(st, profile_id, |idx| {
+    match idx.secrets.iter_mut().find(|s| s.key == key) {
+      Some(s) => {
+        write_value(&dir, profile_id, s.backend, key, value)?;
+        s.updated_at_ms = now;
+      }
+      None => {
+        write_value(&dir, profile_id, backend, key, value)?;
+        idx.secrets.push(SecretInfo { key: key.to_string(), created_at_ms: now, updated_at_ms: now, backend, ..Default::default() });
+      }
+    }
+    Ok(())
+  })
+}
+
+fn provider_env(provider: &str) -> Option<&'static str> {
+  PROVIDERS.iter().find(|(p, _)| *p == provider).map(|(_, env)| *env)
+}
+
+fn valid_env_name(name: &str) -> bool {
+  let mut chars = name.chars();
+  chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Environment variables a secret is exported as to OpenClaw.
+fn openclaw_vars(s: &SecretInfo) -> Vec<&str> {
+  s.env.as_deref().into_iter().chain(s.provider.as_deref().and_then(provider_env)).collect()
+}
+
+pub fn set_meta(st: &Storage, profile_id: &str, key: &str, meta: SecretMeta) -> Result<SecretInfo> {
+  let trimmed = |v: Option<String>| v.map(|x| x.trim().to_string()).filter(|x| !x.is_empty());
+  let (purpose, provider, env) = (trimmed(meta.purpose), trimmed(meta.provider).map(|p| p.to_lowercase()), trimmed(meta.env));
+  if let Some(p) = &provider {
+    if provider_env(p).is_none() {
+      let known: Vec<&str> = PROVIDERS.iter().map(|(p, _)| *p).collect();
+      return Err(anyhow!("unknown provider {p} (expected one of {})", known.join(", ")));
+    }
+  }
+  if let Some(e) = &env {
+    if !valid_env_name(e) {
+      return Err(anyhow!("invalid environment variable name {e}"));
+    }
+    if RESERVED_ENV.contains(&e.as_str()) || e.starts_with("LD_") || e.starts_with("DYLD_") {
+      return Err(anyhow!("{e} can't be set from a secret"));
+    }
+  }
+
+  let mut out = None;
+  update_index(st, profile_id, |idx| {
+    let pos = idx.secrets.iter().position(|s| s.key == key).ok_or_else(|| anyhow!("secret not found: {key}"))?;
+    let updated = SecretInfo { purpose, provider, env, ..idx.secrets[pos].clone() };
+    for other in idx.secrets.iter().filter(|s| s.key != key) {
+      if updated.provider.is_some() && other.provider == updated.provider {
+        return Err(anyhow!("secret {} is already the {} key", other.key, other.provider.as_deref().unwrap_or_default()));
+      }
+      if let Some(var) = openclaw_vars(&updated).into_iter().find(|v| openclaw_vars(other).contains(v)) {
+        return Err(anyhow!("secret {} is already exported as {var}", other.key));
+      }
+    }
+    idx.secrets[pos] = updated.clone();
+    out = Some(updated);
+    Ok(())
+  })?;
+  out.ok_or_else(|| anyhow!("secret not found: {key}"))
+}
+
+// Untracked keys are looked up in the keychain, where secrets used to go.
+pub fn get_secret(st: &Storage, profile_id: &str, key: &str) -> Result<Option<String>> {
+  let dir = st.profile_dir(profile_id)?;
+  let backend = load_index(&dir).secrets.into_iter().find(|s| s.key == key).map(|s| s.backend).unwrap_or_default();
+  read_value(&dir, profile_id, backend, key)
+}
+
+pub fn delete_secret(st: &Storage, profile_id: &str, key: &str) -> Result<()> {
+  let dir = st.profile_dir(profile_id)?;
+  update_index(st, profile_id, |idx| {
+    let backend = idx.secrets.iter().find(|s| s.key == key).map(|s| s.backend).unwrap_or_default();
+    delete_value(&dir, profile_id, backend, key)?;
+    idx.secrets.retain(|s| s.key != key);
+    Ok(())
+  })
+}
+
+// Removes the tracked secrets of a profile whose data is in `dir` (which may be in the trash).
+// Vault secrets go with the dir, so only keychain entries are deleted here, along with the chat
+// history key (chat_crypto.rs). Returns the keys removed and the errors for the rest.
+pub(crate) fn remove_secrets(dir: &Path, profile_id: &str) -> (Vec<String>, Vec<String>) {
+  let (mut removed, mut errors) = (vec![], vec![]);
+  for s in load_index(dir).secrets {
+    let res = match s.backend {
+      SecretBackend::Keychain => delete_value(dir, profile_id, s.backend, &s.key),
+      SecretBackend::Vault => Ok(()),
+    };
+    match res {
+      Ok(()) => removed.push(s.key),
+      Err(e) => errors.push(format!("secret {}: {e:#}", s.key)),
+    }
+  }
+  if let Err(e) = crate::chat_crypto::remove_key(dir, profile_id) {
+    errors.push(format!("chat history key: {e:#}"));
+  }
+  (removed, errors)
+}
+
+// Copies the keychain entries of the profile whose data is in `dir` from profile `from`'s service
+// to `to`'s: the tracked keychain secrets and the chat history keys. Returns the errors for the
+// ones not copied.
+pub(crate) fn copy_keychain(dir: &Path, from: &str, to: &str) -> Vec<String> {
+  let mut errors = vec![];
+  for s in load_index(dir).secrets.into_iter().filter(|s| s.backend == SecretBackend::Keychain) {
+    let copied = match read_value(dir, from, s.backend, &s.key) {
+      Ok(Some(v)) => write_value(dir, to, s.backend, &s.key, &v),
+      Ok(None) => Ok(()),
+      Err(e) => Err(e),
+    };
+    if let Err(e) = copied {
+      errors.push(format!("secret {}: {e:#}", s.key));
+    }
+  }
+  if let Err(e) = crate::chat_crypto::copy_keychain_keys(dir, from, to) {
+    errors.push(format!("chat history key: {e:#}"));
+  }
+  errors
+}
+
+pub fn backend_status(st: &Storage, profile_id: &str) -> Result<BackendStatus> {
+  Ok(BackendStatus {
+    configured: crate::settings::load_settings(st, profile_id)?.secret_backend,
+    effective: effective_backend(st, profile_id),
+    keychain_available: keychain_available(),
+    keychain_error: keychain_problem(),
+    vault: crate::vault::status(st, profile_id)?,
+  })
+}
+
+// Moves every secret stored elsewhere to `to` and makes it the profile's backend. A secret that
+// fails to move stays where it is and is reported.
+pub fn migrate(st: &Storage, profile_id: &str, to: SecretBackend) -> Result<MigrateResult> {
+  let dir = st.profile_dir(profile_id)?;
+  let mut result = MigrateResult { backend: to, moved: vec![], errors: vec![] };
+  update_index(st, profile_id, |idx| {
+    for s in idx.secrets.iter_mut().filter(|s| s.backend != to) {
+      let moved = read_value(&dir, profile_id, s.backend, &s.key)
+        .and_then(|v| v.ok_or_else(|| anyhow!("no value stored in the {:?} backend", s.backend)))
+        .and_then(|v| write_value(&dir, profile_id, to, &s.key, &v));
+      match moved {
+        Ok(()) => {
+          if let Err(e) = delete_value(&dir, profile_id, s.backend, &s.key) {
+            result.errors.push(format!("secret {}: moved, but the old copy remains: {e:#}", s.key));
+          }
+          s.backend = to;
+          result.moved.push(s.key.clone());
+        }
+        Err(e) => result.errors.push(format!("secret {}: {e:#}", s.key)),
+      }
+    }
+    Ok(())
+  })?;
+  crate::settings::update_settings(st, profile_id, |s| s.secret_backend = Some(to))?;
+  Ok(result)
+}
+
+// Values of the secrets `pick` selects, and why the unreadable ones were skipped: a locked or
+// missing keychain entry shouldn't stop a run.
+fn values(st: &Storage, profile_id: &str, pick: impl Fn(&SecretInfo) -> bool) -> (Vec<(SecretInfo, String)>, Vec<String>) {
+  let (mut loaded, mut skipped) = (vec![], vec![]);
+  for s in list(st, profile_id).unwrap_or_default().into_iter().filter(|s| pick(s)) {
+    match get_secret(st, profile_id, &s.key) {
+      Ok(Some(v)) => loaded.push((s, v)),
+      Ok(None) => {}
+      Err(e) => skipped.push(format!("secret {} skipped: {e:#}", s.key)),
+    }
+  }
+  (loaded, skipped)
+}
+
+// Environment for the profile's OpenClaw subprocesses: `env` mappings plus provider keys.
+// Secrets that can't be read are left out.
+pub fn openclaw_env(st: &Storage, profile_id: &str) -> Vec<(String, String)> {
+  let (loaded, _) = values(st, profile_id, |s| !openclaw_vars(s).is_empty());
+  let keys: Vec<&str> = loaded.iter().map(|(s, _)| s.key.as_str()).collect();
+  mark_used(st, profile_id, &keys);
+  loaded.iter().flat_map(|(s, v)| openclaw_vars(s).into_iter().map(move |var| (var.to_string(), v.clone()))).collect()
+}
+
+// What one chat tool-loop run gets from the profile's secrets.
+#[derive(Debug, Clone, Default)]
+pub struct RunSecrets {
+  // Exported to exec commands.
+  pub exec_env: Vec<(String, String)>,
+  // Bearer token for the Ollama server.
+  pub ollama_key: Option<String>,
+  // (key, value) of the secrets above, redacted from stored tool output.
+  pub values: Vec<(String, String)>,
+  // Secrets that couldn't be read, with why; the run goes on without them.
+  pub skipped: Vec<String>,
+}
+
+impl RunSecrets {
+  // Reads only the secrets the run is given: those with an `env` mapping and the Ollama key.
+  pub fn for_profile(st: &Storage, profile_id: &str) -> Self {
+    let (loaded, skipped) = values(st, profile_id, |s| s.env.is_some() || s.provider.as_deref() == Some("ollama"));
+    let used: Vec<&str> = loaded.iter().map(|(s, _)| s.key.as_str()).collect();
+    mark_used(st, profile_id, &used);
+    Self {
+      exec_env: loaded.iter().filter_map(|(s, v)| Some((s.env.clone()?, v.clone()))).collect(),
+      ollama_key: loaded.iter().find(|(s, _)| s.provider.as_deref() == Some("ollama")).map(|(_, v)| v.clone()),
+      values: loaded.into_iter().map(|(s, v)| (s.key, v)).collect(),
+      skipped,
+    }
+  }
+
+  // Replaces every secret value in `text` with `[redacted:{key}]`, longest values first so a
+  // value containing another is replaced whole.
+  pub fn redact(&self, text: &str) -> String {
+    let mut values: Vec<&(String, String)> = self.values.iter().filter(|(_, v)| v.len() >= MIN_REDACT_LEN).collect();
+    values.sort_by_key(|(_, v)| std::cmp::Reverse(v.len()));
+    values.into_iter().fold(text.to_string(), |out, (k, v)| out.replace(v.as_str(), &format!("[redacted:{k}]")))
+  }
+}
+
+#[tauri::command]
+pub fn secrets_list(storage: State<'_, Storage>, profile_id: String) -> Result<Vec<SecretInfo>, String> {
+  list(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn secret_set(storage: State<'_, Storage>, profile_id: String, key: String, value: String) -> Result<(), String> {
+  set_secret(&storage, &profile_id, &key, &value).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn secret_set_meta(storage: State<'_, Storage>, profile_id: String, key: String, meta: SecretMeta) -> Result<SecretInfo, String> {
+  set_meta(&storage, &profile_id, &key, meta).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn secret_get(storage: State<'_, Storage>, profile_id: String, key: String) -> Result<Option<String>, String> {
+  get_secret(&storage, &profile_id, &key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn secrets_backend_status(storage: State<'_, Storage>, profile_id: String) -> Result<BackendStatus, String> {
+  backend_status(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn secrets_migrate(storage: State<'_, Storage>, profile_id: String, to: SecretBackend) -> Result<MigrateResult, String> {
+  migrate(&storage, &profile_id, to).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn secret_delete(storage: State<'_, Storage>, profile_id: String, key: String) -> Result<(), String> {
+  delete_secret(&storage, &profile_id, &key).map_err(|e| e.to_string())
+}
+
+// Providers a secret can be mapped to, with the environment variable each is exported as.
+#[tauri::command]
+pub fn secret_providers() -> Vec<(String, String)> {
+  PROVIDERS.iter().map(|(p, e)| (p.to_string(), e.to_string())).collect()
+}
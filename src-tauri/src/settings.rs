@@ -1,18 +1,104 @@
-use std::{fs, path::PathBuf, process::Command};
+use std::{fs, path::PathBuf};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
+use tauri::State;
 
-fn profile_dir(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  let base = app.path().app_data_dir().context("app_data_dir not available")?;
-  let dir = base.join("profiles").join(profile_id);
-  fs::create_dir_all(&dir).context("failed to create profile data dir")?;
-  Ok(dir)
+use crate::core::{write_atomic, DirLock, Storage};
+
+fn settings_path(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  Ok(st.profile_dir(profile_id)?.join("settings.json"))
+}
+
+// Bounds for the chat tool loop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentLimits {
+  pub max_steps: u32,
+  pub max_wall_secs: u64,
+  pub max_tool_output_bytes: u64,
+}
+
+impl Default for AgentLimits {
+  fn default() -> Self {
+    Self { max_steps: 6, max_wall_secs: 600, max_tool_output_bytes: 512 * 1024 }
+  }
+}
+
+impl AgentLimits {
+  pub fn validate(&self) -> Result<()> {
+    if self.max_steps == 0 || self.max_wall_secs == 0 || self.max_tool_output_bytes == 0 {
+      return Err(anyhow::anyhow!("limits must be greater than zero"));
+    }
+    Ok(())
+  }
 }
 
-fn settings_path(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  Ok(profile_dir(app, profile_id)?.join("settings.json"))
+// Local OpenAI-compatible API (see api_server.rs). Requests authenticate with the profile's
+// `api_token` secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiServerConfig {
+  // Listens on 127.0.0.1 only.
+  pub port: u16,
+  // Chat (id or title) that requests are written into; created on first use. None runs each
+  // request in a throwaway thread using only the messages the client sent.
+  #[serde(default)]
+  pub chat: Option<String>,
+}
+
+// Gateway supervision (see gateway_supervisor.rs).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewaySupervisorConfig {
+  pub poll_secs: u64,
+  // Delay before the first restart after a crash; doubles per failed attempt up to backoff_max_secs.
+  pub backoff_base_secs: u64,
+  pub backoff_max_secs: u64,
+  // Polled instead of `openclaw gateway status` when set; any 2xx counts as healthy.
+  #[serde(default)]
+  pub health_url: Option<String>,
+}
+
+impl Default for GatewaySupervisorConfig {
+  fn default() -> Self {
+    Self { poll_secs: 15, backoff_base_secs: 2, backoff_max_secs: 300, health_url: None }
+  }
+}
+
+impl GatewaySupervisorConfig {
+  pub fn validate(&self) -> Result<()> {
+    if self.poll_secs == 0 || self.backoff_base_secs == 0 {
+      return Err(anyhow::anyhow!("intervals must be greater than zero"));
+    }
+    if self.backoff_max_secs < self.backoff_base_secs {
+      return Err(anyhow::anyhow!("backoff_max_secs must be at least backoff_base_secs"));
+    }
+    if let Some(u) = &self.health_url {
+      if !(u.starts_with("http://") || u.starts_with("https://")) {
+        return Err(anyhow::anyhow!("health_url must be an http(s) URL"));
+      }
+    }
+    Ok(())
+  }
+}
+
+// Where chat replies come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatBackend {
+  // The in-process tool loop talking to Ollama directly.
+  Ollama,
+  // `openclaw agent --session-id <chat session>`; OpenClaw keeps the conversation state.
+  Openclaw,
+}
+
+// Where secret values are stored (see secrets.rs).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+  // The OS keychain (Keychain, Secret Service, Credential Manager).
+  #[default]
+  Keychain,
+  // The passphrase-encrypted file in the profile dir (vault.rs).
+  Vault,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -24,16 +110,55 @@ pub struct ProfileSettings {
   // Local model (Ollama)
   pub ollama_base_url: Option<String>,
   pub ollama_model: Option<String>,
+  // Context window for chat requests, in tokens. None keeps the model's default.
+  #[serde(default)]
+  pub ollama_num_ctx: Option<u32>,
 
   // Dangerous: allows unrestricted exec with no prompts.
   pub dev_full_exec_auto: Option<bool>,
 
   // When enabled, actiony user requests default to tool execution.
   pub auto_do_mode: Option<bool>,
+
+  // When enabled, saved memories (MEMORY.md) are injected into the system prompt.
+  pub memory_inject: Option<bool>,
+
+  // Custom system prompt template ({{date}}, {{os}}, {{cwd}}, {{tools}}, {{workspace_files}}).
+  // None means the built-in default; tool-calling instructions are always appended.
+  #[serde(default)]
+  pub system_prompt_template: Option<String>,
+
+  // How auto-do mode detects action requests. None means the default keyword rules.
+  #[serde(default)]
+  pub intent: Option<crate::intent::IntentConfig>,
+
+  // Tool loop limits. None means AgentLimits::default().
+  #[serde(default)]
+  pub agent_limits: Option<AgentLimits>,
+
+  // Opt-in local API server. None means disabled.
+  #[serde(default)]
+  pub api_server: Option<ApiServerConfig>,
+
+  // Background gateway supervisor. None means the gateway is not watched.
+  #[serde(default)]
+  pub gateway_supervisor: Option<GatewaySupervisorConfig>,
+
+  // Node used to run openclaw. None picks the one next to openclaw, or the first found.
+  #[serde(default)]
+  pub node_path: Option<String>,
+
+  // Default backend for chats without their own. None means Ollama.
+  #[serde(default)]
+  pub chat_backend: Option<ChatBackend>,
+
+  // Where new secrets go. None picks the keychain when it works, else the vault.
+  #[serde(default)]
+  pub secret_backend: Option<SecretBackend>,
 }
 
-pub fn load_settings(app: &AppHandle, profile_id: &str) -> Result<ProfileSettings> {
-  let path = settings_path(app, profile_id)?;
+pub fn load_settings(st: &Storage, profile_id: &str) -> Result<ProfileSettings> {
+  let path = settings_path(st, profile_id)?;
   if !path.exists() {
     return Ok(ProfileSettings {
       version: 1,
@@ -41,8 +166,18 @@ pub fn load_settings(app: &AppHandle, profile_id: &str) -> Result<ProfileSetting
       openclaw_profile: None,
       ollama_base_url: Some("http://localhost:11434".to_string()),
       ollama_model: Some("ollama/huihui_ai/qwen3-abliterated:8b".to_string()),
+      ollama_num_ctx: None,
       dev_full_exec_auto: Some(false),
       auto_do_mode: Some(false),
+      memory_inject: Some(false),
+      system_prompt_template: None,
+      intent: None,
+      agent_limits: None,
+      api_server: None,
+      gateway_supervisor: None,
+      node_path: None,
+      chat_backend: None,
+      secret_backend: None,
     });
   }
   let raw = fs::read_to_string(&path).context("failed to read settings.json")?;
@@ -66,142 +201,177 @@ pub fn load_settings(app: &AppHandle, profile_id: &str) -> Result<ProfileSetting
   if s.auto_do_mode.is_none() {
     s.auto_do_mode = Some(false);
   }
+  if s.memory_inject.is_none() {
+    s.memory_inject = Some(false);
+  }
   Ok(s)
 }
 
-pub fn save_settings(app: &AppHandle, profile_id: &str, s: &ProfileSettings) -> Result<()> {
-  let path = settings_path(app, profile_id)?;
+pub fn save_settings(st: &Storage, profile_id: &str, s: &ProfileSettings) -> Result<()> {
+  let path = settings_path(st, profile_id)?;
   let raw = serde_json::to_string_pretty(s).context("failed to serialize settings")?;
-  fs::write(&path, raw).context("failed to write settings.json")?;
+  write_atomic(&path, raw).context("failed to write settings.json")?;
   Ok(())
 }
 
-fn which(cmd: &str) -> Result<Option<String>> {
-  let out = Command::new("/usr/bin/which").arg(cmd).output();
-  match out {
-    Ok(o) if o.status.success() => {
-      let p = String::from_utf8_lossy(&o.stdout).trim().to_string();
-      if p.is_empty() {
-        Ok(None)
-      } else {
-        Ok(Some(p))
-      }
+pub fn is_node_script(path: &PathBuf) -> bool {
+  let s = path.to_string_lossy();
+  s.ends_with(".mjs") || s.ends_with(".js")
+}
+
+// The pinned `openclaw_path`, or the first install found (see discovery.rs).
+pub fn resolve_openclaw_bin(st: &Storage, profile_id: &str) -> Result<PathBuf> {
+  crate::discovery::resolve_openclaw(st, profile_id)
+}
+
+// The OpenClaw profile the app derives from a local profile id, used unless settings pin another.
+// Keep it short-ish and filesystem-safe.
+pub fn derived_openclaw_profile(profile_id: &str) -> String {
+  let safe = profile_id
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+    .collect::<String>();
+  format!("ocd-{safe}")
+}
+
+pub fn resolve_openclaw_profile(st: &Storage, profile_id: &str) -> Result<String> {
+  let s = load_settings(st, profile_id).unwrap_or_default();
+  if let Some(p) = s.openclaw_profile {
+    let t = p.trim().to_string();
+    if !t.is_empty() {
+      return Ok(t);
     }
-    _ => Ok(None),
   }
+
+  Ok(derived_openclaw_profile(profile_id))
 }
 
-pub fn resolve_node_bin() -> Result<PathBuf> {
-  if let Some(p) = which("node")? {
-    return Ok(PathBuf::from(p));
-  }
+// Load, apply `f`, save. Returns the saved settings.
+pub fn update_settings<F: FnOnce(&mut ProfileSettings)>(st: &Storage, profile_id: &str, f: F) -> Result<ProfileSettings> {
+  let _lock = DirLock::acquire(&st.profile_dir(profile_id)?)?;
+  let mut s = load_settings(st, profile_id)?;
+  f(&mut s);
+  save_settings(st, profile_id, &s)?;
+  Ok(s)
+}
 
-  // Prefer Homebrew node if present.
-  let brew = PathBuf::from("/opt/homebrew/bin/node");
-  if brew.exists() {
-    return Ok(brew);
+#[tauri::command]
+pub fn settings_get(storage: State<'_, Storage>, profile_id: String) -> Result<ProfileSettings, String> {
+  load_settings(&storage, &profile_id).map_err(|e| e.to_string())
+}
+
+// Trims a path setting; a set path must exist.
+fn pinned_path(path: Option<String>) -> Result<Option<String>> {
+  let Some(p) = path.map(|x| x.trim().to_string()).filter(|x| !x.is_empty()) else {
+    return Ok(None);
+  };
+  if !PathBuf::from(&p).exists() {
+    return Err(anyhow::anyhow!("{p} does not exist"));
   }
+  Ok(Some(p))
+}
 
-  // Fallback to the known NVM location in this environment.
-  Ok(PathBuf::from("/Users/aasish/.nvm/versions/node/v22.22.0/bin/node"))
+#[tauri::command]
+pub fn settings_set_openclaw_path(storage: State<'_, Storage>, profile_id: String, openclaw_path: Option<String>) -> Result<ProfileSettings, String> {
+  let openclaw_path = pinned_path(openclaw_path).map_err(|e| e.to_string())?;
+  update_settings(&storage, &profile_id, |s| {
+    s.version = 1;
+    s.openclaw_path = openclaw_path;
+  })
+  .map_err(|e| e.to_string())
 }
 
-pub fn is_node_script(path: &PathBuf) -> bool {
-  let s = path.to_string_lossy();
-  s.ends_with(".mjs") || s.ends_with(".js")
+#[tauri::command]
+pub fn settings_set_node_path(storage: State<'_, Storage>, profile_id: String, node_path: Option<String>) -> Result<ProfileSettings, String> {
+  let node_path = pinned_path(node_path).map_err(|e| e.to_string())?;
+  update_settings(&storage, &profile_id, |s| s.node_path = node_path).map_err(|e| e.to_string())
 }
 
-pub fn resolve_openclaw_bin(app: &AppHandle, profile_id: &str) -> Result<PathBuf> {
-  let s = load_settings(app, profile_id).unwrap_or_default();
-  if let Some(p) = s.openclaw_path {
-    return Ok(PathBuf::from(p));
-  }
+#[tauri::command]
+pub fn settings_set_chat_backend(storage: State<'_, Storage>, profile_id: String, backend: Option<ChatBackend>) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| s.chat_backend = backend).map_err(|e| e.to_string())
+}
 
-  if let Some(p) = which("openclaw")? {
-    return Ok(PathBuf::from(p));
-  }
+#[tauri::command]
+pub fn settings_set_ollama_base_url(storage: State<'_, Storage>, profile_id: String, ollama_base_url: Option<String>) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| {
+    s.ollama_base_url = ollama_base_url.and_then(|x| {
+      let t = x.trim().to_string();
+      if t.is_empty() { None } else { Some(t) }
+    });
+  })
+  .map_err(|e| e.to_string())
+}
 
-  // Fallback to the known location in this environment.
-  Ok(PathBuf::from("/Users/aasish/.nvm/versions/node/v22.22.0/bin/openclaw"))
+#[tauri::command]
+pub fn settings_set_ollama_model(storage: State<'_, Storage>, profile_id: String, ollama_model: Option<String>) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| {
+    s.ollama_model = ollama_model.and_then(|x| {
+      let t = x.trim().to_string();
+      if t.is_empty() { None } else { Some(t) }
+    });
+  })
+  .map_err(|e| e.to_string())
 }
 
-pub fn resolve_openclaw_profile(app: &AppHandle, profile_id: &str) -> Result<String> {
-  let s = load_settings(app, profile_id).unwrap_or_default();
-  if let Some(p) = s.openclaw_profile {
-    let t = p.trim().to_string();
-    if !t.is_empty() {
-      return Ok(t);
-    }
-  }
+// None (or 0) goes back to the model's default context window.
+#[tauri::command]
+pub fn settings_set_ollama_num_ctx(storage: State<'_, Storage>, profile_id: String, num_ctx: Option<u32>) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| s.ollama_num_ctx = num_ctx.filter(|n| *n > 0)).map_err(|e| e.to_string())
+}
 
-  // Derive a stable profile name from our local profile id.
-  // Keep it short-ish and filesystem-safe.
-  let safe = profile_id
-    .chars()
-    .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
-    .collect::<String>();
-  Ok(format!("ocd-{safe}"))
+#[tauri::command]
+pub fn settings_set_dev_full_exec_auto(storage: State<'_, Storage>, profile_id: String, enabled: bool) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| s.dev_full_exec_auto = Some(enabled)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn settings_get(app: AppHandle, profile_id: String) -> Result<ProfileSettings, String> {
-  load_settings(&app, &profile_id).map_err(|e| e.to_string())
+pub fn settings_set_auto_do_mode(storage: State<'_, Storage>, profile_id: String, enabled: bool) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| s.auto_do_mode = Some(enabled)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn settings_set_openclaw_path(app: AppHandle, profile_id: String, openclaw_path: Option<String>) -> Result<ProfileSettings, String> {
-  let mut s = load_settings(&app, &profile_id).unwrap_or(ProfileSettings {
-    version: 1,
-    openclaw_path: None,
-    openclaw_profile: None,
-    ollama_base_url: Some("http://localhost:11434".to_string()),
-    ollama_model: Some("ollama/huihui_ai/qwen3-abliterated:8b".to_string()),
-    dev_full_exec_auto: Some(false),
-    auto_do_mode: Some(false),
-  });
-  s.version = 1;
-  s.openclaw_path = openclaw_path.and_then(|x| {
-    let t = x.trim().to_string();
-    if t.is_empty() { None } else { Some(t) }
-  });
-  save_settings(&app, &profile_id, &s).map_err(|e| e.to_string())?;
-  Ok(s)
+pub fn settings_set_memory_inject(storage: State<'_, Storage>, profile_id: String, enabled: bool) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| s.memory_inject = Some(enabled)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn settings_set_ollama_base_url(app: AppHandle, profile_id: String, ollama_base_url: Option<String>) -> Result<ProfileSettings, String> {
-  let mut s = load_settings(&app, &profile_id).map_err(|e| e.to_string())?;
-  s.ollama_base_url = ollama_base_url.and_then(|x| {
-    let t = x.trim().to_string();
-    if t.is_empty() { None } else { Some(t) }
-  });
-  save_settings(&app, &profile_id, &s).map_err(|e| e.to_string())?;
-  Ok(s)
+pub fn settings_set_system_prompt_template(storage: State<'_, Storage>, profile_id: String, template: Option<String>) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| s.system_prompt_template = template.filter(|t| !t.trim().is_empty())).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn settings_set_ollama_model(app: AppHandle, profile_id: String, ollama_model: Option<String>) -> Result<ProfileSettings, String> {
-  let mut s = load_settings(&app, &profile_id).map_err(|e| e.to_string())?;
-  s.ollama_model = ollama_model.and_then(|x| {
-    let t = x.trim().to_string();
-    if t.is_empty() { None } else { Some(t) }
-  });
-  save_settings(&app, &profile_id, &s).map_err(|e| e.to_string())?;
-  Ok(s)
+pub fn settings_set_intent(storage: State<'_, Storage>, profile_id: String, intent: Option<crate::intent::IntentConfig>) -> Result<ProfileSettings, String> {
+  update_settings(&storage, &profile_id, |s| s.intent = intent).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn settings_set_dev_full_exec_auto(app: AppHandle, profile_id: String, enabled: bool) -> Result<ProfileSettings, String> {
-  let mut s = load_settings(&app, &profile_id).map_err(|e| e.to_string())?;
-  s.dev_full_exec_auto = Some(enabled);
-  save_settings(&app, &profile_id, &s).map_err(|e| e.to_string())?;
-  Ok(s)
+pub fn settings_set_agent_limits(storage: State<'_, Storage>, profile_id: String, limits: Option<AgentLimits>) -> Result<ProfileSettings, String> {
+  if let Some(l) = &limits {
+    l.validate().map_err(|e| e.to_string())?;
+  }
+  update_settings(&storage, &profile_id, |s| s.agent_limits = limits).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn settings_set_auto_do_mode(app: AppHandle, profile_id: String, enabled: bool) -> Result<ProfileSettings, String> {
-  let mut s = load_settings(&app, &profile_id).map_err(|e| e.to_string())?;
-  s.auto_do_mode = Some(enabled);
-  save_settings(&app, &profile_id, &s).map_err(|e| e.to_string())?;
-  Ok(s)
+pub fn settings_set_api_server(storage: State<'_, Storage>, profile_id: String, config: Option<ApiServerConfig>) -> Result<ProfileSettings, String> {
+  if config.as_ref().is_some_and(|c| c.port == 0) {
+    return Err("port must be between 1 and 65535".to_string());
+  }
+  let config = config.map(|mut c| {
+    c.chat = c.chat.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    c
+  });
+  update_settings(&storage, &profile_id, |s| s.api_server = config).map_err(|e| e.to_string())
+}
+
+// Replacing a supervisor joins its worker, which may be mid-restart: not on the main thread.
+#[tauri::command(async)]
+pub fn settings_set_gateway_supervisor(app: tauri::AppHandle, storage: State<'_, Storage>, profile_id: String, config: Option<GatewaySupervisorConfig>) -> Result<ProfileSettings, String> {
+  if let Some(c) = &config {
+    c.validate().map_err(|e| e.to_string())?;
+  }
+  let saved = update_settings(&storage, &profile_id, |s| s.gateway_supervisor = config).map_err(|e| e.to_string())?;
+  crate::gateway_supervisor::supervise(&storage, std::sync::Arc::new(app), &profile_id).map_err(|e| e.to_string())?;
+  Ok(saved)
 }
@@ -7,15 +7,21 @@ pub struct Profile {
   pub created_at_ms: i64,
 }
 
+// Version 2: ids are collision-free (core::new_id) and unique within each store.
+pub const PROFILES_STORE_VERSION: i32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfilesStore {
   pub version: i32,
   pub active_profile_id: Option<String>,
   pub profiles: Vec<Profile>,
+  // What the id migration changed (see profiles::migrate_ids), kept until the user dismisses it.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub migration_notes: Vec<String>,
 }
 
 impl Default for ProfilesStore {
   fn default() -> Self {
-    Self { version: 1, active_profile_id: None, profiles: vec![] }
+    Self { version: PROFILES_STORE_VERSION, active_profile_id: None, profiles: vec![], migration_notes: vec![] }
   }
 }
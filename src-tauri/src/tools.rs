@@ -8,12 +8,37 @@ pub enum ToolCall {
   Exec { cmd: String },
   WebGet { url: String },
   Final { text: String },
+  Remember { text: String },
+  Recall {
+    #[serde(default)]
+    query: String,
+  },
+  // By id only: the model can't bulk-delete by a loose match.
+  Forget {
+    #[serde(default)]
+    id: String,
+  },
 }
 
-pub fn exec(cmd: &str, cwd: &std::path::Path) -> Result<String> {
+impl ToolCall {
+  pub fn name(&self) -> &'static str {
+    match self {
+      ToolCall::Exec { .. } => "exec",
+      ToolCall::WebGet { .. } => "web_get",
+      ToolCall::Final { .. } => "final",
+      ToolCall::Remember { .. } => "remember",
+      ToolCall::Recall { .. } => "recall",
+      ToolCall::Forget { .. } => "forget",
+    }
+  }
+}
+
+// `env` adds variables on top of the app's environment (secrets mapped to env vars).
+pub fn exec(cmd: &str, cwd: &std::path::Path, env: &[(String, String)]) -> Result<String> {
   // zsh -lc for PATH + shell features.
   let out = Command::new("/bin/zsh")
     .current_dir(cwd)
+    .envs(env.iter().map(|(k, v)| (k, v)))
     .arg("-lc")
     .arg(cmd)
     .output()
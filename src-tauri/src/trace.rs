@@ -0,0 +1,170 @@
+use std::{
+  fs,
+  path::PathBuf,
+  time::Instant,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::core::{write_atomic, DirLock, Storage};
+use crate::settings::AgentLimits;
+
+// Per-message record of what the tool loop did, plus the budget that bounds it.
+
+fn now_ms() -> i64 {
+  let dur = std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .unwrap_or_default();
+  dur.as_millis() as i64
+}
+
+fn traces_path(st: &Storage, profile_id: &str, chat_id: &str) -> Result<PathBuf> {
+  let dir = st.profile_dir(profile_id)?.join("traces");
+  fs::create_dir_all(&dir).context("failed to create traces dir")?;
+  Ok(dir.join(format!("chat_{chat_id}.json")))
+}
+
+// Why the loop paused. Stored on the assistant message so the UI can offer "continue".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopPause {
+  // "steps" | "wall_time" | "tool_output"
+  pub limit: String,
+  pub detail: String,
+  pub steps_taken: u32,
+}
+
+pub struct LoopBudget {
+  limits: AgentLimits,
+  started: Instant,
+  pub steps: u32,
+  pub tool_output_bytes: u64,
+}
+
+impl LoopBudget {
+  pub fn new(limits: AgentLimits) -> Self {
+    Self { limits, started: Instant::now(), steps: 0, tool_output_bytes: 0 }
+  }
+
+  // Checked before each model call.
+  pub fn check(&self) -> Option<LoopPause> {
+    let (limit, detail) = if self.steps >= self.limits.max_steps {
+      ("steps", format!("reached {} tool steps", self.limits.max_steps))
+    } else if self.started.elapsed().as_secs() >= self.limits.max_wall_secs {
+      ("wall_time", format!("ran for more than {}s", self.limits.max_wall_secs))
+    } else if self.tool_output_bytes >= self.limits.max_tool_output_bytes {
+      ("tool_output", format!("tools returned more than {} bytes", self.limits.max_tool_output_bytes))
+    } else {
+      return None;
+    };
+    Some(LoopPause { limit: limit.to_string(), detail, steps_taken: self.steps })
+  }
+
+  pub fn add_tool_output(&mut self, out: &str) {
+    self.tool_output_bytes += out.len() as u64;
+  }
+}
+
+pub fn pause_text(p: &LoopPause) -> String {
+  format!("[paused] Stopped after {} step(s): {}. Continue to let the agent keep going.", p.steps_taken, p.detail)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceStep {
+  pub index: u32,
+  pub prompt_messages: usize,
+  pub prompt_chars: usize,
+  pub model_latency_ms: u64,
+  pub response_chars: usize,
+  pub tool: Option<String>,
+  pub result_bytes: Option<usize>,
+  pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentTrace {
+  pub message_id: String,
+  pub started_at_ms: i64,
+  pub finished_at_ms: Option<i64>,
+  // "final" | "paused" | "error"
+  pub outcome: Option<String>,
+  pub steps: Vec<TraceStep>,
+  // Problems that didn't stop the run, e.g. a secret that couldn't be read.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub warnings: Vec<String>,
+}
+
+impl AgentTrace {
+  pub fn new(message_id: &str) -> Self {
+    Self {
+      message_id: message_id.to_string(),
+      started_at_ms: now_ms(),
+      finished_at_ms: None,
+      outcome: None,
+      steps: vec![],
+      warnings: vec![],
+    }
+  }
+
+  pub fn finish(&mut self, outcome: &str) {
+    self.finished_at_ms = Some(now_ms());
+    self.outcome = Some(outcome.to_string());
+  }
+
+  // Attaches the tool outcome to the most recent step.
+  pub fn tool(&mut self, tool: &str, result_bytes: usize, note: Option<&str>) {
+    if let Some(s) = self.steps.last_mut() {
+      s.tool = Some(tool.to_string());
+      s.result_bytes = Some(result_bytes);
+      s.note = note.map(|n| n.to_string());
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TraceFile {
+  version: i32,
+  traces: Vec<AgentTrace>,
+}
+
+const MAX_TRACES_PER_CHAT: usize = 50;
+
+fn load_file(st: &Storage, profile_id: &str, chat_id: &str) -> Result<TraceFile> {
+  let path = traces_path(st, profile_id, chat_id)?;
+  if !path.exists() {
+    return Ok(TraceFile { version: 1, traces: vec![] });
+  }
+  let raw = fs::read_to_string(&path).context("failed to read trace file")?;
+  serde_json::from_str(&raw).context("failed to parse trace file")
+}
+
+pub fn save_trace(st: &Storage, profile_id: &str, chat_id: &str, trace: &AgentTrace) -> Result<()> {
+  let _lock = DirLock::acquire(&st.profile_dir(profile_id)?.join("traces"))?;
+  let mut f = load_file(st, profile_id, chat_id)?;
+  f.traces.retain(|t| t.message_id != trace.message_id);
+  f.traces.push(trace.clone());
+  let extra = f.traces.len().saturating_sub(MAX_TRACES_PER_CHAT);
+  f.traces.drain(..extra);
+
+  let path = traces_path(st, profile_id, chat_id)?;
+  let raw = serde_json::to_string_pretty(&f).context("failed to serialize trace file")?;
+  write_atomic(&path, raw).context("failed to write trace file")?;
+  Ok(())
+}
+
+pub fn delete_traces(st: &Storage, profile_id: &str, chat_id: &str) {
+  if let Ok(p) = traces_path(st, profile_id, chat_id) {
+    let _ = fs::remove_file(p);
+  }
+}
+
+pub fn load_trace(st: &Storage, profile_id: &str, chat_id: &str, message_id: &str) -> Result<Option<AgentTrace>> {
+  let f = load_file(st, profile_id, chat_id)?;
+  Ok(f.traces.into_iter().find(|t| t.message_id == message_id))
+}
+
+#[tauri::command]
+pub fn chat_trace(storage: State<'_, Storage>, profile_id: String, chat_id: String, message_id: String) -> Result<Option<AgentTrace>, String> {
+  load_trace(&storage, &profile_id, &chat_id, &message_id).map_err(|e| e.to_string())
+}
@@ -1,16 +1,17 @@
-use tauri::{AppHandle, Manager, Emitter};
+use tauri::{AppHandle, Emitter, Listener, Manager, Wry};
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{TrayIconBuilder, TrayIconEvent};
 
 pub fn init_tray(app: &AppHandle) -> tauri::Result<()> {
+  let gateway = MenuItem::with_id(app, "gateway_state", "Gateway: unknown", false, None::<&str>)?;
   let open = MenuItem::with_id(app, "open", "Open", true, None::<&str>)?;
   let new_chat = MenuItem::with_id(app, "new_chat", "New chat", true, None::<&str>)?;
   let restart_gateway = MenuItem::with_id(app, "restart_gateway", "Restart gateway", true, None::<&str>)?;
   let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 
-  let menu = Menu::with_items(app, &[&open, &new_chat, &restart_gateway, &quit])?;
+  let menu = Menu::with_items(app, &[&gateway, &open, &new_chat, &restart_gateway, &quit])?;
 
-  let _tray = TrayIconBuilder::new()
+  let _tray = TrayIconBuilder::with_id(TRAY_ID)
     .menu(&menu)
     .show_menu_on_left_click(true)
     .on_tray_icon_event(|tray: &tauri::tray::TrayIcon, event: TrayIconEvent| {
@@ -40,9 +41,40 @@ pub fn init_tray(app: &AppHandle) -> tauri::Result<()> {
     })
     .build(app)?;
 
+  let handle = app.clone();
+  app.listen_any(crate::gateway_supervisor::STATE_EVENT, move |ev| {
+    if let Ok(state) = serde_json::from_str::<serde_json::Value>(ev.payload()) {
+      show_gateway_state(&handle, &gateway, &state);
+    }
+  });
+
   Ok(())
 }
 
+const TRAY_ID: &str = "main";
+
+// Mirrors the active profile's gateway state in the menu and tooltip.
+fn show_gateway_state(app: &AppHandle, item: &MenuItem<Wry>, state: &serde_json::Value) {
+  let Some(storage) = app.try_state::<crate::core::Storage>() else { return };
+  // Runs on the emitting supervisor's thread: no root lock here (see profiles::active_profile_id).
+  let active = crate::profiles::active_profile_id(&storage);
+  if active.as_deref() != state["profile_id"].as_str() {
+    return;
+  }
+
+  let health = state["health"].as_str().unwrap_or("unknown");
+  let label = match health {
+    "down" => "down, restart scheduled".to_string(),
+    "restarting" => "restarting…".to_string(),
+    other if state["supervised"].as_bool() == Some(false) => format!("{other} (not supervised)"),
+    other => other.to_string(),
+  };
+  let _ = item.set_text(format!("Gateway: {label}"));
+  if let Some(tray) = app.tray_by_id(TRAY_ID) {
+    let _ = tray.set_tooltip(Some(format!("OpenClaw — gateway {health}")));
+  }
+}
+
 fn show_main(app: &AppHandle) -> tauri::Result<()> {
   if let Some(w) = app.get_webview_window("main") {
     let _ = w.show();
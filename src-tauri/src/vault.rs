@@ -0,0 +1,148 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs,
+  path::{Path, PathBuf},
+  sync::Mutex,
+};
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::State;
+
+use crate::core::{write_atomic, DirLock, Storage};
+use crate::crypto::{self, DerivedKey, Sealed};
+
+// Passphrase-encrypted secret store for systems without a usable keychain (headless Linux, CI).
+// profiles/{id}/secrets.vault holds one sealed JSON map of key to value. Unlocking derives the
+// key once and keeps it in memory for this process; without an unlock, the passphrase is taken
+// from PASSPHRASE_ENV when set.
+
+pub const PASSPHRASE_ENV: &str = "OPENCLAW_DESKTOP_VAULT_PASSPHRASE";
+const VAULT_FILE: &str = "secrets.vault";
+// AEAD context for the vault contents.
+const CONTEXT: &str = "openclaw-desktop secret vault v1";
+
+// Unlocked vaults by profile data dir.
+static KEYS: Lazy<Mutex<HashMap<PathBuf, DerivedKey>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+  pub exists: bool,
+  pub unlocked: bool,
+}
+
+fn path(dir: &Path) -> PathBuf {
+  dir.join(VAULT_FILE)
+}
+
+fn read_sealed(dir: &Path) -> Result<Option<Sealed>> {
+  let p = path(dir);
+  if !p.exists() {
+    return Ok(None);
+  }
+  let raw = fs::read_to_string(&p).context("failed to read secrets.vault")?;
+  Ok(Some(serde_json::from_str(&raw).context("failed to parse secrets.vault")?))
+}
+
+fn cached(dir: &Path) -> Option<DerivedKey> {
+  KEYS.lock().ok()?.get(dir).cloned()
+}
+
+// Opens (or creates, when there is no vault yet) the vault in `dir` with `passphrase` and keeps
+// the key. A wrong passphrase is an error.
+fn unlock_dir(dir: &Path, passphrase: &str) -> Result<DerivedKey> {
+  let key = match read_sealed(dir)? {
+    Some(sealed) => {
+      let key = crypto::derive_key(passphrase, &sealed.kdf)?;
+      crypto::open_with(&key, CONTEXT, &sealed)?;
+      key
+    }
+    None => {
+      let key = crypto::derive_key(passphrase, &crypto::new_kdf()?)?;
+      write_map(dir, &key, &BTreeMap::new())?;
+      key
+    }
+  };
+  KEYS.lock().map_err(|_| anyhow!("vault lock poisoned"))?.insert(dir.to_path_buf(), key.clone());
+  Ok(key)
+}
+
+fn key_for(dir: &Path) -> Result<DerivedKey> {
+  if let Some(key) = cached(dir) {
+    return Ok(key);
+  }
+  match std::env::var(PASSPHRASE_ENV) {
+    Ok(p) if !p.is_empty() => unlock_dir(dir, &p),
+    _ => Err(anyhow!("the secret vault is locked; unlock it with its passphrase (or set {PASSPHRASE_ENV})")),
+  }
+}
+
+fn read_map(dir: &Path, key: &DerivedKey) -> Result<BTreeMap<String, String>> {
+  match read_sealed(dir)? {
+    Some(sealed) => Ok(serde_json::from_slice(&crypto::open_with(key, CONTEXT, &sealed)?).context("corrupt secret vault")?),
+    None => Ok(BTreeMap::new()),
+  }
+}
+
+fn write_map(dir: &Path, key: &DerivedKey, map: &BTreeMap<String, String>) -> Result<()> {
+  let sealed = crypto::seal_with(key, CONTEXT, &serde_json::to_vec(map)?)?;
+  write_atomic(&path(dir), serde_json::to_string_pretty(&sealed)?).context("failed to write secrets.vault")
+}
+
+pub fn unlock(st: &Storage, profile_id: &str, passphrase: &str) -> Result<VaultStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  {
+    let _lock = DirLock::acquire(&dir)?;
+    unlock_dir(&dir, passphrase)?;
+  }
+  status(st, profile_id)
+}
+
+// Forgets the key; the vault stays on disk.
+pub fn lock(st: &Storage, profile_id: &str) -> Result<VaultStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  KEYS.lock().map_err(|_| anyhow!("vault lock poisoned"))?.remove(&dir);
+  status(st, profile_id)
+}
+
+pub fn status(st: &Storage, profile_id: &str) -> Result<VaultStatus> {
+  let dir = st.profile_dir(profile_id)?;
+  Ok(VaultStatus { exists: path(&dir).exists(), unlocked: cached(&dir).is_some() })
+}
+
+// Callers of the functions below hold the profile dir lock for writes.
+
+pub(crate) fn get(dir: &Path, key: &str) -> Result<Option<String>> {
+  let k = key_for(dir)?;
+  Ok(read_map(dir, &k)?.remove(key))
+}
+
+pub(crate) fn set(dir: &Path, key: &str, value: &str) -> Result<()> {
+  let k = key_for(dir)?;
+  let mut map = read_map(dir, &k)?;
+  map.insert(key.to_string(), value.to_string());
+  write_map(dir, &k, &map)
+}
+
+pub(crate) fn delete(dir: &Path, key: &str) -> Result<()> {
+  if !path(dir).exists() {
+    return Ok(());
+  }
+  let k = key_for(dir)?;
+  let mut map = read_map(dir, &k)?;
+  if map.remove(key).is_some() {
+    write_map(dir, &k, &map)?;
+  }
+  Ok(())
+}
+
+#[tauri::command(async)]
+pub fn secrets_vault_unlock(storage: State<'_, Storage>, profile_id: String, passphrase: String) -> Result<VaultStatus, String> {
+  unlock(&storage, &profile_id, &passphrase).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn secrets_vault_lock(storage: State<'_, Storage>, profile_id: String) -> Result<VaultStatus, String> {
+  lock(&storage, &profile_id).map_err(|e| e.to_string())
+}
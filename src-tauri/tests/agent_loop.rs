@@ -0,0 +1,293 @@
+// Drives the chat tool loop end to end against a scripted mock Ollama server.
+
+mod support;
+
+use std::{path::Path, sync::Mutex};
+
+use tauri_appopenclaw_macapp_lib::{
+  agent::{self, AgentRun, AgentSink, LoopEnd},
+  chat_stream::ChatStreamEvent,
+  chats::{self, ChatMessage, ChatRole},
+  core::{NullSink, Storage},
+  memory,
+  settings::{self, AgentLimits, ProfileSettings},
+  tools::ToolCall,
+};
+
+use support::mock_ollama::MockOllama;
+
+#[derive(Default)]
+struct CollectSink(Mutex<Vec<ChatStreamEvent>>);
+
+impl AgentSink for CollectSink {
+  fn chat_event(&self, ev: ChatStreamEvent) {
+    self.0.lock().unwrap().push(ev);
+  }
+}
+
+impl CollectSink {
+  fn events(&self) -> Vec<ChatStreamEvent> {
+    self.0.lock().unwrap().clone()
+  }
+}
+
+fn settings_for(mock: &MockOllama) -> ProfileSettings {
+  ProfileSettings {
+    version: 1,
+    ollama_base_url: Some(mock.base_url()),
+    ollama_model: Some("ollama/test-model".to_string()),
+    ..Default::default()
+  }
+}
+
+// Creates a chat, sends `text` and returns the request the commands would hand the engine.
+fn send(dir: &Path, settings: ProfileSettings, text: &str, stream: bool) -> AgentRun {
+  let chat = chats::create_chat_in(dir, Some("Test".to_string())).unwrap();
+  let pending = chats::begin_send_in(dir, &chat.id, text).unwrap();
+  AgentRun {
+    profile_id: "p_test".to_string(),
+    chat_id: chat.id,
+    assistant_message_id: pending.assistant_message_id,
+    profile_dir: dir.to_path_buf(),
+    memory_path: dir.join("MEMORY.md"),
+    settings,
+    system_prompt: "You are a test agent.".to_string(),
+    max_steps: None,
+    stream,
+    secrets: Default::default(),
+  }
+}
+
+fn assistant(dir: &Path, req: &AgentRun) -> ChatMessage {
+  let t = chats::load_thread_in(dir, &req.chat_id).unwrap();
+  t.messages.into_iter().find(|m| m.id == req.assistant_message_id).expect("assistant message")
+}
+
+fn tool_messages(dir: &Path, req: &AgentRun) -> Vec<ChatMessage> {
+  let t = chats::load_thread_in(dir, &req.chat_id).unwrap();
+  t.messages.into_iter().filter(|m| matches!(m.role, ChatRole::Tool)).collect()
+}
+
+#[test]
+fn blocking_reply_is_persisted() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&["Hello there"]);
+  let req = send(dir.path(), settings_for(&mock), "hi", false);
+
+  let out = agent::run(&req, &CollectSink::default());
+  assert!(matches!(out.end, LoopEnd::Reply(ref r) if r == "Hello there"));
+  assert_eq!(out.trace.outcome.as_deref(), Some("final"));
+  assert_eq!(out.trace.steps.len(), 1);
+
+  // The thread on disk holds the user message followed by the reply.
+  let t = chats::load_thread_in(dir.path(), &req.chat_id).unwrap();
+  assert_eq!(t.messages.len(), 2);
+  assert!(matches!(t.messages[0].role, ChatRole::User));
+  assert_eq!(t.messages[0].text, "hi");
+  assert_eq!(assistant(dir.path(), &req).text, "Hello there");
+
+  let idx = chats::load_index_in(dir.path()).unwrap();
+  assert_eq!(idx.chats.len(), 1);
+  assert_eq!(idx.chats[0].id, req.chat_id);
+
+  let reqs = mock.requests();
+  assert_eq!(reqs.len(), 1);
+  assert_eq!(reqs[0]["model"], "test-model");
+  assert_eq!(reqs[0]["stream"], false);
+  assert_eq!(reqs[0]["messages"][0]["role"], "system");
+  assert_eq!(mock.last_prompt(0), "hi");
+}
+
+#[test]
+fn streaming_deltas_build_the_reply() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&["Streaming works fine."]);
+  let req = send(dir.path(), settings_for(&mock), "stream please", true);
+  let sink = CollectSink::default();
+
+  let out = agent::run(&req, &sink);
+  assert!(matches!(out.end, LoopEnd::Reply(ref r) if r == "Streaming works fine."));
+
+  let events = sink.events();
+  let joined: String = events
+    .iter()
+    .filter(|e| e.message_id == req.assistant_message_id && !e.done)
+    .map(|e| e.delta.as_str())
+    .collect();
+  assert_eq!(joined, "Streaming works fine.");
+  assert!(events.len() > 2, "expected several deltas, got {}", events.len());
+  assert!(events.last().unwrap().done);
+  assert_eq!(mock.requests()[0]["stream"], true);
+
+  assert_eq!(assistant(dir.path(), &req).text, "Streaming works fine.");
+}
+
+#[test]
+fn tool_calls_are_dispatched_and_fed_back() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[r#"{"tool":"remember","text":"prefers pnpm"}"#]);
+  let url = mock.page("/notes", "the page body");
+  mock.reply(&format!(r#"{{"tool":"web_get","url":"{url}"}}"#));
+  mock.reply(r#"{"tool":"final","text":"all done"}"#);
+
+  let req = send(dir.path(), settings_for(&mock), "save that and read the notes", false);
+  let out = agent::run(&req, &CollectSink::default());
+  assert!(matches!(out.end, LoopEnd::Reply(ref r) if r == "all done"));
+
+  // remember wrote to MEMORY.md; web_get fetched the mock page.
+  let memory = std::fs::read_to_string(dir.path().join("MEMORY.md")).unwrap();
+  assert!(memory.contains("prefers pnpm"));
+
+  let tools = tool_messages(dir.path(), &req);
+  assert_eq!(tools.len(), 2);
+  assert!(tools[0].text.starts_with("remember:"));
+  assert!(tools[1].text.contains("the page body"));
+
+  // Each tool result goes back to the model as the next user turn.
+  assert_eq!(mock.requests().len(), 3);
+  assert!(mock.last_prompt(1).starts_with("Tool result (remember):"));
+  assert!(mock.last_prompt(2).starts_with("Tool result (web_get):"));
+  assert!(mock.last_prompt(2).contains("the page body"));
+
+  let tools_in_trace: Vec<_> = out.trace.steps.iter().map(|s| s.tool.clone()).collect();
+  assert_eq!(tools_in_trace, vec![Some("remember".to_string()), Some("web_get".to_string()), None]);
+  assert_eq!(assistant(dir.path(), &req).text, "all done");
+}
+
+#[test]
+fn forget_removes_one_memory_by_id() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("MEMORY.md");
+  let keep = memory::remember(&path, "the user prefers pnpm").unwrap();
+  let gone = memory::remember(&path, "the build uses vite").unwrap();
+  let call = |json: &str| memory::run_tool(&path, &serde_json::from_str::<ToolCall>(json).unwrap());
+
+  // A loose match can't wipe the file.
+  assert!(call(r#"{"tool":"forget","query":"the"}"#).starts_with("[tool_error]"));
+  assert_eq!(memory::load_entries(&path).unwrap().len(), 2);
+  assert_eq!(call(&format!(r#"{{"tool":"forget","id":"{}"}}"#, gone.id)), format!("removed {}", gone.id));
+  let left = memory::load_entries(&path).unwrap();
+  assert_eq!((left.len(), left[0].id.as_str()), (1, keep.id.as_str()));
+}
+
+#[test]
+fn secret_values_are_redacted_from_tool_output() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[]);
+  let url = mock.page("/env", "OPENAI_API_KEY=sk-live-1234");
+  mock.reply(&format!(r#"{{"tool":"web_get","url":"{url}"}}"#));
+  mock.reply(r#"{"tool":"final","text":"done"}"#);
+
+  let mut req = send(dir.path(), settings_for(&mock), "read it", false);
+  req.secrets.values = vec![("openai".to_string(), "sk-live-1234".to_string())];
+  agent::run(&req, &CollectSink::default());
+
+  let tools = tool_messages(dir.path(), &req);
+  assert!(tools[0].text.contains("OPENAI_API_KEY=[redacted:openai]"), "{}", tools[0].text);
+  assert!(!mock.last_prompt(1).contains("sk-live-1234"));
+}
+
+#[test]
+fn exec_is_denied_without_developer_mode() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[r#"{"tool":"exec","cmd":"touch should-not-exist"}"#, "I could not run that."]);
+  let req = send(dir.path(), settings_for(&mock), "run touch", false);
+
+  let out = agent::run(&req, &CollectSink::default());
+  assert!(matches!(out.end, LoopEnd::Reply(ref r) if r == "I could not run that."));
+  assert!(!dir.path().join("should-not-exist").exists());
+
+  let tools = tool_messages(dir.path(), &req);
+  assert_eq!(tools.len(), 1);
+  assert!(tools[0].text.starts_with("exec denied"));
+  assert!(mock.last_prompt(1).starts_with("Tool denied"));
+  assert_eq!(out.trace.steps[0].note.as_deref(), Some("denied"));
+}
+
+#[test]
+fn web_get_rejects_non_http_urls() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[r#"{"tool":"web_get","url":"file:///etc/passwd"}"#, "ok"]);
+  let req = send(dir.path(), settings_for(&mock), "read it", false);
+
+  agent::run(&req, &CollectSink::default());
+  assert!(tool_messages(dir.path(), &req).is_empty());
+  assert!(mock.last_prompt(1).starts_with("Tool error: web_get only supports http(s)"));
+}
+
+#[test]
+fn loop_pauses_at_the_step_limit() {
+  let dir = tempfile::tempdir().unwrap();
+  let recall = r#"{"tool":"recall","query":"anything"}"#;
+  let mock = MockOllama::start(&[recall, recall, recall]);
+  let mut settings = settings_for(&mock);
+  settings.agent_limits = Some(AgentLimits { max_steps: 2, ..Default::default() });
+  let req = send(dir.path(), settings, "keep looking", true);
+  let sink = CollectSink::default();
+
+  let out = agent::run(&req, &sink);
+  let LoopEnd::Paused(pause) = out.end else {
+    panic!("expected the loop to pause");
+  };
+  assert_eq!(pause.limit, "steps");
+  assert_eq!(pause.steps_taken, 2);
+  assert_eq!(mock.requests().len(), 2);
+  assert_eq!(out.trace.outcome.as_deref(), Some("paused"));
+
+  // The pause is persisted on the message and announced to the sink.
+  let m = assistant(dir.path(), &req);
+  assert_eq!(m.pause.as_ref().map(|p| p.limit.as_str()), Some("steps"));
+  assert!(m.text.contains("[paused]"));
+  assert!(sink.events().iter().any(|e| e.pause.is_some()));
+}
+
+#[test]
+fn step_override_lets_a_paused_loop_continue() {
+  let dir = tempfile::tempdir().unwrap();
+  let recall = r#"{"tool":"recall","query":"anything"}"#;
+  let mock = MockOllama::start(&[recall, "finished"]);
+  let mut settings = settings_for(&mock);
+  settings.agent_limits = Some(AgentLimits { max_steps: 1, ..Default::default() });
+  let mut req = send(dir.path(), settings, "keep looking", false);
+  req.max_steps = Some(3);
+
+  let out = agent::run(&req, &CollectSink::default());
+  assert!(matches!(out.end, LoopEnd::Reply(ref r) if r == "finished"));
+}
+
+#[test]
+fn model_errors_are_recorded_on_the_message() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[]);
+  let req = send(dir.path(), settings_for(&mock), "hello?", false);
+  let sink = CollectSink::default();
+
+  let out = agent::run(&req, &sink);
+  assert!(matches!(out.end, LoopEnd::Failed(ref e) if e.contains("500")));
+  assert_eq!(out.trace.outcome.as_deref(), Some("error"));
+
+  // The user message survives and the placeholder carries the error.
+  let t = chats::load_thread_in(dir.path(), &req.chat_id).unwrap();
+  assert_eq!(t.messages[0].text, "hello?");
+  assert!(assistant(dir.path(), &req).text.starts_with("[error]"));
+  assert!(sink.events().last().unwrap().error.is_some());
+}
+
+#[test]
+fn send_runs_headless_against_a_storage_root() {
+  let root = tempfile::tempdir().unwrap();
+  let st = Storage::new(root.path().join("data"), root.path().join("home"));
+  let mock = MockOllama::start(&["stored reply"]);
+  settings::save_settings(&st, "p_test", &settings_for(&mock)).unwrap();
+
+  let chat = chats::create(&st, "p_test", None).unwrap();
+  let (thread, end) = chats::send(&st, &NullSink, "p_test", &chat.id, "hello", false).unwrap();
+  assert!(matches!(end, LoopEnd::Reply(_)));
+  assert_eq!(thread.messages.last().unwrap().text, "stored reply");
+
+  // Everything lands under profiles/{id} in the storage root, trace included.
+  let dir = root.path().join("data").join("profiles").join("p_test");
+  assert!(dir.join(format!("chat_{}.json", chat.id)).exists());
+  assert!(dir.join("traces").join(format!("chat_{}.json", chat.id)).exists());
+  assert_eq!(chats::load_index(&st, "p_test").unwrap().chats.len(), 1);
+}
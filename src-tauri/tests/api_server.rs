@@ -0,0 +1,137 @@
+// Talks to the local OpenAI-compatible API over HTTP, backed by a mock Ollama.
+
+mod support;
+
+use serde_json::{json, Value};
+
+use tauri_appopenclaw_macapp_lib::{
+  api_server::{self, ApiServer},
+  chats::{self, ChatRole},
+  core::Storage,
+  settings::{self, ApiServerConfig, ProfileSettings},
+};
+
+use support::mock_ollama::MockOllama;
+
+const TOKEN: &str = "test-token";
+
+fn serve(dir: &std::path::Path, mock: &MockOllama, chat: Option<&str>) -> (Storage, ApiServer) {
+  let st = Storage::new(dir, dir.join("home"));
+  let s = ProfileSettings {
+    version: 1,
+    ollama_base_url: Some(mock.base_url()),
+    ollama_model: Some("ollama/test-model".to_string()),
+    ..Default::default()
+  };
+  settings::save_settings(&st, "p_test", &s).unwrap();
+  let config = ApiServerConfig { port: 0, chat: chat.map(String::from) };
+  let server = api_server::start(st.clone(), "p_test", &config, TOKEN).unwrap();
+  (st, server)
+}
+
+fn url(server: &ApiServer, path: &str) -> String {
+  format!("http://{}{path}", server.addr())
+}
+
+fn client() -> reqwest::blocking::Client {
+  reqwest::blocking::Client::new()
+}
+
+#[test]
+fn models_require_the_bearer_token() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[]);
+  let (_st, server) = serve(dir.path(), &mock, None);
+
+  let res = client().get(url(&server, "/v1/models")).send().unwrap();
+  assert_eq!(res.status(), 401);
+  let res = client().get(url(&server, "/v1/models")).bearer_auth("wrong").send().unwrap();
+  assert_eq!(res.status(), 401);
+
+  let res = client().get(url(&server, "/v1/models")).bearer_auth(TOKEN).send().unwrap();
+  assert_eq!(res.status(), 200);
+  let body: Value = res.json().unwrap();
+  assert_eq!(body["data"][0]["id"], "ollama/test-model");
+}
+
+#[test]
+fn completion_without_a_chat_leaves_no_history() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&["Four."]);
+  let (st, server) = serve(dir.path(), &mock, None);
+
+  let res = client()
+    .post(url(&server, "/v1/chat/completions"))
+    .bearer_auth(TOKEN)
+    .json(&json!({
+      "model": "test-model",
+      "messages": [
+        { "role": "system", "content": "Answer briefly." },
+        { "role": "user", "content": "2+2?" },
+      ],
+    }))
+    .send()
+    .unwrap();
+  assert_eq!(res.status(), 200);
+  let body: Value = res.json().unwrap();
+  assert_eq!(body["object"], "chat.completion");
+  assert_eq!(body["choices"][0]["message"]["content"], "Four.");
+  assert_eq!(body["choices"][0]["finish_reason"], "stop");
+
+  // The client's system message is appended to the profile prompt.
+  let sent = &mock.requests()[0]["messages"];
+  assert!(sent[0]["content"].as_str().unwrap().ends_with("Answer briefly."));
+  assert_eq!(sent[1]["content"], "2+2?");
+
+  assert!(chats::load_index(&st, "p_test").unwrap().chats.is_empty());
+  let leftovers = std::fs::read_dir(st.profile_dir("p_test").unwrap())
+    .unwrap()
+    .filter_map(|e| e.ok())
+    .filter(|e| e.file_name().to_string_lossy().starts_with("chat_"))
+    .count();
+  assert_eq!(leftovers, 0);
+
+  let res = client()
+    .post(url(&server, "/v1/chat/completions"))
+    .bearer_auth(TOKEN)
+    .json(&json!({ "model": "gpt-4o", "messages": [{ "role": "user", "content": "hi" }] }))
+    .send()
+    .unwrap();
+  assert_eq!(res.status(), 404);
+}
+
+#[test]
+fn streaming_completion_writes_into_the_named_chat() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[r#"{"tool":"recall","query":"x"}"#, "Streamed answer"]);
+  let (st, server) = serve(dir.path(), &mock, Some("Editor"));
+
+  let res = client()
+    .post(url(&server, "/v1/chat/completions"))
+    .bearer_auth(TOKEN)
+    .json(&json!({ "stream": true, "messages": [{ "role": "user", "content": [{ "type": "text", "text": "hello" }] }] }))
+    .send()
+    .unwrap();
+  assert_eq!(res.status(), 200);
+  assert_eq!(res.headers()["content-type"], "text/event-stream");
+
+  let events: Vec<String> = res
+    .text()
+    .unwrap()
+    .lines()
+    .filter_map(|l| l.strip_prefix("data: ").map(String::from))
+    .collect();
+  assert_eq!(events.last().map(String::as_str), Some("[DONE]"));
+  let chunks: Vec<Value> = events[..events.len() - 1].iter().map(|e| serde_json::from_str(e).unwrap()).collect();
+  let text: String = chunks.iter().filter_map(|c| c["choices"][0]["delta"]["content"].as_str()).collect();
+  assert_eq!(text, "Streamed answer");
+  assert_eq!(chunks.last().unwrap()["choices"][0]["finish_reason"], "stop");
+
+  let idx = chats::load_index(&st, "p_test").unwrap();
+  assert_eq!(idx.chats.len(), 1);
+  assert_eq!(idx.chats[0].title, "Editor");
+  let thread = chats::load_thread(&st, "p_test", &idx.chats[0].id).unwrap();
+  assert_eq!(thread.messages[0].text, "hello");
+  let reply = thread.messages.iter().rev().find(|m| matches!(m.role, ChatRole::Assistant)).unwrap();
+  assert_eq!(reply.text, "Streamed answer");
+}
@@ -0,0 +1,128 @@
+// Encryption at rest for chat history: enabling, locking, key rotation and cloning.
+
+mod support;
+
+use std::fs;
+
+use tauri_appopenclaw_macapp_lib::{
+  chat_crypto::{self, KeySource},
+  chats,
+  core::Storage,
+  profiles,
+};
+
+fn on_disk(dir: &std::path::Path) -> String {
+  let mut all = String::new();
+  for e in fs::read_dir(dir).unwrap().flatten() {
+    let name = e.file_name().to_string_lossy().to_string();
+    if name == "chats.json" || name.starts_with("chat_") {
+      all.push_str(&fs::read_to_string(e.path()).unwrap());
+    }
+  }
+  all
+}
+
+#[test]
+fn encrypts_locks_and_rotates() {
+  support::keychain::install();
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let chat = chats::create(&st, "p1", Some("Private notes".into())).unwrap();
+  let dir = st.profile_dir("p1").unwrap();
+  chats::begin_send_in(&dir, &chat.id, "the launch code is 0000").unwrap();
+  assert!(chat_crypto::enable(&st, "p1", KeySource::Passphrase, None).is_err());
+
+  let status = chat_crypto::enable(&st, "p1", KeySource::Passphrase, Some("pw-one")).unwrap();
+  assert!(status.enabled && status.unlocked);
+  let raw = on_disk(&dir);
+  assert!(!raw.contains("launch code") && !raw.contains("Private notes"), "{raw}");
+  // New writes are sealed too.
+  chats::create(&st, "p1", Some("Second".into())).unwrap();
+  assert!(!on_disk(&dir).contains("Second"));
+  assert_eq!(chats::load_thread(&st, "p1", &chat.id).unwrap().messages[0].text, "the launch code is 0000");
+
+  chat_crypto::lock(&st, "p1").unwrap();
+  let err = chats::load_index(&st, "p1").unwrap_err().to_string();
+  assert!(err.contains("locked"), "{err}");
+  assert!(chats::create(&st, "p1", None).is_err());
+  assert!(chat_crypto::unlock(&st, "p1", Some("wrong")).is_err());
+  chat_crypto::unlock(&st, "p1", Some("pw-one")).unwrap();
+  assert_eq!(chats::load_index(&st, "p1").unwrap().chats.len(), 2);
+
+  chat_crypto::rotate(&st, "p1", KeySource::Passphrase, Some("pw-two")).unwrap();
+  chat_crypto::lock(&st, "p1").unwrap();
+  assert!(chat_crypto::unlock(&st, "p1", Some("pw-one")).is_err());
+  chat_crypto::unlock(&st, "p1", Some("pw-two")).unwrap();
+  assert_eq!(chats::load_thread(&st, "p1", &chat.id).unwrap().messages[0].text, "the launch code is 0000");
+
+  let status = chat_crypto::disable(&st, "p1").unwrap();
+  assert!(!status.enabled);
+  assert!(on_disk(&dir).contains("launch code"));
+  assert!(!dir.join("chats_key.json").exists());
+}
+
+#[test]
+fn keychain_keys_outlive_the_cache_and_clone() {
+  support::keychain::install();
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let id = profiles::ensure_default_profile(&st).unwrap().profiles[0].id.clone();
+  let chat = chats::create(&st, &id, Some("Plans".into())).unwrap();
+  chats::begin_send_in(&st.profile_dir(&id).unwrap(), &chat.id, "secret plans").unwrap();
+  chat_crypto::enable(&st, &id, KeySource::Keychain, None).unwrap();
+
+  // With the key dropped from memory, it comes back from the keychain.
+  chat_crypto::lock(&st, &id).unwrap();
+  assert!(chats::load_thread(&st, &id, &chat.id).is_err());
+  assert!(chat_crypto::unlock(&st, &id, None).unwrap().unlocked);
+  assert_eq!(chats::load_thread(&st, &id, &chat.id).unwrap().messages[0].text, "secret plans");
+
+  let copy = profiles::clone(&st, &id, "Copy", true).unwrap().active_profile_id.unwrap();
+  assert!(chat_crypto::status(&st, &copy).unwrap().enabled);
+  assert!(!on_disk(&st.profile_dir(&copy).unwrap()).contains("secret plans"));
+  let idx = chats::load_index(&st, &copy).unwrap();
+  assert_eq!(chats::load_thread(&st, &copy, &idx.chats[0].id).unwrap().messages[0].text, "secret plans");
+
+  // Without chats the clone starts out unencrypted.
+  let bare = profiles::clone(&st, &id, "Bare", false).unwrap().active_profile_id.unwrap();
+  assert!(!chat_crypto::status(&st, &bare).unwrap().enabled);
+}
+
+#[test]
+fn interrupted_rotation_keeps_both_keys() {
+  support::keychain::install();
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let dir = st.profile_dir("p1").unwrap();
+  let mut ids = vec![];
+  for text in ["first", "second"] {
+    let chat = chats::create(&st, "p1", None).unwrap();
+    chats::begin_send_in(&dir, &chat.id, text).unwrap();
+    ids.push(chat.id);
+  }
+  chat_crypto::enable(&st, "p1", KeySource::Passphrase, Some("old-pw")).unwrap();
+  // A thread whose temp file name is too long to create: resealing fails after the threads
+  // sorted before it were written with the new key.
+  let blocker = format!("chat_{}.json", "z".repeat(240));
+  fs::write(dir.join(&blocker), "{}").unwrap();
+
+  assert!(chat_crypto::rotate(&st, "p1", KeySource::Passphrase, Some("new-pw")).is_err());
+  assert!(chat_crypto::status(&st, "p1").unwrap().rotating);
+  // Each key opens the files sealed with it, whichever passphrase is given first.
+  chat_crypto::lock(&st, "p1").unwrap();
+  chat_crypto::unlock(&st, "p1", Some("new-pw")).unwrap();
+  chat_crypto::unlock(&st, "p1", Some("old-pw")).unwrap();
+  for (id, text) in ids.iter().zip(["first", "second"]) {
+    assert_eq!(chats::load_thread(&st, "p1", id).unwrap().messages[0].text, text);
+  }
+
+  fs::remove_file(dir.join(&blocker)).unwrap();
+  let status = chat_crypto::rotate(&st, "p1", KeySource::Passphrase, Some("newest-pw")).unwrap();
+  assert!(!status.rotating && status.unlocked);
+  chat_crypto::lock(&st, "p1").unwrap();
+  assert!(chat_crypto::unlock(&st, "p1", Some("new-pw")).is_err());
+  chat_crypto::unlock(&st, "p1", Some("newest-pw")).unwrap();
+  for (id, text) in ids.iter().zip(["first", "second"]) {
+    assert_eq!(chats::load_thread(&st, "p1", id).unwrap().messages[0].text, text);
+  }
+}
@@ -0,0 +1,111 @@
+// Chats answered by `openclaw agent` instead of Ollama, against a fake openclaw script.
+#![cfg(unix)]
+
+use std::{fs, os::unix::fs::PermissionsExt, path::PathBuf, sync::Mutex};
+
+use serde_json::Value;
+
+use tauri_appopenclaw_macapp_lib::{
+  agent::LoopEnd,
+  chats::{self, ChatRole},
+  core::{EventSink, Storage},
+  settings::{self, ChatBackend},
+};
+
+#[derive(Default)]
+struct Events(Mutex<Vec<(String, Value)>>);
+
+impl EventSink for Events {
+  fn emit_json(&self, event: &str, payload: Value) {
+    self.0.lock().unwrap().push((event.to_string(), payload));
+  }
+}
+
+impl Events {
+  fn named(&self, event: &str) -> Vec<Value> {
+    self.0.lock().unwrap().iter().filter(|(e, _)| e == event).map(|(_, p)| p.clone()).collect()
+  }
+}
+
+// Logs its arguments and answers like a 2026.2 openclaw.
+fn fake_openclaw(dir: &std::path::Path, reply: &str) -> (PathBuf, PathBuf) {
+  let log = dir.join("args.log");
+  let path = dir.join("openclaw");
+  let body = format!(
+    r#"#!/bin/sh
+if [ "$1" = --version ]; then echo 2026.2.14; exit 0; fi
+echo "$*" >> '{log}'
+case "$*" in
+  *"agents list"*) echo '{{"agents":[]}}' ;;
+  *"agents add"*) ;;
+  *" agent "*) echo 'thinking...' >&2; echo '{reply}' ;;
+esac
+"#,
+    log = log.display()
+  );
+  fs::write(&path, body).unwrap();
+  fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+  (path, log)
+}
+
+fn storage_with(home: &std::path::Path, bin: &std::path::Path) -> Storage {
+  let st = Storage::new(home.join("data"), home);
+  let mut s = settings::load_settings(&st, "p1").unwrap();
+  s.openclaw_path = Some(bin.to_string_lossy().to_string());
+  s.ollama_model = Some("ollama/Qwen3:8b".to_string());
+  // Nothing listens here; an Ollama-backed send would fail.
+  s.ollama_base_url = Some("http://127.0.0.1:9".to_string());
+  settings::save_settings(&st, "p1", &s).unwrap();
+  st
+}
+
+#[test]
+fn routes_chats_through_the_agent_session() {
+  let home = tempfile::tempdir().unwrap();
+  let (bin, log) = fake_openclaw(home.path(), r#"{"result":{"payloads":[{"text":"hi from agent"}]}}"#);
+  let st = storage_with(home.path(), &bin);
+  let chat = chats::create(&st, "p1", Some("Agent".into())).unwrap();
+  chats::set_backend(&st, "p1", &chat.id, Some(ChatBackend::Openclaw)).unwrap();
+
+  let events = Events::default();
+  let (thread, end) = chats::send(&st, &events, "p1", &chat.id, "hello", true).unwrap();
+  assert!(matches!(end, LoopEnd::Reply(ref t) if t == "hi from agent"));
+  let last = thread.messages.last().unwrap();
+  assert!(matches!(last.role, ChatRole::Assistant));
+  assert_eq!(last.text, "hi from agent");
+
+  let calls = fs::read_to_string(&log).unwrap();
+  let agent_call = calls.lines().find(|l| l.contains(" agent ")).unwrap();
+  assert!(agent_call.contains(&format!("--session-id {}", chat.session_id)), "{agent_call}");
+  assert!(agent_call.contains("--message hello"), "{agent_call}");
+  assert!(agent_call.contains("--agent desktop-ocd-p1-ollama-qwen3-8b"), "{agent_call}");
+  assert!(calls.lines().any(|l| l.contains("agents add --non-interactive") && l.ends_with("--model ollama/Qwen3:8b desktop-ocd-p1-ollama-qwen3-8b")), "{calls}");
+
+  let progress = events.named("chat_progress");
+  assert_eq!(progress.len(), 1);
+  assert_eq!(progress[0]["line"], "thinking...");
+  assert_eq!(progress[0]["chat_id"], chat.id.as_str());
+  let done = events.named("chat_stream");
+  assert_eq!(done.last().unwrap()["delta"], "hi from agent");
+  assert_eq!(done.last().unwrap()["done"], true);
+}
+
+#[test]
+fn profile_default_applies_and_errors_land_in_the_thread() {
+  let home = tempfile::tempdir().unwrap();
+  let (bin, _log) = fake_openclaw(home.path(), "not json");
+  let st = storage_with(home.path(), &bin);
+  let mut s = settings::load_settings(&st, "p1").unwrap();
+  s.chat_backend = Some(ChatBackend::Openclaw);
+  settings::save_settings(&st, "p1", &s).unwrap();
+  let chat = chats::create(&st, "p1", None).unwrap();
+
+  let events = Events::default();
+  let (thread, end) = chats::send(&st, &events, "p1", &chat.id, "hello", false).unwrap();
+  match end {
+    LoopEnd::Failed(e) => assert!(e.contains("no JSON"), "{e}"),
+    _ => panic!("expected a failed run"),
+  }
+  assert!(thread.messages.last().unwrap().text.starts_with("[error]"));
+  assert!(events.named("chat_stream").last().unwrap()["error"].is_string());
+}
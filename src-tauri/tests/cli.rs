@@ -0,0 +1,103 @@
+// Runs the `openclaw-desktop` binary against a temporary data dir and a mock Ollama.
+
+mod support;
+
+use std::process::{Command, Output};
+
+use tauri_appopenclaw_macapp_lib::{
+  chats,
+  core::{try_lock_file, Storage},
+  settings,
+};
+
+use support::mock_ollama::MockOllama;
+
+fn cli(data_dir: &std::path::Path, args: &[&str]) -> Output {
+  Command::new(env!("CARGO_BIN_EXE_openclaw-desktop"))
+    .arg("--data-dir")
+    .arg(data_dir)
+    .args(args)
+    .output()
+    .expect("run openclaw-desktop")
+}
+
+fn stdout(o: &Output) -> String {
+  String::from_utf8_lossy(&o.stdout).to_string()
+}
+
+// Creates the default profile through the CLI and points it at the mock.
+fn setup(data_dir: &std::path::Path, mock: &MockOllama) -> (Storage, String) {
+  let out = cli(data_dir, &["profiles"]);
+  assert!(out.status.success());
+  let pid = stdout(&out).split_whitespace().nth(1).expect("profile id").to_string();
+
+  let st = Storage::new(data_dir, data_dir.join("home"));
+  settings::update_settings(&st, &pid, |s| s.ollama_base_url = Some(mock.base_url())).unwrap();
+  (st, pid)
+}
+
+#[test]
+fn chats_send_and_export_share_the_gui_files() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[r#"{"tool":"recall","query":"x"}"#, "Streamed answer"]);
+  let (st, pid) = setup(dir.path(), &mock);
+
+  let out = cli(dir.path(), &["chats", "create", "From", "cron"]);
+  assert!(out.status.success());
+  let chat_id = stdout(&out).trim().to_string();
+  assert_eq!(chats::load_index(&st, &pid).unwrap().chats[0].title, "From cron");
+
+  // Tool-call steps stay off stdout; the answer is streamed.
+  let out = cli(dir.path(), &["send", &chat_id, "hello", "there"]);
+  assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+  assert_eq!(stdout(&out), "Streamed answer\n");
+  assert!(String::from_utf8_lossy(&out.stderr).contains("[tool] recall"));
+
+  let thread = chats::load_thread(&st, &pid, &chat_id).unwrap();
+  assert_eq!(thread.messages[0].text, "hello there");
+  let reply = thread.messages.iter().rev().find(|m| matches!(m.role, chats::ChatRole::Assistant)).unwrap();
+  assert_eq!(reply.text, "Streamed answer");
+
+  let md = stdout(&cli(dir.path(), &["export", &chat_id]));
+  assert!(md.starts_with("# From cron\n"));
+  assert!(md.contains("## User") && md.contains("hello there"));
+  assert!(md.contains("Streamed answer"));
+}
+
+#[test]
+fn send_refuses_a_chat_busy_in_another_process() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&["unused"]);
+  let (st, pid) = setup(dir.path(), &mock);
+  let chat = chats::create(&st, &pid, None).unwrap();
+
+  // What a GUI send holds while it runs.
+  let lock = st.profile_dir(&pid).unwrap().join("locks").join(format!("chat_{}.lock", chat.id));
+  let _held = try_lock_file(&lock).unwrap().expect("lock");
+
+  let out = cli(dir.path(), &["send", &chat.id, "hi"]);
+  assert!(!out.status.success());
+  assert!(String::from_utf8_lossy(&out.stderr).contains("busy"));
+  assert!(mock.requests().is_empty());
+  assert!(chats::load_thread(&st, &pid, &chat.id).unwrap().messages.is_empty());
+}
+
+#[test]
+fn settings_get_and_set() {
+  let dir = tempfile::tempdir().unwrap();
+  let mock = MockOllama::start(&[]);
+  let (st, pid) = setup(dir.path(), &mock);
+
+  assert!(cli(dir.path(), &["settings", "set", "auto_do_mode", "true"]).status.success());
+  assert!(cli(dir.path(), &["settings", "set", "ollama_model", "ollama/llama3:8b"]).status.success());
+  let s = settings::load_settings(&st, &pid).unwrap();
+  assert_eq!(s.auto_do_mode, Some(true));
+  assert_eq!(s.ollama_model.as_deref(), Some("ollama/llama3:8b"));
+  assert_eq!(stdout(&cli(dir.path(), &["settings", "get", "ollama_model"])), "ollama/llama3:8b\n");
+
+  // Bad values and unknown keys are rejected without touching the file.
+  let bad = cli(dir.path(), &["settings", "set", "agent_limits", r#"{"max_steps":0,"max_wall_secs":1,"max_tool_output_bytes":1}"#]);
+  assert!(!bad.status.success());
+  assert!(!cli(dir.path(), &["settings", "set", "no_such_key", "1"]).status.success());
+  assert!(settings::load_settings(&st, &pid).unwrap().agent_limits.is_none());
+}
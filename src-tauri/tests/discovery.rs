@@ -0,0 +1,111 @@
+// node/openclaw discovery over fake version-manager layouts.
+#![cfg(unix)]
+
+use std::{
+  fs,
+  os::unix::fs::PermissionsExt,
+  path::{Path, PathBuf},
+};
+
+use tauri_appopenclaw_macapp_lib::{
+  core::Storage,
+  discovery::{self, SearchEnv, Tool},
+  settings,
+};
+
+fn fake_bin(dir: &Path, name: &str, version_output: &str) -> PathBuf {
+  fs::create_dir_all(dir).unwrap();
+  let path = dir.join(name);
+  fs::write(&path, format!("#!/bin/sh\necho '{version_output}'\n")).unwrap();
+  fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+  path
+}
+
+fn empty_env(home: &Path) -> SearchEnv {
+  SearchEnv { nvm_dir: Some(home.join(".nvm")), ..SearchEnv::default() }
+}
+
+#[test]
+fn finds_newest_usable_node_across_managers() {
+  let home = tempfile::tempdir().unwrap();
+  let nvm = home.path().join(".nvm");
+  fake_bin(&nvm.join("versions/node/v18.20.0/bin"), "node", "v18.20.0");
+  let v20 = fake_bin(&nvm.join("versions/node/v20.11.1/bin"), "node", "v20.11.1");
+  fs::create_dir_all(nvm.join("alias")).unwrap();
+  fs::write(nvm.join("alias/default"), "20\n").unwrap();
+  let asdf = fake_bin(&home.path().join(".asdf/installs/nodejs/22.3.0/bin"), "node", "v22.3.0");
+
+  let mut env = empty_env(home.path());
+  env.asdf_dir = Some(home.path().join(".asdf"));
+  // The same directory on PATH is reported once, as a PATH hit.
+  env.path = vec![v20.parent().unwrap().to_path_buf()];
+
+  let found = discovery::scan(&env, Tool::Node, None);
+  let summary: Vec<(&str, Option<&str>, bool)> = found.iter().map(|c| (c.source.as_str(), c.version.as_deref(), c.usable)).collect();
+  assert_eq!(summary, [("path", Some("20.11.1"), false), ("nvm", Some("18.20.0"), false), ("asdf", Some("22.3.0"), true)]);
+  assert!(found[0].error.as_deref().unwrap().contains("too old"));
+
+  assert_eq!(discovery::resolve_node(&env).unwrap(), asdf);
+}
+
+#[test]
+fn finds_openclaw_and_reports_versions() {
+  let home = tempfile::tempdir().unwrap();
+  let bin = home.path().join(".local/share/fnm/node-versions/v22.3.0/installation/bin");
+  fake_bin(&bin, "node", "v22.3.0");
+  let oc = fake_bin(&bin, "openclaw", "openclaw 2026.2.14");
+
+  let mut env = empty_env(home.path());
+  env.fnm_dirs = vec![home.path().join(".local/share/fnm")];
+  let found = discovery::find(&env, Tool::Openclaw, None).unwrap();
+  assert_eq!(found.path, oc.to_string_lossy());
+  assert_eq!(found.source, "fnm");
+  assert_eq!(found.version.as_deref(), Some("2026.2.14"));
+
+  let st = Storage::new(home.path().join("data"), home.path());
+  let report = discovery::report(&st, &env, None);
+  assert_eq!(report.selected_openclaw.as_deref(), Some(&*oc.to_string_lossy()));
+  assert!(report.selected_node.is_some());
+  assert!(report.error.is_none());
+}
+
+#[test]
+fn explains_when_nothing_is_found() {
+  let home = tempfile::tempdir().unwrap();
+  let env = empty_env(home.path());
+  assert!(discovery::find(&env, Tool::Openclaw, None).is_none());
+  let err = discovery::resolve_node(&env).unwrap_err().to_string();
+  assert!(err.contains("node 22+ not found"), "{err}");
+  assert!(err.contains("nvm") && err.contains("Homebrew"), "{err}");
+
+  let st = Storage::new(home.path().join("data"), home.path());
+  let report = discovery::report(&st, &env, None);
+  assert!(report.error.unwrap().contains("npm install -g openclaw"));
+}
+
+#[test]
+fn pinned_paths_win_and_must_exist() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let oc = fake_bin(&home.path().join("custom"), "openclaw", "2026.1.0");
+
+  let mut s = settings::load_settings(&st, "p1").unwrap();
+  s.openclaw_path = Some(oc.to_string_lossy().to_string());
+  settings::save_settings(&st, "p1", &s).unwrap();
+  assert_eq!(settings::resolve_openclaw_bin(&st, "p1").unwrap(), oc);
+
+  s.openclaw_path = Some(home.path().join("gone/openclaw").to_string_lossy().to_string());
+  settings::save_settings(&st, "p1", &s).unwrap();
+  let err = settings::resolve_openclaw_bin(&st, "p1").unwrap_err().to_string();
+  assert!(err.contains("pinned openclaw path"), "{err}");
+}
+
+#[test]
+fn reads_npm_prefix_from_npmrc() {
+  let home = tempfile::tempdir().unwrap();
+  fs::write(home.path().join(".npmrc"), "registry=https://registry.npmjs.org/\nprefix = ~/tools/npm\n").unwrap();
+  let env = SearchEnv::from_process(home.path());
+  assert!(env.npm_prefixes.contains(&home.path().join("tools/npm")));
+  assert_eq!(discovery::parse_version("v22.3.0\n"), Some("22.3.0".into()));
+  assert_eq!(discovery::parse_version("openclaw 2026.2.14 (abc123)"), Some("2026.2.14".into()));
+}
@@ -0,0 +1,166 @@
+// Gateway log tail reads, filters, follow mode (including rotation and truncation) and snapshots.
+
+use std::{
+  fs::{self, OpenOptions},
+  io::Write,
+  path::Path,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use tauri_appopenclaw_macapp_lib::{
+  core::{EventSink, Storage},
+  gateway_logs::{self, LogFilter, LogLevel},
+};
+
+#[derive(Default)]
+struct Events(Mutex<Vec<Value>>);
+
+impl EventSink for Events {
+  fn emit_json(&self, event: &str, payload: Value) {
+    assert_eq!(event, gateway_logs::LOG_EVENT);
+    self.0.lock().unwrap().push(payload);
+  }
+}
+
+impl Events {
+  fn texts(&self) -> Vec<String> {
+    let events = self.0.lock().unwrap();
+    events.iter().flat_map(|e| e["lines"].as_array().unwrap().iter().map(|l| l["text"].as_str().unwrap().to_string())).collect()
+  }
+
+  fn resets(&self) -> Vec<String> {
+    self.0.lock().unwrap().iter().filter_map(|e| e["reset"].as_str().map(String::from)).collect()
+  }
+
+  fn wait_for(&self, pred: impl Fn(&Self) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !pred(self) {
+      assert!(Instant::now() < deadline, "timed out; got {:?}", self.texts());
+      std::thread::sleep(Duration::from_millis(10));
+    }
+  }
+}
+
+fn append(path: &Path, text: &str) {
+  let mut f = OpenOptions::new().create(true).append(true).open(path).unwrap();
+  f.write_all(text.as_bytes()).unwrap();
+}
+
+#[test]
+fn tail_reads_last_lines_across_chunks() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("gateway.log");
+  let body: String = (0..20_000).map(|i| format!("INFO line {i} {}\n", "x".repeat(i % 50))).collect();
+  fs::write(&path, body).unwrap();
+
+  let lines = gateway_logs::tail_lines(&path, 3, |_| true).unwrap();
+  assert_eq!(lines.len(), 3);
+  assert!(lines[0].starts_with("INFO line 19997 "));
+  assert!(lines[2].starts_with("INFO line 19999 "));
+
+  // Matches far from the end still come back, oldest first.
+  let lines = gateway_logs::tail_lines(&path, 2, |l| l.contains("line 12 ") || l.contains("line 5 ")).unwrap();
+  assert!(lines[0].starts_with("INFO line 5 "));
+  assert!(lines[1].starts_with("INFO line 12 "));
+
+  assert_eq!(gateway_logs::tail_lines(&path, 0, |_| true).unwrap().len(), 20_000);
+  assert!(gateway_logs::tail_lines(&dir.path().join("missing.log"), 10, |_| true).unwrap().is_empty());
+}
+
+#[test]
+fn filters_by_level_and_pattern() {
+  let home = tempfile::tempdir().unwrap();
+  let root = tempfile::tempdir().unwrap();
+  let st = Storage::new(root.path(), home.path());
+  let logs = st.openclaw_logs_dir();
+  fs::create_dir_all(&logs).unwrap();
+  fs::write(
+    logs.join("gateway.log"),
+    "2026-01-01T00:00:00Z [debug] polling\n2026-01-01T00:00:01Z [info] listening on 127.0.0.1:18789\n{\"level\":\"error\",\"msg\":\"telegram: 401\"}\nWARN slow response from telegram\n",
+  )
+  .unwrap();
+  fs::write(logs.join("gateway.err.log"), "unhandled rejection in plugin\n").unwrap();
+
+  let warn = LogFilter { level: Some(LogLevel::Warn), pattern: None };
+  let got = gateway_logs::read_logs(&st, None, None, &warn).unwrap();
+  assert_eq!(got.out, "{\"level\":\"error\",\"msg\":\"telegram: 401\"}\nWARN slow response from telegram");
+  // Unlabelled stderr lines count as warnings.
+  assert_eq!(got.err, "unhandled rejection in plugin");
+
+  let re = LogFilter { level: None, pattern: Some("telegram".into()) };
+  assert_eq!(gateway_logs::read_logs(&st, None, Some(1), &re).unwrap().out, "WARN slow response from telegram");
+
+  let bad = LogFilter { level: None, pattern: Some("(".into()) };
+  assert!(gateway_logs::read_logs(&st, None, None, &bad).unwrap_err().to_string().contains("invalid pattern"));
+}
+
+#[test]
+fn follow_reports_new_lines_through_rotation_and_truncation() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("gateway.log");
+  fs::write(&path, "INFO old line\n").unwrap();
+
+  let events = Arc::new(Events::default());
+  let filter = LogFilter { level: None, pattern: Some("^(INFO|ERROR)".into()) };
+  let follower = gateway_logs::follow(dir.path(), "f1", Some("p1"), &filter, events.clone(), Duration::from_millis(20)).unwrap();
+
+  append(&path, "INFO first\nskipped\nINFO sec");
+  events.wait_for(|e| e.texts() == ["INFO first"]);
+  // The partial line is reported once it's complete.
+  append(&path, "ond\n");
+  events.wait_for(|e| e.texts().len() == 2);
+  assert_eq!(events.texts()[1], "INFO second");
+
+  // Rotation: the old file is renamed and a new one takes its place.
+  append(&path, "INFO last before rotate\n");
+  fs::rename(&path, dir.path().join("gateway.log.1")).unwrap();
+  fs::write(&path, "INFO after rotate\n").unwrap();
+  events.wait_for(|e| e.texts().iter().any(|t| t == "INFO after rotate"));
+  assert!(events.texts().iter().any(|t| t == "INFO last before rotate"));
+  assert!(events.resets().contains(&"rotated".to_string()));
+
+  // Truncation in place.
+  std::thread::sleep(Duration::from_millis(60));
+  fs::write(&path, "").unwrap();
+  std::thread::sleep(Duration::from_millis(60));
+  append(&dir.path().join("gateway.err.log"), "ERROR from stderr\n");
+  append(&path, "INFO after truncate\n");
+  events.wait_for(|e| e.texts().iter().any(|t| t == "INFO after truncate"));
+  assert!(events.resets().contains(&"truncated".to_string()));
+
+  let first = events.0.lock().unwrap()[0].clone();
+  assert_eq!(first["follow_id"], "f1");
+  assert_eq!(first["profile_id"], "p1");
+  assert!(events.0.lock().unwrap().iter().flat_map(|e| e["lines"].as_array().unwrap().clone()).any(|l| l["stream"] == "err" && l["level"] == "error"));
+  assert!(!events.texts().iter().any(|t| t == "INFO old line" || t == "skipped"));
+
+  drop(follower);
+  let n = events.texts().len();
+  append(&path, "INFO after stop\n");
+  std::thread::sleep(Duration::from_millis(100));
+  assert_eq!(events.texts().len(), n);
+}
+
+#[test]
+fn snapshot_writes_filtered_tail() {
+  let home = tempfile::tempdir().unwrap();
+  let root = tempfile::tempdir().unwrap();
+  let st = Storage::new(root.path(), home.path());
+  let logs = st.openclaw_logs_dir();
+  fs::create_dir_all(&logs).unwrap();
+  fs::write(logs.join("gateway.log"), "INFO a\nERROR b\nINFO c\n").unwrap();
+
+  let filter = LogFilter { level: Some(LogLevel::Error), pattern: None };
+  let path = gateway_logs::save_snapshot(&st, "p1", None, None, &filter).unwrap();
+  assert!(path.starts_with(root.path()));
+  let body = fs::read_to_string(&path).unwrap();
+  assert!(body.contains("ERROR b"));
+  assert!(!body.contains("INFO a"));
+
+  let dest = root.path().join("out.log");
+  assert_eq!(gateway_logs::save_snapshot(&st, "p1", Some(dest.clone()), Some(1), &LogFilter::default()).unwrap(), dest);
+  assert!(fs::read_to_string(&dest).unwrap().contains("INFO c"));
+}
@@ -0,0 +1,184 @@
+// Runs the gateway supervisor against a fake gateway with millisecond timings.
+
+use std::{
+  sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    mpsc, Arc, Mutex,
+  },
+  time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use tauri_appopenclaw_macapp_lib::{
+  core::EventSink,
+  gateway_supervisor::{self, GatewayControl, GatewayHealth, Supervisor, Timing},
+};
+
+#[derive(Default)]
+struct FakeGateway {
+  up: AtomicBool,
+  starts: AtomicU32,
+  // Number of start calls that fail before one succeeds.
+  failing_starts: AtomicU32,
+}
+
+impl GatewayControl for FakeGateway {
+  fn check(&self) -> Result<bool> {
+    Ok(self.up.load(Ordering::SeqCst))
+  }
+
+  fn start(&self) -> Result<()> {
+    self.starts.fetch_add(1, Ordering::SeqCst);
+    if self.failing_starts.load(Ordering::SeqCst) > 0 {
+      self.failing_starts.fetch_sub(1, Ordering::SeqCst);
+      return Err(anyhow!("port in use"));
+    }
+    self.up.store(true, Ordering::SeqCst);
+    Ok(())
+  }
+}
+
+#[derive(Default)]
+struct Events(Mutex<Vec<Value>>);
+
+impl EventSink for Events {
+  fn emit_json(&self, event: &str, payload: Value) {
+    assert_eq!(event, gateway_supervisor::STATE_EVENT);
+    self.0.lock().unwrap().push(payload);
+  }
+}
+
+impl Events {
+  fn healths(&self) -> Vec<String> {
+    let mut out: Vec<String> = vec![];
+    for e in self.0.lock().unwrap().iter() {
+      let h = e["health"].as_str().unwrap().to_string();
+      if out.last() != Some(&h) {
+        out.push(h);
+      }
+    }
+    out
+  }
+}
+
+fn timing() -> Timing {
+  Timing {
+    poll: Duration::from_millis(10),
+    backoff_base: Duration::from_millis(40),
+    backoff_max: Duration::from_millis(200),
+    stable: Duration::from_secs(60),
+  }
+}
+
+fn wait_for(what: &str, f: impl Fn() -> bool) {
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while !f() {
+    assert!(Instant::now() < deadline, "timed out waiting for {what}");
+    std::thread::sleep(Duration::from_millis(5));
+  }
+}
+
+fn spawn(dir: &std::path::Path, gw: &Arc<FakeGateway>, events: &Arc<Events>) -> Supervisor {
+  Supervisor::spawn("p_test", dir.to_path_buf(), gw.clone(), events.clone(), timing())
+}
+
+#[test]
+fn crash_is_restarted_and_recorded() {
+  let dir = tempfile::tempdir().unwrap();
+  let gw = Arc::new(FakeGateway::default());
+  gw.up.store(true, Ordering::SeqCst);
+  let events = Arc::new(Events::default());
+  let sup = spawn(dir.path(), &gw, &events);
+
+  wait_for("running", || sup.state().health == GatewayHealth::Running);
+  gw.up.store(false, Ordering::SeqCst);
+  wait_for("restart", || gw.starts.load(Ordering::SeqCst) == 1 && sup.state().health == GatewayHealth::Running);
+
+  assert_eq!(events.healths(), ["running", "down", "restarting", "running"]);
+  let state = sup.state();
+  assert_eq!(state.restarts.len(), 1);
+  assert!(state.restarts[0].ok);
+
+  // History survives the supervisor.
+  drop(sup);
+  let record = gateway_supervisor::load_record(dir.path()).unwrap();
+  assert_eq!(record.restarts.len(), 1);
+  assert_eq!(record.restarts[0].attempt, 1);
+}
+
+#[test]
+fn failed_restarts_back_off_exponentially() {
+  let dir = tempfile::tempdir().unwrap();
+  let gw = Arc::new(FakeGateway::default());
+  gw.failing_starts.store(2, Ordering::SeqCst);
+  // The user started it, so being down is a crash.
+  gateway_supervisor::set_desired_in(dir.path(), true).unwrap();
+  let events = Arc::new(Events::default());
+  let sup = spawn(dir.path(), &gw, &events);
+
+  wait_for("recovery", || sup.state().health == GatewayHealth::Running);
+  let r = sup.state().restarts;
+  assert_eq!(r.iter().map(|r| (r.attempt, r.ok)).collect::<Vec<_>>(), [(1, false), (2, false), (3, true)]);
+  assert_eq!(r[0].error.as_deref(), Some("port in use"));
+  // 40ms after the first failure, then 80ms.
+  assert!(r[1].at_ms - r[0].at_ms >= 40, "{:?}", r);
+  assert!(r[2].at_ms - r[1].at_ms >= 80, "{:?}", r);
+}
+
+#[test]
+fn a_deliberate_stop_is_not_restarted() {
+  let dir = tempfile::tempdir().unwrap();
+  let gw = Arc::new(FakeGateway::default());
+  gw.up.store(true, Ordering::SeqCst);
+  let events = Arc::new(Events::default());
+  let sup = spawn(dir.path(), &gw, &events);
+  wait_for("running", || sup.state().health == GatewayHealth::Running);
+
+  // What `gateway stop` records before stopping it.
+  gateway_supervisor::set_desired_in(dir.path(), false).unwrap();
+  gw.up.store(false, Ordering::SeqCst);
+  sup.poke();
+  wait_for("stopped", || sup.state().health == GatewayHealth::Stopped);
+  std::thread::sleep(Duration::from_millis(150));
+
+  assert_eq!(gw.starts.load(Ordering::SeqCst), 0);
+  assert_eq!(events.healths(), ["running", "stopped"]);
+}
+
+// Stands in for a listener that waits on something (the root lock, the main thread) held by
+// whoever owns the supervisor.
+#[derive(Default)]
+struct BlockingSink {
+  entered: AtomicBool,
+  release: AtomicBool,
+}
+
+impl EventSink for BlockingSink {
+  fn emit_json(&self, _event: &str, _payload: Value) {
+    self.entered.store(true, Ordering::SeqCst);
+    while !self.release.load(Ordering::SeqCst) {
+      std::thread::sleep(Duration::from_millis(1));
+    }
+  }
+}
+
+#[test]
+fn listeners_run_without_the_state_lock() {
+  let dir = tempfile::tempdir().unwrap();
+  let gw = Arc::new(FakeGateway::default());
+  gw.up.store(true, Ordering::SeqCst);
+  let sink = Arc::new(BlockingSink::default());
+  let sup = Supervisor::spawn("p_test", dir.path().to_path_buf(), gw.clone(), sink.clone(), timing());
+  wait_for("an event", || sink.entered.load(Ordering::SeqCst));
+
+  // The owner can still reach the supervisor while a listener is stuck.
+  let (tx, rx) = mpsc::channel();
+  std::thread::scope(|s| {
+    s.spawn(|| tx.send(sup.state().health).unwrap());
+    let health = rx.recv_timeout(Duration::from_secs(2));
+    sink.release.store(true, Ordering::SeqCst);
+    assert_eq!(health, Ok(GatewayHealth::Running));
+  });
+}
@@ -0,0 +1,80 @@
+// Collision-free ids and the migration that repairs duplicates left by the old id scheme.
+
+mod support;
+
+use std::fs;
+
+use tauri_appopenclaw_macapp_lib::{chats, core, profiles, secrets, settings};
+
+#[test]
+fn new_ids_are_unique_and_sorted() {
+  let ids: Vec<String> = (0..2000).map(|_| core::new_id("c")).collect();
+  assert!(ids.windows(2).all(|w| w[0] < w[1]), "not strictly increasing");
+  assert!(ids.iter().all(|id| id.len() == 28 && id.starts_with("c_")));
+
+  let home = tempfile::tempdir().unwrap();
+  let st = core::Storage::new(home.path().join("data"), home.path());
+  profiles::ensure_default_profile(&st).unwrap();
+  for i in 0..5 {
+    profiles::create(&st, &format!("P{i}")).unwrap();
+  }
+  let store = profiles::ensure_default_profile(&st).unwrap();
+  assert_eq!(store.profiles.len(), 6);
+  assert!(core::ensure_unique_ids("profile", store.profiles.iter().map(|p| p.id.as_str())).is_ok());
+}
+
+#[test]
+fn migrates_duplicate_ids_and_rejects_new_ones() {
+  support::keychain::install();
+  let home = tempfile::tempdir().unwrap();
+  let data = home.path().join("data");
+  let st = core::Storage::new(&data, home.path());
+  // Two profiles created in the same millisecond under the old scheme share an id and a dir.
+  let dir = data.join("profiles/p_1700000000000");
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(
+    data.join("profiles.json"),
+    r#"{"version":1,"active_profile_id":"p_1700000000000","profiles":[
+      {"id":"p_1700000000000","name":"Work","created_at_ms":1700000000000},
+      {"id":"p_1700000000000","name":"Home","created_at_ms":1700000000000}]}"#,
+  )
+  .unwrap();
+  let chat = r#"{"id":"c_1700000000001_1","title":"T","session_id":"desktop-c_1700000000001_1","created_at_ms":1,"updated_at_ms":1,"agent_id":null,"thinking":null,"worker":null}"#;
+  fs::write(dir.join("chats.json"), format!(r#"{{"version":1,"chats":[{chat},{chat}]}}"#)).unwrap();
+  let msg = r#"{"id":"m_1700000000002_1","role":"user","text":"hi","created_at_ms":2}"#;
+  fs::write(dir.join("chat_c_1700000000001_1.json"), format!(r#"{{"version":1,"chat_id":"c_1700000000001_1","messages":[{msg},{msg}]}}"#)).unwrap();
+  assert!(chats::load_index_in(&dir).is_err());
+  secrets::set_secret(&st, "p_1700000000000", "openai", "sk-shared").unwrap();
+
+  let store = profiles::ensure_default_profile(&st).unwrap();
+  assert_eq!(store.version, 2);
+  // The first of each duplicate keeps its id; the old ids stay readable.
+  assert_eq!(store.profiles[0].id, "p_1700000000000");
+  let home_id = &store.profiles[1].id;
+  assert_ne!(home_id, "p_1700000000000");
+  assert!(store.migration_notes[0].contains(&format!("replaced by {home_id}")), "{:?}", store.migration_notes);
+  // Shown until dismissed.
+  assert_eq!(profiles::ensure_default_profile(&st).unwrap().migration_notes, store.migration_notes);
+  assert!(profiles::dismiss_migration_notes(&st).unwrap().migration_notes.is_empty());
+  assert!(profiles::ensure_default_profile(&st).unwrap().migration_notes.is_empty());
+  // The copy keeps its keychain secrets and the OpenClaw profile the two shared.
+  assert_eq!(secrets::get_secret(&st, home_id, "openai").unwrap().as_deref(), Some("sk-shared"));
+  assert_eq!(settings::resolve_openclaw_profile(&st, home_id).unwrap(), "ocd-p-1700000000000");
+  for id in ["p_1700000000000", home_id.as_str()] {
+    let idx = chats::load_index(&st, id).unwrap();
+    assert_eq!(idx.chats[0].id, "c_1700000000001_1");
+    assert_ne!(idx.chats[1].id, idx.chats[0].id);
+    assert_eq!(idx.chats[1].session_id, format!("desktop-{}", idx.chats[1].id));
+    let copy = chats::load_thread(&st, id, &idx.chats[1].id).unwrap();
+    assert_eq!(copy.chat_id, idx.chats[1].id);
+    let thread = chats::load_thread(&st, id, "c_1700000000001_1").unwrap();
+    assert_eq!(thread.messages[0].id, "m_1700000000002_1");
+    assert_ne!(thread.messages[1].id, thread.messages[0].id);
+  }
+
+  // Once migrated, a duplicate is an error instead of being silently repaired.
+  let raw = fs::read_to_string(data.join("profiles.json")).unwrap();
+  fs::write(data.join("profiles.json"), raw.replace(home_id.as_str(), "p_1700000000000")).unwrap();
+  let err = format!("{:#}", profiles::ensure_default_profile(&st).unwrap_err());
+  assert!(err.contains("duplicate profile id p_1700000000000"), "{err}");
+}
@@ -0,0 +1,54 @@
+// Auto-do intent detection: keyword rules and the model classifier's fallback to them.
+
+mod support;
+
+use tauri_appopenclaw_macapp_lib::intent::{self, IntentConfig, IntentMode};
+
+use support::mock_ollama::MockOllama;
+
+fn forces_tools(text: &str) -> bool {
+  intent::classify_keywords(text, &intent::default_rules()).force_tools
+}
+
+#[test]
+fn keywords_match_whole_words_and_respect_negations() {
+  // The motivating case: "update" is an action word, but this is a question.
+  let d = intent::classify_keywords("What's the update on the release?", &intent::default_rules());
+  assert!(!d.force_tools);
+  assert_eq!(d.reason, "negation matched: \"what's the\"");
+  assert!(!forces_tools("what\u{2019}s the update on X"));
+
+  let d = intent::classify_keywords("Run the tests", &intent::default_rules());
+  assert!(d.force_tools);
+  assert_eq!((d.method.as_str(), d.reason.as_str()), ("keywords", "action phrase matched: \"run\""));
+  assert!(forces_tools("please set up the repo"));
+
+  assert!(!forces_tools("don't run anything"));
+  assert!(!forces_tools("Do not delete my files"));
+  // Words only match whole: "running" isn't "run", "fixture" isn't "fix".
+  assert!(!forces_tools("I was running late"));
+  assert!(!forces_tools("the fixture looks odd"));
+  assert!(!forces_tools("thanks!"));
+}
+
+#[test]
+fn model_mode_falls_back_to_keywords() {
+  let cfg = IntentConfig { mode: IntentMode::Model, ..Default::default() };
+
+  let mock = MockOllama::start(&["ACTION"]);
+  let d = intent::classify(&cfg, &mock.base_url(), None, "ollama/qwen3:8b", "tidy up my downloads");
+  assert!(d.force_tools);
+  assert_eq!(d.method, "model");
+
+  // Nothing listening: the keyword rules decide.
+  let d = intent::classify(&cfg, "http://127.0.0.1:9", None, "ollama/qwen3:8b", "run the tests");
+  assert!(d.force_tools);
+  assert_eq!(d.method, "keywords_fallback");
+  assert!(d.reason.contains("classifier failed"), "{}", d.reason);
+
+  // An answer that's neither ACTION nor CHAT counts as a failure too.
+  let mock = MockOllama::start(&["maybe?"]);
+  let d = intent::classify(&cfg, &mock.base_url(), None, "ollama/qwen3:8b", "what's the update on X");
+  assert!(!d.force_tools);
+  assert_eq!(d.method, "keywords_fallback");
+}
@@ -0,0 +1,23 @@
+// Without a keychain that keeps values, new secrets go to the vault. A separate test binary: the
+// keychain probe runs once per process.
+
+use tauri_appopenclaw_macapp_lib::{core::Storage, secrets, settings::SecretBackend, vault};
+
+#[test]
+fn secrets_fall_back_to_the_vault() {
+  // keyring's mock store answers every call but forgets values between entries.
+  keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+
+  let status = secrets::backend_status(&st, "p1").unwrap();
+  assert!(!status.keychain_available);
+  assert_eq!((status.configured, status.effective), (None, SecretBackend::Vault));
+  assert!(status.keychain_error.unwrap().contains("doesn't keep values"));
+
+  assert!(secrets::set_secret(&st, "p1", "openai", "sk-one").is_err());
+  vault::unlock(&st, "p1", "pass").unwrap();
+  secrets::set_secret(&st, "p1", "openai", "sk-one").unwrap();
+  assert_eq!(secrets::list(&st, "p1").unwrap()[0].backend, SecretBackend::Vault);
+  assert_eq!(secrets::get_secret(&st, "p1", "openai").unwrap().as_deref(), Some("sk-one"));
+}
@@ -0,0 +1,114 @@
+// OpenClaw agent management against a fake openclaw that keeps its agents in a file.
+
+use tauri_appopenclaw_macapp_lib::{
+  desktop_agent,
+  openclaw_agents::{self, AgentInfo},
+};
+
+#[test]
+fn default_agent_ids_fit_openclaws_limit() {
+  assert_eq!(desktop_agent::default_agent_id("ocd-p1", "ollama/qwen3:8b"), "desktop-ocd-p1-ollama-qwen3-8b");
+
+  let profile = "ocd-p-01jabcdefghjkmnpqrstvwxyz0";
+  let id = desktop_agent::default_agent_id(profile, "ollama/qwen2.5-coder:14b");
+  assert!(id.len() <= 64 && id.starts_with("desktop-ollama-qwen2-5-coder"), "{id}");
+  openclaw_agents::validate_id(&id).unwrap();
+  let long = desktop_agent::default_agent_id(profile, &format!("ollama/{}", "x".repeat(200)));
+  openclaw_agents::validate_id(&long).unwrap();
+  // Stable, and distinct per profile and model.
+  assert_eq!(desktop_agent::default_agent_id(profile, "ollama/qwen2.5-coder:14b"), id);
+  assert_ne!(desktop_agent::default_agent_id("ocd-p-01jabcdefghjkmnpqrstvwxyz1", "ollama/qwen2.5-coder:14b"), id);
+  assert_ne!(desktop_agent::default_agent_id(profile, "ollama/qwen2.5-coder:32b"), id);
+}
+
+#[test]
+fn parses_agent_lists_and_validates_ids() {
+  let bare = r#"[{"id":"main","isDefault":true,"workspace":"/w/main","model":{"primary":"ollama/qwen3:8b"}}]"#;
+  assert_eq!(
+    openclaw_agents::parse_list(bare).unwrap(),
+    [AgentInfo {
+      id: "main".into(),
+      name: None,
+      workspace: Some("/w/main".into()),
+      model: Some("ollama/qwen3:8b".into()),
+      is_default: true,
+    }]
+  );
+  let wrapped = "Config warnings: none\n{\"agents\":[{\"id\":\"a\",\"name\":\"A\",\"model\":\"m\"},{\"name\":\"no id\"}]}";
+  let agents = openclaw_agents::parse_list(wrapped).unwrap();
+  assert_eq!(agents.len(), 1);
+  assert_eq!((agents[0].name.as_deref(), agents[0].model.as_deref(), agents[0].is_default), (Some("A"), Some("m"), false));
+  assert!(openclaw_agents::parse_list("no agents").is_err());
+
+  for ok in ["main", "desktop-ocd-p1-ollama-qwen3-8b", "a_1"] {
+    openclaw_agents::validate_id(ok).unwrap();
+  }
+  for bad in ["", "-lead", "Upper", "has space", "../x", &"a".repeat(65)] {
+    assert!(openclaw_agents::validate_id(bad).is_err(), "{bad}");
+  }
+}
+
+#[cfg(unix)]
+#[test]
+fn manages_agents_and_the_chats_using_them() {
+  use std::{fs, os::unix::fs::PermissionsExt};
+
+  use tauri_appopenclaw_macapp_lib::{chats, core::Storage, settings};
+
+  let home = tempfile::tempdir().unwrap();
+  let agents = home.path().join("agents.txt");
+  let log = home.path().join("args.log");
+  fs::write(&agents, "main\n").unwrap();
+  // Agents are one id per line; `config get agents.list` lists the non-default ones.
+  let body = format!(
+    r#"#!/bin/sh
+if [ "$1" = --version ]; then echo 2026.2.14; exit 0; fi
+echo "$*" >> '{log}'
+shift 2
+case "$1 $2" in
+  "agents list") printf '['; sep=''; while read -r id; do printf '%s{{"id":"%s","isDefault":%s}}' "$sep" "$id" "$([ "$id" = main ] && echo true || echo false)"; sep=,; done < '{agents}'; echo ']' ;;
+  "agents add") for last; do :; done; echo "$last" >> '{agents}' ;;
+  "agents delete") grep -vx "$3" '{agents}' > '{agents}.new'; mv '{agents}.new' '{agents}' ;;
+  "config get") printf '['; sep=''; grep -vx main '{agents}' | while read -r id; do printf '%s{{"id":"%s"}}' "$sep" "$id"; sep=,; done; echo ']' ;;
+  "config set") ;;
+  *) echo "unexpected: $*" >&2; exit 1 ;;
+esac
+"#,
+    log = log.display(),
+    agents = agents.display()
+  );
+  let bin = home.path().join("openclaw");
+  fs::write(&bin, body).unwrap();
+  fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+
+  let st = Storage::new(home.path().join("data"), home.path());
+  let mut s = settings::load_settings(&st, "p1").unwrap();
+  s.openclaw_path = Some(bin.to_string_lossy().to_string());
+  s.ollama_model = Some("ollama/qwen3:8b".into());
+  settings::save_settings(&st, "p1", &s).unwrap();
+
+  let listed = openclaw_agents::create(&st, "p1", "research", None, None).unwrap();
+  assert_eq!(listed.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), ["main", "research"]);
+  let err = openclaw_agents::create(&st, "p1", "research", None, None).unwrap_err().to_string();
+  assert!(err.contains("already exists"), "{err}");
+  assert!(openclaw_agents::create(&st, "p1", "Bad Id", None, None).is_err());
+
+  openclaw_agents::update(&st, "p1", "research", None, Some("ollama/llama3:8b".into())).unwrap();
+  assert!(openclaw_agents::update(&st, "p1", "ghost", None, Some("m".into())).is_err());
+
+  let chat = chats::create(&st, "p1", None).unwrap();
+  assert!(chats::set_agent(&st, "p1", &chat.id, Some("ghost".into())).is_err());
+  let idx = chats::set_agent(&st, "p1", &chat.id, Some("research".into())).unwrap();
+  assert_eq!(idx.chats[0].agent_id.as_deref(), Some("research"));
+
+  let listed = openclaw_agents::delete(&st, "p1", "research").unwrap();
+  assert_eq!(listed.len(), 1);
+  assert_eq!(chats::load_index(&st, "p1").unwrap().chats[0].agent_id, None);
+
+  let calls = fs::read_to_string(&log).unwrap();
+  let ws = st.profile_dir("p1").unwrap().join("workspace");
+  assert!(calls.contains(&format!("--profile ocd-p1 agents add --non-interactive --workspace {} --model ollama/qwen3:8b research", ws.display())), "{calls}");
+  assert!(ws.join("SOUL.md").exists());
+  assert!(calls.contains("config set agents.list[0].model ollama/llama3:8b"), "{calls}");
+  assert!(calls.contains("agents delete research --force"), "{calls}");
+}
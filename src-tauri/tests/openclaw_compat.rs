@@ -0,0 +1,79 @@
+// OpenClaw version ranges and the flags passed to each release.
+
+use tauri_appopenclaw_macapp_lib::openclaw_compat::{Compat, Support};
+
+#[test]
+fn classifies_versions() {
+  let current = Compat::for_version(Some("2026.2.14"));
+  assert_eq!(current.support, Support::Supported);
+  assert!(current.message.is_none());
+  assert!(current.caps.gateway_status_json && current.caps.models_status_json && current.caps.agent_channel);
+  current.require_supported().unwrap();
+
+  let old = Compat::for_version(Some("2025.12.3"));
+  assert_eq!(old.support, Support::Unsupported);
+  let err = old.require_supported().unwrap_err().to_string();
+  assert!(err.contains("2025.12.3 is not supported") && err.contains("npm install -g openclaw@latest"), "{err}");
+
+  let newer = Compat::for_version(Some("2026.3.0"));
+  assert_eq!(newer.support, Support::Untested);
+  assert!(newer.message.unwrap().contains("newer than"));
+  assert_eq!(Compat::for_version(Some("2027")).support, Support::Untested);
+
+  let unknown = Compat::for_version(None);
+  assert_eq!(unknown.support, Support::Unknown);
+  assert!(unknown.caps.gateway_status_json && unknown.caps.agents_non_interactive);
+  unknown.require_supported().unwrap();
+}
+
+#[test]
+fn gates_flags_by_release() {
+  let early = Compat::for_version(Some("2026.1.7")).caps;
+  assert!(!early.gateway_status_json);
+  assert!(!early.models_status_json);
+  assert!(!early.agents_non_interactive);
+  assert!(!early.agent_channel);
+
+  let mid = Compat::for_version(Some("2026.1.20")).caps;
+  assert!(mid.agents_non_interactive && mid.agent_channel);
+  assert!(!mid.gateway_status_json);
+}
+
+#[cfg(unix)]
+#[test]
+fn commands_follow_the_installed_release() {
+  use std::{fs, os::unix::fs::PermissionsExt};
+
+  use tauri_appopenclaw_macapp_lib::{core::Storage, gateway, models, settings};
+
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let log = home.path().join("args.log");
+  let fake = |name: &str, version: &str| {
+    let path = home.path().join(name);
+    let body = format!(
+      "#!/bin/sh\nif [ \"$1\" = --version ]; then echo {version}; exit 0; fi\necho \"$*\" >> '{}'\necho 'Gateway: running (pid 42)'\n",
+      log.display()
+    );
+    fs::write(&path, body).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+  };
+  let pin = |path: &std::path::Path| {
+    let mut s = settings::load_settings(&st, "p1").unwrap();
+    s.openclaw_path = Some(path.to_string_lossy().to_string());
+    settings::save_settings(&st, "p1", &s).unwrap();
+  };
+
+  pin(&fake("openclaw-early", "2026.1.7"));
+  let out = gateway::status(&st, "p1").unwrap();
+  assert_eq!(out.info.unwrap().pid, Some(42));
+  models::status(&st, "p1").unwrap();
+  let calls = fs::read_to_string(&log).unwrap();
+  assert!(!calls.contains("--json"), "{calls}");
+  assert!(calls.contains("models status --status-plain"), "{calls}");
+
+  pin(&fake("openclaw-old", "2025.11.0"));
+  let err = gateway::status(&st, "p1").unwrap_err().to_string();
+  assert!(err.contains("not supported"), "{err}");
+}
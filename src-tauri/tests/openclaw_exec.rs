@@ -0,0 +1,129 @@
+// The openclaw process runner against small shell scripts standing in for openclaw.
+#![cfg(unix)]
+
+use std::{
+  fs,
+  os::unix::fs::PermissionsExt,
+  path::{Path, PathBuf},
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use tauri_appopenclaw_macapp_lib::{
+  core::EventSink,
+  openclaw_exec::{self, ExecError, RunOpts},
+};
+
+fn script(dir: &Path, name: &str, body: &str) -> PathBuf {
+  let path = dir.join(name);
+  fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+  fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+  path
+}
+
+#[derive(Default)]
+struct Events(Mutex<Vec<Value>>);
+
+impl EventSink for Events {
+  fn emit_json(&self, event: &str, payload: Value) {
+    assert_eq!(event, openclaw_exec::OUTPUT_EVENT);
+    self.0.lock().unwrap().push(payload);
+  }
+}
+
+#[test]
+fn collects_and_streams_output() {
+  let dir = tempfile::tempdir().unwrap();
+  let bin = script(dir.path(), "openclaw", "echo \"args: $*\"\necho oops >&2\necho done");
+  let events = Events::default();
+  let opts = RunOpts { handle: Some("h-stream"), sink: Some(&events), ..RunOpts::default() };
+
+  let out = openclaw_exec::run(bin, vec!["gateway".into(), "status".into()], &opts).unwrap();
+  assert_eq!(out.code, 0);
+  assert_eq!(out.stdout, "args: gateway status\ndone\n");
+  assert_eq!(out.stderr, "oops\n");
+
+  let events = events.0.lock().unwrap();
+  assert_eq!(events.len(), 3);
+  assert!(events.iter().all(|e| e["handle"] == "h-stream"));
+  assert!(events.iter().any(|e| e["stream"] == "stderr" && e["line"] == "oops"));
+}
+
+#[test]
+fn separates_failure_kinds() {
+  let dir = tempfile::tempdir().unwrap();
+
+  let missing = openclaw_exec::run(dir.path().join("nope"), vec![], &RunOpts::default()).unwrap_err();
+  assert!(matches!(missing, ExecError::NotFound(_)), "{missing}");
+
+  let shim = dir.path().join("shim");
+  fs::write(&shim, "#!/nonexistent/node\n").unwrap();
+  fs::set_permissions(&shim, fs::Permissions::from_mode(0o755)).unwrap();
+  let node = openclaw_exec::run(shim, vec![], &RunOpts::default()).unwrap_err();
+  assert!(matches!(node, ExecError::NodeMissing(_)), "{node}");
+
+  let bin = script(dir.path(), "failing", "echo 'session file locked' >&2\nexit 3");
+  let out = openclaw_exec::run(bin, vec![], &RunOpts::default()).unwrap();
+  assert_eq!(out.code, 3);
+  let err = out.success().unwrap_err();
+  assert!(matches!(err, ExecError::Exit { code: 3, .. }));
+  assert_eq!(err.to_string(), "session file locked");
+}
+
+#[test]
+fn times_out_and_cancels() {
+  let dir = tempfile::tempdir().unwrap();
+  let bin = script(dir.path(), "slow", "echo started\nsleep 30");
+
+  let started = Instant::now();
+  let opts = RunOpts { timeout: Duration::from_millis(300), ..RunOpts::default() };
+  match openclaw_exec::run(bin.clone(), vec![], &opts) {
+    Err(ExecError::Timeout { stdout, .. }) => assert_eq!(stdout, "started\n"),
+    other => panic!("expected timeout, got {other:?}"),
+  }
+  assert!(started.elapsed() < Duration::from_secs(5));
+
+  assert!(!openclaw_exec::cancel("h-cancel"));
+  let canceller = std::thread::spawn(|| {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !openclaw_exec::cancel("h-cancel") {
+      assert!(Instant::now() < deadline);
+      std::thread::sleep(Duration::from_millis(20));
+    }
+  });
+  let started = Instant::now();
+  let opts = RunOpts { handle: Some("h-cancel"), ..RunOpts::default() };
+  assert!(matches!(openclaw_exec::run(bin, vec![], &opts), Err(ExecError::Cancelled)));
+  assert!(started.elapsed() < Duration::from_secs(5));
+  canceller.join().unwrap();
+  // The handle is released once the run ends.
+  assert!(!openclaw_exec::cancel("h-cancel"));
+}
+
+#[test]
+fn times_out_when_the_child_closes_its_output_and_keeps_running() {
+  let dir = tempfile::tempdir().unwrap();
+  let bin = script(dir.path(), "quiet", "echo bye\nexec >/dev/null 2>&1\nsleep 30");
+  let started = Instant::now();
+  let opts = RunOpts { timeout: Duration::from_millis(500), ..RunOpts::default() };
+  match openclaw_exec::run(bin, vec![], &opts) {
+    Err(ExecError::Timeout { stdout, .. }) => assert_eq!(stdout, "bye\n"),
+    other => panic!("expected timeout, got {other:?}"),
+  }
+  assert!(started.elapsed() < Duration::from_secs(5));
+
+  let bin = script(dir.path(), "quiet-exit", "exec >/dev/null 2>&1\nsleep 0.3\nexit 3");
+  assert_eq!(openclaw_exec::run(bin, vec![], &RunOpts::timeout(10)).unwrap().code, 3);
+}
+
+#[test]
+fn returns_when_a_background_child_keeps_the_pipes_open() {
+  let dir = tempfile::tempdir().unwrap();
+  let bin = script(dir.path(), "daemonizing", "sleep 30 &\necho launched");
+  let started = Instant::now();
+  let out = openclaw_exec::run(bin, vec![], &RunOpts::timeout(10)).unwrap();
+  assert_eq!(out.stdout, "launched\n");
+  assert!(started.elapsed() < Duration::from_secs(5));
+}
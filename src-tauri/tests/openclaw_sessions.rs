@@ -0,0 +1,116 @@
+// Chat delete/reset against OpenClaw session files laid out the way OpenClaw writes them.
+
+use std::{fs, path::Path};
+
+use tauri_appopenclaw_macapp_lib::{
+  chats,
+  core::Storage,
+  openclaw_sessions, profiles,
+  settings::{self, ChatBackend},
+};
+
+fn session(state: &Path, agent: &str, id: &str) -> std::path::PathBuf {
+  let dir = state.join("agents").join(agent).join("sessions");
+  fs::create_dir_all(&dir).unwrap();
+  let path = dir.join(format!("{id}.jsonl"));
+  fs::write(&path, "{\"type\":\"message\"}\n").unwrap();
+  let store = dir.join("sessions.json");
+  let mut map: serde_json::Map<String, serde_json::Value> = fs::read_to_string(&store).ok().map(|s| serde_json::from_str(&s).unwrap()).unwrap_or_default();
+  map.insert(format!("agent:{agent}:{id}"), serde_json::json!({ "sessionId": id }));
+  fs::write(&store, serde_json::to_string(&map).unwrap()).unwrap();
+  path
+}
+
+#[test]
+fn delete_and_reset_follow_the_chat_session() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let state = openclaw_sessions::state_dir(&st, "p1").unwrap();
+  assert_eq!(state, home.path().join(".openclaw-ocd-p1"));
+
+  let kept = chats::create(&st, "p1", Some("Kept".into())).unwrap();
+  let gone = chats::create(&st, "p1", Some("Gone".into())).unwrap();
+  let kept_file = session(&state, "main", &kept.session_id);
+  let gone_file = session(&state, "main", &gone.session_id);
+  session(&state, "main", "desktop-c_old_0");
+  session(&state, "main", "telegram-123");
+
+  let listed = openclaw_sessions::list(&st, "p1").unwrap();
+  assert_eq!(listed.len(), 4);
+  let orphans: Vec<&str> = listed.iter().filter(|s| s.orphaned).map(|s| s.session_id.as_str()).collect();
+  assert_eq!(orphans, ["desktop-c_old_0"]);
+  assert_eq!(listed.iter().find(|s| s.session_id == kept.session_id).unwrap().chat_title.as_deref(), Some("Kept"));
+
+  chats::delete(&st, "p1", &gone.id).unwrap();
+  assert!(!gone_file.exists());
+  let store = fs::read_to_string(state.join("agents/main/sessions/sessions.json")).unwrap();
+  assert!(!store.contains(&gone.session_id) && store.contains(&kept.session_id), "{store}");
+
+  chats::reset(&st, "p1", &kept.id).unwrap();
+  assert!(!kept_file.exists());
+  let rotated = chats::load_index(&st, "p1").unwrap().chats.into_iter().find(|c| c.id == kept.id).unwrap();
+  assert_ne!(rotated.session_id, kept.session_id);
+  assert!(rotated.session_id.starts_with(&format!("desktop-{}-", kept.id)));
+
+  assert_eq!(openclaw_sessions::prune(&st, "p1").unwrap(), ["desktop-c_old_0"]);
+  let left: Vec<String> = openclaw_sessions::list(&st, "p1").unwrap().into_iter().map(|s| s.session_id).collect();
+  assert_eq!(left, ["telegram-123"]);
+}
+
+#[test]
+fn profiles_sharing_an_openclaw_profile_keep_each_others_sessions() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let work = profiles::ensure_default_profile(&st).unwrap().profiles[0].id.clone();
+  let home_id = profiles::create(&st, "Home").unwrap().active_profile_id.unwrap();
+  for id in [&work, &home_id] {
+    settings::update_settings(&st, id, |s| s.openclaw_profile = Some("shared".into())).unwrap();
+  }
+  let state = openclaw_sessions::state_dir(&st, &work).unwrap();
+  assert_eq!(state, openclaw_sessions::state_dir(&st, &home_id).unwrap());
+
+  let theirs = chats::create(&st, &home_id, Some("Groceries".into())).unwrap();
+  session(&state, "main", &theirs.session_id);
+  session(&state, "main", "desktop-c_old_0");
+
+  let listed = openclaw_sessions::list(&st, &work).unwrap();
+  let used = listed.iter().find(|s| s.session_id == theirs.session_id).unwrap();
+  assert!(!used.orphaned);
+  assert_eq!(used.chat_title.as_deref(), Some("Groceries (Home)"));
+  assert_eq!(openclaw_sessions::prune(&st, &work).unwrap(), ["desktop-c_old_0"]);
+  assert!(openclaw_sessions::exists(&st, &home_id, &theirs.session_id).unwrap());
+}
+
+#[test]
+fn clears_stale_locks_and_attaches_sessions() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let state = openclaw_sessions::state_dir(&st, "p1").unwrap();
+  let stale = session(&state, "main", "desktop-a");
+  let live = session(&state, "main", "desktop-b");
+  // No process has pid 0x7fffffff; this test process holds the other lock.
+  fs::write(state.join("agents/main/sessions/desktop-a.jsonl.lock"), "{\"pid\":2147483647}").unwrap();
+  fs::write(state.join("agents/main/sessions/desktop-b.jsonl.lock"), format!("{{\"pid\":{}}}", std::process::id())).unwrap();
+
+  let listed = openclaw_sessions::list(&st, "p1").unwrap();
+  let a = listed.iter().find(|s| s.session_id == "desktop-a").unwrap();
+  assert!(a.locked);
+  #[cfg(unix)]
+  {
+    assert!(a.lock_stale);
+    assert!(!listed.iter().find(|s| s.session_id == "desktop-b").unwrap().lock_stale);
+    let cleared = openclaw_sessions::clear_locks(&st, "p1", None, false).unwrap();
+    assert_eq!(cleared.len(), 1);
+    assert!(cleared[0].ends_with("desktop-a.jsonl.lock"));
+  }
+  assert_eq!(openclaw_sessions::clear_locks(&st, "p1", Some("desktop-b"), true).unwrap().len(), 1);
+  assert!(stale.exists() && live.exists());
+
+  let chat = chats::attach_session(&st, "p1", "desktop-a", None).unwrap();
+  assert_eq!(chat.session_id, "desktop-a");
+  assert_eq!(chat.backend, Some(ChatBackend::Openclaw));
+  let err = chats::attach_session(&st, "p1", "desktop-a", None).unwrap_err().to_string();
+  assert!(err.contains("already used"), "{err}");
+  assert!(chats::attach_session(&st, "p1", "nope", None).is_err());
+  assert!(!openclaw_sessions::list(&st, "p1").unwrap().iter().find(|s| s.session_id == "desktop-a").unwrap().orphaned);
+}
@@ -0,0 +1,112 @@
+// Profile export/import round trips and the passphrase encryption used for bundled secrets.
+
+mod support;
+
+use std::{fs, io::Read};
+
+use tauri_appopenclaw_macapp_lib::{
+  chat_crypto::{self, KeySource},
+  chats::{self, ChatRole},
+  core::Storage,
+  crypto, profile_bundle, profiles,
+  settings::{self, ApiServerConfig},
+};
+
+#[test]
+fn round_trips_a_profile_with_fresh_ids() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let id = profiles::ensure_default_profile(&st).unwrap().profiles[0].id.clone();
+
+  let chat = chats::create(&st, &id, Some("Plans".into())).unwrap();
+  chats::update(&st, &id, &chat.id, None, Some("research".into()), None).unwrap();
+  let dir = st.profile_dir(&id).unwrap();
+  chats::begin_send_in(&dir, &chat.id, "hello").unwrap();
+  fs::create_dir_all(dir.join("workspace/notes")).unwrap();
+  fs::write(dir.join("workspace/notes/todo.md"), "- ship it\n").unwrap();
+  fs::create_dir_all(dir.join("traces")).unwrap();
+  fs::write(dir.join("traces/chat_x.json"), "{}").unwrap();
+  let mut s = settings::load_settings(&st, &id).unwrap();
+  s.ollama_model = Some("ollama/qwen3:8b".into());
+  s.openclaw_path = Some("/Users/someone/.nvm/bin/openclaw".into());
+  s.api_server = Some(ApiServerConfig { port: 8765, chat: Some(chat.id.clone()) });
+  settings::save_settings(&st, &id, &s).unwrap();
+
+  let bundle = home.path().join("plans.ocdprofile");
+  let out = profile_bundle::export(&st, &id, &bundle, None).unwrap();
+  assert_eq!((out.chats, out.files), (1, 4));
+  assert!(out.secrets.is_empty());
+
+  // Imported next to the original: a new name, new ids, and the machine-specific bits dropped.
+  let res = profile_bundle::import(&st, &bundle, None).unwrap();
+  assert_ne!(res.profile.id, id);
+  assert_eq!(res.profile.name, "Default (imported)");
+  assert_eq!(res.store.profiles.len(), 2);
+  let joined = res.conflicts.join("\n");
+  assert!(joined.contains("imported as"), "{joined}");
+  assert!(joined.contains("uses OpenClaw agent research"), "{joined}");
+  assert!(joined.contains("dropped the pinned openclaw path"), "{joined}");
+  assert!(joined.contains("port 8765"), "{joined}");
+
+  let idx = chats::load_index(&st, &res.profile.id).unwrap();
+  let copy = &idx.chats[0];
+  assert_eq!(copy.title, "Plans");
+  assert_ne!(copy.id, chat.id);
+  assert_eq!(copy.session_id, format!("desktop-{}", copy.id));
+  let thread = chats::load_thread(&st, &res.profile.id, &copy.id).unwrap();
+  assert_eq!(thread.chat_id, copy.id);
+  assert!(matches!(thread.messages[0].role, ChatRole::User));
+  assert_eq!(thread.messages[0].text, "hello");
+
+  let imported = settings::load_settings(&st, &res.profile.id).unwrap();
+  assert_eq!(imported.ollama_model.as_deref(), Some("ollama/qwen3:8b"));
+  assert!(imported.openclaw_path.is_none() && imported.api_server.is_none());
+  let new_dir = st.profile_dir(&res.profile.id).unwrap();
+  assert_eq!(fs::read_to_string(new_dir.join("workspace/notes/todo.md")).unwrap(), "- ship it\n");
+  assert!(!new_dir.join("traces").exists());
+
+  let garbage = home.path().join("garbage.ocdprofile");
+  fs::write(&garbage, "not a bundle").unwrap();
+  assert!(profile_bundle::import(&st, &garbage, None).is_err());
+  assert_eq!(profiles::ensure_default_profile(&st).unwrap().profiles.len(), 2);
+}
+
+#[test]
+fn encrypted_chats_stay_encrypted_in_the_bundle() {
+  support::keychain::install();
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let id = profiles::ensure_default_profile(&st).unwrap().profiles[0].id.clone();
+  let chat = chats::create(&st, &id, Some("Private plans".into())).unwrap();
+  chats::begin_send_in(&st.profile_dir(&id).unwrap(), &chat.id, "the launch code is 0000").unwrap();
+  chat_crypto::enable(&st, &id, KeySource::Passphrase, Some("chat-pw")).unwrap();
+
+  let bundle = home.path().join("private.ocdprofile");
+  let err = profile_bundle::export(&st, &id, &bundle, None).unwrap_err().to_string();
+  assert!(err.contains("passphrase"), "{err}");
+  profile_bundle::export(&st, &id, &bundle, Some("bundle-pw")).unwrap();
+  let mut raw = vec![];
+  flate2::read::GzDecoder::new(fs::File::open(&bundle).unwrap()).read_to_end(&mut raw).unwrap();
+  let raw = String::from_utf8_lossy(&raw);
+  assert!(!raw.contains("launch code") && !raw.contains("Private plans"));
+
+  assert!(profile_bundle::import(&st, &bundle, None).is_err());
+  assert!(profile_bundle::import(&st, &bundle, Some("wrong")).is_err());
+  assert_eq!(profiles::ensure_default_profile(&st).unwrap().profiles.len(), 1);
+  let res = profile_bundle::import(&st, &bundle, Some("bundle-pw")).unwrap();
+  assert!(res.conflicts.join("\n").contains("turn on chat encryption"), "{:?}", res.conflicts);
+  let idx = chats::load_index(&st, &res.profile.id).unwrap();
+  assert_eq!(idx.chats[0].title, "Private plans");
+  assert_eq!(chats::load_thread(&st, &res.profile.id, &idx.chats[0].id).unwrap().messages[0].text, "the launch code is 0000");
+}
+
+#[test]
+fn sealed_data_needs_the_passphrase_and_context() {
+  let sealed = crypto::seal("correct horse", "bundle", b"{\"api_token\":\"s3cret\"}").unwrap();
+  assert!(!sealed.data.contains("s3cret"));
+  assert_eq!(crypto::open("correct horse", "bundle", &sealed).unwrap(), b"{\"api_token\":\"s3cret\"}");
+  let err = crypto::open("wrong", "bundle", &sealed).unwrap_err().to_string();
+  assert!(err.contains("wrong passphrase"), "{err}");
+  assert!(crypto::open("correct horse", "vault", &sealed).is_err());
+  assert!(crypto::seal("", "bundle", b"x").is_err());
+}
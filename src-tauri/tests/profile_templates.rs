@@ -0,0 +1,84 @@
+// Cloning profiles and creating them from templates.
+
+use std::fs;
+
+use tauri_appopenclaw_macapp_lib::{
+  chats,
+  core::Storage,
+  profile_templates, profiles,
+  settings::{self, ApiServerConfig},
+};
+
+#[test]
+fn clones_settings_workspace_and_optionally_chats() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let id = profiles::ensure_default_profile(&st).unwrap().profiles[0].id.clone();
+  let chat = chats::create(&st, &id, Some("Notes".into())).unwrap();
+  let dir = st.profile_dir(&id).unwrap();
+  chats::begin_send_in(&dir, &chat.id, "hello").unwrap();
+  fs::create_dir_all(dir.join("workspace/docs")).unwrap();
+  fs::write(dir.join("workspace/docs/a.md"), "a\n").unwrap();
+  fs::write(dir.join("prompts.json"), "{\"presets\":[]}").unwrap();
+  let mut s = settings::load_settings(&st, &id).unwrap();
+  s.system_prompt_template = Some("Be terse.".into());
+  s.api_server = Some(ApiServerConfig { port: 8765, chat: None });
+  settings::save_settings(&st, &id, &s).unwrap();
+
+  let store = profiles::clone(&st, &id, "Copy", true).unwrap();
+  let copy = store.active_profile_id.unwrap();
+  assert_ne!(copy, id);
+  let cs = settings::load_settings(&st, &copy).unwrap();
+  assert_eq!(cs.system_prompt_template.as_deref(), Some("Be terse."));
+  assert!(cs.api_server.is_none());
+  let copy_dir = st.profile_dir(&copy).unwrap();
+  assert_eq!(fs::read_to_string(copy_dir.join("workspace/docs/a.md")).unwrap(), "a\n");
+  assert!(copy_dir.join("prompts.json").exists());
+  let idx = chats::load_index(&st, &copy).unwrap();
+  assert_eq!(idx.chats.len(), 1);
+  assert_ne!(idx.chats[0].id, chat.id);
+  assert_eq!(idx.chats[0].session_id, format!("desktop-{}", idx.chats[0].id));
+  assert_eq!(chats::load_thread(&st, &copy, &idx.chats[0].id).unwrap().messages[0].text, "hello");
+
+  let bare = profiles::clone(&st, &id, "Bare", false).unwrap().active_profile_id.unwrap();
+  assert!(chats::load_index(&st, &bare).unwrap().chats.is_empty());
+  assert!(profiles::clone(&st, "p_missing", "X", false).is_err());
+}
+
+#[test]
+fn templates_preset_settings_and_seed_the_workspace() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  profiles::ensure_default_profile(&st).unwrap();
+  let ids: Vec<String> = profile_templates::list(&st).unwrap().into_iter().map(|t| t.id).collect();
+  assert_eq!(ids, ["safe-research", "dev-box"]);
+
+  let dev = profile_templates::create(&st, "Dev", "dev-box").unwrap().active_profile_id.unwrap();
+  let s = settings::load_settings(&st, &dev).unwrap();
+  assert_eq!((s.dev_full_exec_auto, s.auto_do_mode, s.ollama_num_ctx), (Some(true), Some(true), Some(32768)));
+  assert_eq!(s.agent_limits.unwrap().max_steps, 16);
+  // Everything the template doesn't set keeps the defaults.
+  assert!(s.ollama_model.is_some());
+  let agents = fs::read_to_string(st.profile_dir(&dev).unwrap().join("workspace/AGENTS.md")).unwrap();
+  assert!(agents.contains("Development workspace"), "{agents}");
+
+  // Saved from a profile: machine-specific settings stay behind.
+  let mut s = settings::load_settings(&st, &dev).unwrap();
+  s.openclaw_path = Some("/opt/openclaw".into());
+  settings::save_settings(&st, &dev, &s).unwrap();
+  let saved = profile_templates::save_from_profile(&st, &dev, "Mine", None).unwrap();
+  assert!(!saved.builtin && !saved.settings.contains_key("openclaw_path"));
+  assert_eq!(saved.settings["ollama_num_ctx"], 32768);
+  assert!(saved.workspace.contains_key("AGENTS.md"));
+
+  let mine = profile_templates::create(&st, "From mine", &saved.id).unwrap().active_profile_id.unwrap();
+  let s = settings::load_settings(&st, &mine).unwrap();
+  assert_eq!(s.ollama_num_ctx, Some(32768));
+  assert!(s.openclaw_path.is_none());
+
+  assert!(profile_templates::delete(&st, "dev-box").is_err());
+  assert_eq!(profile_templates::delete(&st, &saved.id).unwrap().len(), 2);
+  let before = profiles::ensure_default_profile(&st).unwrap().profiles.len();
+  assert!(profile_templates::create(&st, "Nope", &saved.id).is_err());
+  assert_eq!(profiles::ensure_default_profile(&st).unwrap().profiles.len(), before);
+}
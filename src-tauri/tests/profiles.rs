@@ -0,0 +1,101 @@
+// Profile deletion: the trash, restore, retention and secret cleanup.
+
+mod support;
+
+use std::fs;
+
+use tauri_appopenclaw_macapp_lib::{
+  chats,
+  core::Storage,
+  profiles::{self, DeleteOptions},
+  secrets, settings,
+};
+
+fn oc_dir(st: &Storage, id: &str) -> std::path::PathBuf {
+  st.home().join(format!(".openclaw-{}", settings::derived_openclaw_profile(id)))
+}
+
+// A second profile (the last one can't be deleted) with a chat, a secret and an OpenClaw state dir.
+fn setup(st: &Storage) -> String {
+  support::keychain::install();
+  profiles::ensure_default_profile(st).unwrap();
+  let store = profiles::create(st, "Doomed").unwrap();
+  let id = store.active_profile_id.unwrap();
+  chats::create(st, &id, Some("Keep me".into())).unwrap();
+  secrets::set_secret(st, &id, "api_token", "s3cret").unwrap();
+  fs::create_dir_all(oc_dir(st, &id).join("agents")).unwrap();
+  id
+}
+
+#[test]
+fn trashed_profiles_can_be_restored() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  let id = setup(&st);
+  assert_eq!(secrets::secret_keys(&st, &id).unwrap(), ["api_token"]);
+  let oc_dir = oc_dir(&st, &id);
+
+  let res = profiles::delete(&st, &id, DeleteOptions { permanent: false, openclaw_profile: true }).unwrap();
+  assert!(res.store.profiles.iter().all(|p| p.id != id));
+  assert_ne!(res.store.active_profile_id.as_deref(), Some(id.as_str()));
+  // Secrets stay until the trash entry is purged.
+  assert!(res.removed_secrets.is_empty());
+  let trash = res.trash.unwrap();
+  assert_eq!(trash.openclaw_profile, Some(settings::derived_openclaw_profile(&id)));
+  assert!(!home.path().join("data/profiles").join(&id).exists());
+  assert!(!oc_dir.exists());
+
+  let listed = profiles::list_trash(&st).unwrap();
+  assert_eq!(listed.len(), 1);
+  assert_eq!(listed[0].profile.name, "Doomed");
+
+  let store = profiles::restore(&st, &trash.trash_id).unwrap();
+  assert!(store.profiles.iter().any(|p| p.id == id));
+  assert_eq!(chats::load_index(&st, &id).unwrap().chats[0].title, "Keep me");
+  assert_eq!(secrets::secret_keys(&st, &id).unwrap(), ["api_token"]);
+  assert!(oc_dir.join("agents").is_dir());
+  assert!(profiles::list_trash(&st).unwrap().is_empty());
+  assert!(profiles::restore(&st, &trash.trash_id).is_err());
+
+  // Trash ids can't reach outside the trash.
+  for bad in ["..", "../profiles", "/tmp", "a/b", ""] {
+    let err = profiles::restore(&st, bad).unwrap_err().to_string();
+    assert!(err.contains("invalid trash id"), "{err}");
+    assert!(profiles::purge(&st, bad).unwrap_err().to_string().contains("invalid trash id"));
+  }
+}
+
+#[test]
+fn permanent_and_expired_deletes_remove_everything() {
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+
+  let id = setup(&st);
+  let res = profiles::delete(&st, &id, DeleteOptions { permanent: true, openclaw_profile: true }).unwrap();
+  assert!(res.trash.is_none());
+  assert_eq!(res.removed_secrets, ["api_token"]);
+  assert!(!home.path().join("data/profiles").join(&id).exists());
+  assert!(!oc_dir(&st, &id).exists());
+
+  // A profile pinned to a shared OpenClaw profile leaves that profile alone.
+  let id = setup(&st);
+  let mut s = settings::load_settings(&st, &id).unwrap();
+  s.openclaw_profile = Some("shared".into());
+  settings::save_settings(&st, &id, &s).unwrap();
+  fs::create_dir_all(home.path().join(".openclaw-shared")).unwrap();
+  let res = profiles::delete(&st, &id, DeleteOptions { permanent: false, openclaw_profile: true }).unwrap();
+  assert!(res.warnings.iter().any(|w| w.contains("kept the OpenClaw profile")), "{:?}", res.warnings);
+  assert!(home.path().join(".openclaw-shared").is_dir());
+
+  // Past the retention window the entry is purged on the next look at the trash.
+  let trash = res.trash.unwrap();
+  let meta = home.path().join("data/trash").join(&trash.trash_id).join("trash.json");
+  let mut entry: serde_json::Value = serde_json::from_str(&fs::read_to_string(&meta).unwrap()).unwrap();
+  entry["expires_at_ms"] = 0.into();
+  fs::write(&meta, entry.to_string()).unwrap();
+  assert!(profiles::list_trash(&st).unwrap().is_empty());
+  assert!(!meta.parent().unwrap().exists());
+
+  let last = profiles::ensure_default_profile(&st).unwrap().profiles[0].id.clone();
+  assert!(profiles::delete(&st, &last, DeleteOptions::default()).is_err());
+}
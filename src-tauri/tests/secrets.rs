@@ -0,0 +1,80 @@
+// The secret inventory: metadata, rotation, env/provider mappings and redaction.
+
+mod support;
+
+use std::fs;
+
+use tauri_appopenclaw_macapp_lib::{
+  core::Storage,
+  secrets::{self, RunSecrets, SecretMeta},
+};
+
+fn meta(provider: Option<&str>, env: Option<&str>) -> SecretMeta {
+  SecretMeta { purpose: Some("model access".into()), provider: provider.map(String::from), env: env.map(String::from) }
+}
+
+#[test]
+fn inventory_tracks_metadata_rotation_and_mappings() {
+  support::keychain::install();
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  // A profile from before the inventory: secrets.json only listed keys.
+  let dir = st.profile_dir("p1").unwrap();
+  fs::write(dir.join("secrets.json"), r#"{"keys":["api_token"]}"#).unwrap();
+
+  secrets::set_secret(&st, "p1", "openai", "sk-one").unwrap();
+  let listed = secrets::list(&st, "p1").unwrap();
+  let keys: Vec<&str> = listed.iter().map(|s| s.key.as_str()).collect();
+  assert_eq!(keys, ["api_token", "openai"]);
+  assert_eq!(listed[0].created_at_ms, 0);
+  let created = listed[1].created_at_ms;
+  assert!(created > 0 && listed[1].last_used_ms.is_none());
+
+  let info = secrets::set_meta(&st, "p1", "openai", meta(Some("OpenAI"), Some("MY_TOKEN"))).unwrap();
+  assert_eq!((info.provider.as_deref(), info.env.as_deref()), (Some("openai"), Some("MY_TOKEN")));
+
+  // Rotating keeps the metadata and the creation time.
+  std::thread::sleep(std::time::Duration::from_millis(2));
+  secrets::set_secret(&st, "p1", "openai", "sk-two").unwrap();
+  let rotated = secrets::list(&st, "p1").unwrap().into_iter().find(|s| s.key == "openai").unwrap();
+  assert_eq!(rotated.created_at_ms, created);
+  assert!(rotated.updated_at_ms > created);
+  assert_eq!(rotated.purpose.as_deref(), Some("model access"));
+
+  for (bad, why) in [
+    (meta(Some("nope"), None), "unknown provider"),
+    (meta(None, Some("PATH")), "can't be set"),
+    (meta(None, Some("DYLD_INSERT_LIBRARIES")), "can't be set"),
+    (meta(None, Some("1BAD")), "invalid environment variable"),
+    (meta(Some("openai"), None), "already the openai key"),
+    (meta(None, Some("OPENAI_API_KEY")), "already exported as OPENAI_API_KEY"),
+  ] {
+    let err = secrets::set_meta(&st, "p1", "api_token", bad).unwrap_err().to_string();
+    assert!(err.contains(why), "{err}");
+  }
+  assert!(secrets::set_meta(&st, "p1", "missing", meta(None, None)).is_err());
+
+  // A run reads only the secrets mapped into it.
+  secrets::set_secret(&st, "p1", "unmapped", "sk-unmapped").unwrap();
+  let run = RunSecrets::for_profile(&st, "p1");
+  assert_eq!(run.exec_env, [("MY_TOKEN".to_string(), "sk-two".to_string())]);
+  assert_eq!(run.values, [("openai".to_string(), "sk-two".to_string())]);
+  secrets::delete_secret(&st, "p1", "unmapped").unwrap();
+
+  secrets::mark_used(&st, "p1", &["api_token"]);
+  assert!(secrets::list(&st, "p1").unwrap()[0].last_used_ms.is_some());
+
+  secrets::delete_secret(&st, "p1", "openai").unwrap();
+  assert_eq!(secrets::secret_keys(&st, "p1").unwrap(), ["api_token"]);
+  let raw = fs::read_to_string(dir.join("secrets.json")).unwrap();
+  assert!(!raw.contains("\"keys\"") && !raw.contains("sk-"), "{raw}");
+}
+
+#[test]
+fn redacts_longest_values_first() {
+  let run = RunSecrets {
+    values: vec![("short".into(), "abc".into()), ("token".into(), "s3cret".into()), ("long".into(), "s3cret-and-more".into())],
+    ..Default::default()
+  };
+  assert_eq!(run.redact("a s3cret-and-more, a s3cret, abc"), "a [redacted:long], a [redacted:token], abc");
+}
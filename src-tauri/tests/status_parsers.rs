@@ -0,0 +1,131 @@
+// Parsers for `openclaw gateway status` / `openclaw models status` against recorded outputs.
+
+use tauri_appopenclaw_macapp_lib::{
+  cli_output::{json_document, parse_duration},
+  gateway::{self, RunState},
+  models::{self, AuthState, ModelsInfo},
+};
+
+fn fixture(name: &str) -> String {
+  let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/openclaw").join(name);
+  std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+fn gateway_json(name: &str) -> gateway::GatewayInfo {
+  gateway::parse_status_json(&json_document(&fixture(name)).expect("json document"))
+}
+
+#[test]
+fn gateway_json_with_nested_runtime() {
+  let info = gateway_json("gateway_status_running.json");
+  assert_eq!(info.state, RunState::Running);
+  assert_eq!(info.pid, Some(48213));
+  assert_eq!(info.port, Some(18789));
+  assert_eq!(info.uptime_secs, Some(7384));
+  assert_eq!(info.version.as_deref(), Some("2026.2.14"));
+  assert_eq!(info.source, "json");
+}
+
+#[test]
+fn gateway_json_after_a_banner_line() {
+  let info = gateway_json("gateway_status_flat.json");
+  assert_eq!(info.state, RunState::Stopped);
+  assert_eq!(info.pid, None);
+  assert_eq!(info.port, Some(18790));
+  assert_eq!(info.version.as_deref(), Some("2026.1.30"));
+}
+
+#[test]
+fn gateway_text_running() {
+  let info = gateway::parse_status_text(&fixture("gateway_status_running.txt"));
+  assert_eq!(info.state, RunState::Running);
+  assert_eq!(info.pid, Some(48213));
+  assert_eq!(info.port, Some(18789));
+  assert_eq!(info.uptime_secs, Some(2 * 3600 + 3 * 60 + 4));
+  assert_eq!(info.version.as_deref(), Some("2026.2.14"));
+  assert_eq!(info.source, "text");
+}
+
+#[test]
+fn gateway_text_stopped() {
+  let info = gateway::parse_status_text(&fixture("gateway_status_stopped.txt"));
+  assert_eq!(info.state, RunState::Stopped);
+  assert_eq!(info.pid, None);
+  assert_eq!(info.port, Some(18789));
+  assert_eq!(info.uptime_secs, None);
+  assert_eq!(info.version, None);
+}
+
+#[test]
+fn gateway_text_from_systemd() {
+  let info = gateway::parse_status_text(&fixture("gateway_status_systemd.txt"));
+  assert_eq!(info.state, RunState::Running);
+  assert_eq!(info.pid, Some(1337));
+  assert_eq!(info.port, Some(18789));
+  assert_eq!(info.uptime_secs, Some(86_400 + 3 * 3600 + 2 * 60 + 1));
+  assert_eq!(info.version.as_deref(), Some("2026.2.1"));
+}
+
+#[test]
+fn gateway_text_tolerates_garbage() {
+  let info = gateway::parse_status_text("error: something odd happened\n\n{}");
+  assert_eq!(info.state, RunState::Unknown);
+  assert_eq!((info.pid, info.port, info.uptime_secs), (None, None, None));
+}
+
+fn auth(info: &ModelsInfo, id: &str) -> (AuthState, Option<String>) {
+  let p = info.providers.iter().find(|p| p.id == id).unwrap_or_else(|| panic!("no provider {id}: {info:?}"));
+  (p.auth, p.detail.clone())
+}
+
+#[test]
+fn models_json_with_auth_map() {
+  let info = models::parse_status_json(&json_document(&fixture("models_status.json")).unwrap());
+  assert_eq!(info.default_model.as_deref(), Some("anthropic/claude-sonnet-4-5"));
+  assert_eq!(info.fallbacks, ["openai/gpt-5-mini", "ollama/llama3.1:8b"]);
+  assert_eq!(info.providers.len(), 4);
+  assert_eq!(auth(&info, "anthropic"), (AuthState::Ok, Some("oauth".into())));
+  assert_eq!(auth(&info, "openai").0, AuthState::Missing);
+  assert_eq!(auth(&info, "google"), (AuthState::Expired, Some("api key".into())));
+  assert_eq!(auth(&info, "ollama").0, AuthState::Ok);
+}
+
+#[test]
+fn models_json_with_provider_list() {
+  let info = models::parse_status_json(&json_document(&fixture("models_status_list.json")).unwrap());
+  assert_eq!(info.default_model.as_deref(), Some("ollama/huihui_ai/qwen3-abliterated:8b"));
+  assert!(info.fallbacks.is_empty());
+  assert_eq!(auth(&info, "ollama"), (AuthState::Ok, Some("local".into())));
+  assert_eq!(auth(&info, "openrouter").0, AuthState::Missing);
+  assert_eq!(auth(&info, "groq").0, AuthState::Unknown);
+}
+
+#[test]
+fn models_plain_text_sections() {
+  let info = models::parse_status_text(&fixture("models_status_plain.txt"));
+  assert_eq!(info.default_model.as_deref(), Some("anthropic/claude-sonnet-4-5"));
+  assert_eq!(info.fallbacks, ["openai/gpt-5-mini", "ollama/llama3.1:8b"]);
+  assert_eq!(info.providers.len(), 3);
+  assert_eq!(auth(&info, "anthropic"), (AuthState::Ok, Some("oauth".into())));
+  assert_eq!(auth(&info, "openai"), (AuthState::Missing, None));
+  assert_eq!(auth(&info, "google"), (AuthState::Expired, Some("api key".into())));
+  assert_eq!(info.source, "text");
+}
+
+#[test]
+fn models_key_value_pairs() {
+  let info = models::parse_status_text(&fixture("models_status_kv.txt"));
+  assert_eq!(info.default_model.as_deref(), Some("ollama/huihui_ai/qwen3-abliterated:8b"));
+  assert_eq!(info.fallbacks, ["openai/gpt-5-mini"]);
+  assert_eq!(auth(&info, "ollama").0, AuthState::Ok);
+  assert_eq!(auth(&info, "openai").0, AuthState::Missing);
+}
+
+#[test]
+fn durations() {
+  assert_eq!(parse_duration("93s"), Some(93));
+  assert_eq!(parse_duration("3d4h"), Some(3 * 86_400 + 4 * 3600));
+  assert_eq!(parse_duration("1 day, 4 hours"), Some(86_400 + 4 * 3600));
+  assert_eq!(parse_duration("01:02:03"), Some(3723));
+  assert_eq!(parse_duration("5 restarts"), None);
+}
@@ -0,0 +1,55 @@
+use std::{
+  any::Any,
+  collections::HashMap,
+  sync::{Arc, Mutex, Once},
+};
+
+use keyring::credential::{Credential, CredentialApi, CredentialBuilderApi};
+
+// In-memory stand-in for the OS keychain, shared by every entry in the test process: unlike
+// keyring's mock store, a value set through one Entry can be read back through another, the way
+// it is after a restart.
+
+type Store = Arc<Mutex<HashMap<(String, String), Vec<u8>>>>;
+
+struct Cred {
+  store: Store,
+  id: (String, String),
+}
+
+impl CredentialApi for Cred {
+  fn set_secret(&self, secret: &[u8]) -> keyring::Result<()> {
+    self.store.lock().unwrap().insert(self.id.clone(), secret.to_vec());
+    Ok(())
+  }
+
+  fn get_secret(&self) -> keyring::Result<Vec<u8>> {
+    self.store.lock().unwrap().get(&self.id).cloned().ok_or(keyring::Error::NoEntry)
+  }
+
+  fn delete_credential(&self) -> keyring::Result<()> {
+    self.store.lock().unwrap().remove(&self.id).map(|_| ()).ok_or(keyring::Error::NoEntry)
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+}
+
+struct Builder(Store);
+
+impl CredentialBuilderApi for Builder {
+  fn build(&self, _target: Option<&str>, service: &str, user: &str) -> keyring::Result<Box<Credential>> {
+    Ok(Box::new(Cred { store: self.0.clone(), id: (service.to_string(), user.to_string()) }))
+  }
+
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+}
+
+// Makes the test keychain the default store. Call before touching secrets.
+pub fn install() {
+  static ONCE: Once = Once::new();
+  ONCE.call_once(|| keyring::set_default_credential_builder(Box::new(Builder(Store::default()))));
+}
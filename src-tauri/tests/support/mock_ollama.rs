@@ -0,0 +1,146 @@
+use std::{
+  collections::{HashMap, VecDeque},
+  io::{BufRead, BufReader, Read, Write},
+  net::{TcpListener, TcpStream},
+  sync::{Arc, Mutex},
+};
+
+use serde_json::{json, Value};
+
+// Minimal stand-in for Ollama's /api/chat. Each POST pops the next scripted assistant reply
+// (answered as one JSON object, or as NDJSON chunks when the request asks for `stream`).
+// GETs serve pages registered with `page`, so web_get can be exercised without the network.
+// Requests are recorded for assertions; an exhausted script answers 500.
+
+#[derive(Default)]
+struct State {
+  replies: VecDeque<String>,
+  requests: Vec<Value>,
+  pages: HashMap<String, String>,
+}
+
+pub struct MockOllama {
+  base_url: String,
+  state: Arc<Mutex<State>>,
+}
+
+impl MockOllama {
+  pub fn start(replies: &[&str]) -> Self {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock ollama");
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let state = Arc::new(Mutex::new(State {
+      replies: replies.iter().map(|r| r.to_string()).collect(),
+      ..Default::default()
+    }));
+
+    let st = state.clone();
+    std::thread::spawn(move || {
+      for stream in listener.incoming().flatten() {
+        let _ = handle(stream, &st);
+      }
+    });
+
+    Self { base_url, state }
+  }
+
+  pub fn base_url(&self) -> String {
+    self.base_url.clone()
+  }
+
+  // Appends a reply to the script (for replies that need `base_url`, e.g. web_get targets).
+  pub fn reply(&self, text: &str) {
+    self.state.lock().unwrap().replies.push_back(text.to_string());
+  }
+
+  pub fn page(&self, path: &str, body: &str) -> String {
+    self.state.lock().unwrap().pages.insert(path.to_string(), body.to_string());
+    format!("{}{}", self.base_url, path)
+  }
+
+  // Request bodies received on /api/chat, oldest first.
+  pub fn requests(&self) -> Vec<Value> {
+    self.state.lock().unwrap().requests.clone()
+  }
+
+  // Content of the last message in the n-th /api/chat request.
+  pub fn last_prompt(&self, n: usize) -> String {
+    let reqs = self.requests();
+    let msgs = reqs[n]["messages"].as_array().expect("messages array");
+    msgs.last().and_then(|m| m["content"].as_str()).unwrap_or_default().to_string()
+  }
+}
+
+fn handle(stream: TcpStream, state: &Mutex<State>) -> std::io::Result<()> {
+  let mut reader = BufReader::new(stream.try_clone()?);
+
+  let mut request_line = String::new();
+  reader.read_line(&mut request_line)?;
+  let mut parts = request_line.split_whitespace();
+  let method = parts.next().unwrap_or_default().to_string();
+  let path = parts.next().unwrap_or_default().to_string();
+
+  let mut content_length = 0usize;
+  loop {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+      break;
+    }
+    if let Some((k, v)) = line.split_once(':') {
+      if k.trim().eq_ignore_ascii_case("content-length") {
+        content_length = v.trim().parse().unwrap_or(0);
+      }
+    }
+  }
+  let mut body = vec![0u8; content_length];
+  reader.read_exact(&mut body)?;
+
+  let mut out = stream;
+  match (method.as_str(), path.as_str()) {
+    ("POST", "/api/chat") => {
+      let req: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+      let streaming = req["stream"].as_bool().unwrap_or(false);
+      let reply = {
+        let mut st = state.lock().unwrap();
+        st.requests.push(req);
+        st.replies.pop_front()
+      };
+
+      match reply {
+        None => respond(&mut out, "500 Internal Server Error", "text/plain", "mock script exhausted"),
+        Some(text) if streaming => {
+          let mut ndjson = String::new();
+          let chars: Vec<char> = text.chars().collect();
+          for chunk in chars.chunks(4) {
+            let piece: String = chunk.iter().collect();
+            ndjson.push_str(&json!({"message": {"role": "assistant", "content": piece}, "done": false}).to_string());
+            ndjson.push('\n');
+          }
+          ndjson.push_str(&json!({"message": {"role": "assistant", "content": ""}, "done": true}).to_string());
+          ndjson.push('\n');
+          respond(&mut out, "200 OK", "application/x-ndjson", &ndjson)
+        }
+        Some(text) => {
+          let body = json!({"message": {"role": "assistant", "content": text}, "done": true}).to_string();
+          respond(&mut out, "200 OK", "application/json", &body)
+        }
+      }
+    }
+    ("GET", p) => {
+      let page = state.lock().unwrap().pages.get(p).cloned();
+      match page {
+        Some(b) => respond(&mut out, "200 OK", "text/plain", &b),
+        None => respond(&mut out, "404 Not Found", "text/plain", "not found"),
+      }
+    }
+    _ => respond(&mut out, "405 Method Not Allowed", "text/plain", ""),
+  }
+}
+
+fn respond(out: &mut TcpStream, status: &str, content_type: &str, body: &str) -> std::io::Result<()> {
+  write!(
+    out,
+    "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+    body.len()
+  )?;
+  out.flush()
+}
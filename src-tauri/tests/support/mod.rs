@@ -0,0 +1,5 @@
+// Each test crate uses a different subset of the helpers.
+#![allow(dead_code)]
+
+pub mod keychain;
+pub mod mock_ollama;
@@ -0,0 +1,77 @@
+// The passphrase-encrypted secret vault and moving secrets between backends.
+
+mod support;
+
+use std::fs;
+
+use tauri_appopenclaw_macapp_lib::{
+  core::Storage,
+  secrets,
+  settings::{self, SecretBackend},
+  vault,
+};
+
+#[test]
+fn vault_stores_secrets_encrypted_and_locks() {
+  support::keychain::install();
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  settings::update_settings(&st, "p1", |s| s.secret_backend = Some(SecretBackend::Vault)).unwrap();
+
+  let err = secrets::set_secret(&st, "p1", "openai", "sk-vaulted").unwrap_err().to_string();
+  assert!(err.contains("locked"), "{err}");
+  assert!(secrets::list(&st, "p1").unwrap().is_empty());
+
+  let status = vault::unlock(&st, "p1", "correct horse").unwrap();
+  assert!(status.exists && status.unlocked);
+  secrets::set_secret(&st, "p1", "openai", "sk-vaulted").unwrap();
+  assert_eq!(secrets::get_secret(&st, "p1", "openai").unwrap().as_deref(), Some("sk-vaulted"));
+  assert_eq!(secrets::list(&st, "p1").unwrap()[0].backend, SecretBackend::Vault);
+  let dir = st.profile_dir("p1").unwrap();
+  assert!(!fs::read_to_string(dir.join("secrets.vault")).unwrap().contains("sk-vaulted"));
+
+  vault::lock(&st, "p1").unwrap();
+  assert!(secrets::get_secret(&st, "p1", "openai").is_err());
+  // A run goes on without the secrets it can't read, and says why.
+  secrets::set_meta(&st, "p1", "openai", secrets::SecretMeta { purpose: None, provider: None, env: Some("MY_TOKEN".into()) }).unwrap();
+  let run = secrets::RunSecrets::for_profile(&st, "p1");
+  assert!(run.exec_env.is_empty() && run.skipped[0].contains("locked"), "{:?}", run.skipped);
+  assert!(vault::unlock(&st, "p1", "wrong").is_err());
+  assert!(!vault::status(&st, "p1").unwrap().unlocked);
+  vault::unlock(&st, "p1", "correct horse").unwrap();
+  assert_eq!(secrets::get_secret(&st, "p1", "openai").unwrap().as_deref(), Some("sk-vaulted"));
+
+  secrets::delete_secret(&st, "p1", "openai").unwrap();
+  assert!(secrets::get_secret(&st, "p1", "openai").unwrap().is_none());
+  assert!(secrets::list(&st, "p1").unwrap().is_empty());
+}
+
+#[test]
+fn migrates_secrets_between_backends() {
+  support::keychain::install();
+  let home = tempfile::tempdir().unwrap();
+  let st = Storage::new(home.path().join("data"), home.path());
+  settings::update_settings(&st, "p1", |s| s.secret_backend = Some(SecretBackend::Vault)).unwrap();
+  vault::unlock(&st, "p1", "pass").unwrap();
+  secrets::set_secret(&st, "p1", "openai", "sk-one").unwrap();
+
+  let res = secrets::migrate(&st, "p1", SecretBackend::Keychain).unwrap();
+  assert_eq!((res.moved.as_slice(), res.errors.len()), (&["openai".to_string()][..], 0));
+  assert_eq!(secrets::list(&st, "p1").unwrap()[0].backend, SecretBackend::Keychain);
+  let status = secrets::backend_status(&st, "p1").unwrap();
+  assert_eq!((status.configured, status.effective), (Some(SecretBackend::Keychain), SecretBackend::Keychain));
+
+  assert_eq!(secrets::get_secret(&st, "p1", "openai").unwrap().as_deref(), Some("sk-one"));
+
+  // A secret that can't be written to the target stays where it is.
+  vault::lock(&st, "p1").unwrap();
+  let res = secrets::migrate(&st, "p1", SecretBackend::Vault).unwrap();
+  assert!(res.moved.is_empty());
+  assert!(res.errors[0].contains("locked"), "{:?}", res.errors);
+  assert_eq!(secrets::list(&st, "p1").unwrap()[0].backend, SecretBackend::Keychain);
+
+  vault::unlock(&st, "p1", "pass").unwrap();
+  assert_eq!(secrets::migrate(&st, "p1", SecretBackend::Vault).unwrap().moved, ["openai"]);
+  assert_eq!(secrets::list(&st, "p1").unwrap()[0].backend, SecretBackend::Vault);
+  assert_eq!(secrets::get_secret(&st, "p1", "openai").unwrap().as_deref(), Some("sk-one"));
+}
@@ -23,6 +23,7 @@ import {
   gatewayStop,
   profilesCreate,
   profilesDelete,
+  profilesDismissMigrationNotes,
   profilesList,
   profilesRename,
   profilesSetActive,
@@ -234,7 +235,7 @@ export default function App() {
       if (!active) return;
       const s = await gatewayStatus(active.id);
       setGw(s);
-      const l = await gatewayLogs(200);
+      const l = await gatewayLogs(200, active.id);
       setGwLogs(l);
     } catch (e) {
       const msg = e instanceof Error ? e.message : String(e);
@@ -250,7 +251,7 @@ export default function App() {
       if (!active) return;
       const s = await gatewayStart(active.id);
       setGw(s);
-      const l = await gatewayLogs(200);
+      const l = await gatewayLogs(200, active.id);
       setGwLogs(l);
       toasts.push({ kind: "success", title: "Gateway started", timeoutMs: 2500 });
     } catch (e) {
@@ -267,7 +268,7 @@ export default function App() {
       if (!active) return;
       const s = await gatewayStop(active.id);
       setGw(s);
-      const l = await gatewayLogs(200);
+      const l = await gatewayLogs(200, active.id);
       setGwLogs(l);
       toasts.push({ kind: "success", title: "Gateway stopped", timeoutMs: 2500 });
     } catch (e) {
@@ -284,7 +285,7 @@ export default function App() {
       if (!active) return;
       const s = await gatewayRestart(active.id);
       setGw(s);
-      const l = await gatewayLogs(200);
+      const l = await gatewayLogs(200, active.id);
       setGwLogs(l);
       toasts.push({ kind: "success", title: "Gateway restarted", timeoutMs: 2500 });
     } catch (e) {
@@ -322,6 +323,15 @@ export default function App() {
     }
   }
 
+  async function dismissMigrationNotes() {
+    try {
+      setStore(await profilesDismissMigrationNotes());
+    } catch (e) {
+      const msg = e instanceof Error ? e.message : String(e);
+      toasts.push({ kind: "error", title: "Failed to dismiss", message: msg, timeoutMs: 6000 });
+    }
+  }
+
   async function renameProfile(profileId: string) {
     const current = store?.profiles.find((p) => p.id === profileId)?.name ?? "";
     setModal({ kind: "rename_profile", profileId, value: current });
@@ -542,8 +552,8 @@ export default function App() {
                   if (!modal || modal.kind !== "delete_profile") return;
                   setBusy("Deleting…");
                   try {
-                    const s = await profilesDelete(modal.profileId);
-                    setStore(s);
+                    const res = await profilesDelete(modal.profileId);
+                    setStore(res.store);
                     setModal(null);
                     toasts.push({ kind: "success", title: "Profile deleted", timeoutMs: 2500 });
                   } finally {
@@ -760,6 +770,13 @@ export default function App() {
           {banner ? (
             <ErrorBanner title={banner.title} message={banner.message} onClose={() => setBanner(null)} />
           ) : null}
+          {store?.migration_notes?.length ? (
+            <ErrorBanner
+              title="Profiles were repaired after an update"
+              message={store.migration_notes.join("\n")}
+              onClose={() => void dismissMigrationNotes()}
+            />
+          ) : null}
           <div className="oc-grid">
             {section === "chats" ? (
               <ChatsPanel
@@ -1,9 +1,26 @@
 import { invoke } from "@tauri-apps/api/core";
 
+export type AuthState = "ok" | "missing" | "expired" | "unknown";
+
+export type ProviderStatus = {
+  id: string;
+  auth: AuthState;
+  detail: string | null;
+};
+
+export type ModelsInfo = {
+  default_model: string | null;
+  fallbacks: string[];
+  providers: ProviderStatus[];
+  source: "json" | "text";
+};
+
 export type ModelsStatus = {
   exit_code: number;
   stdout: string;
   stderr: string;
+  // Parsed `models status` output; null for `models set`.
+  info: ModelsInfo | null;
 };
 
 export async function modelsStatus(profileId: string): Promise<ModelsStatus> {
@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 export type Profile = {
   id: string;
@@ -10,28 +11,184 @@ export type ProfilesStore = {
   version: number;
   active_profile_id: string | null;
   profiles: Profile[];
+  // What the one-time id migration changed, shown until dismissed (profilesDismissMigrationNotes).
+  migration_notes?: string[];
 };
 
 export async function profilesList(): Promise<ProfilesStore> {
   return invoke("profiles_list");
 }
 
-export async function profilesCreate(name: string): Promise<ProfilesStore> {
-  return invoke("profiles_create", { name });
+// templateId applies a profile template (see profileTemplatesList) to the new profile.
+export async function profilesCreate(name: string, templateId?: string): Promise<ProfilesStore> {
+  return invoke("profiles_create", { name, templateId: templateId ?? null });
+}
+
+// New active profile copying the source's settings, prompt presets and workspace, plus its chats if asked.
+export async function profilesClone(sourceId: string, name: string, includeChats = false): Promise<ProfilesStore> {
+  return invoke("profiles_clone", { sourceId, name, includeChats });
+}
+
+export type ProfileTemplate = {
+  id: string;
+  name: string;
+  description: string;
+  builtin: boolean;
+  // ProfileSettings fields set over the defaults.
+  settings: Partial<ProfileSettings>;
+  // Workspace seed files by relative path.
+  workspace: Record<string, string>;
+};
+
+export async function profileTemplatesList(): Promise<ProfileTemplate[]> {
+  return invoke("profile_templates_list");
+}
+
+// Saves the profile's settings and text workspace files as a new template.
+export async function profileTemplatesSave(
+  profileId: string,
+  name: string,
+  description?: string,
+): Promise<ProfileTemplate> {
+  return invoke("profile_templates_save", { profileId, name, description: description ?? null });
+}
+
+export async function profileTemplatesDelete(templateId: string): Promise<ProfileTemplate[]> {
+  return invoke("profile_templates_delete", { templateId });
 }
 
 export async function profilesSetActive(profileId: string): Promise<ProfilesStore> {
   return invoke("profiles_set_active", { profileId });
 }
 
+export async function profilesDismissMigrationNotes(): Promise<ProfilesStore> {
+  return invoke("profiles_dismiss_migration_notes");
+}
+
 export async function profilesRename(profileId: string, name: string): Promise<ProfilesStore> {
   return invoke("profiles_rename", { profileId, name });
 }
 
-export async function profilesDelete(profileId: string): Promise<ProfilesStore> {
-  return invoke("profiles_delete", { profileId });
+export type TrashedProfile = {
+  trash_id: string;
+  profile: Profile;
+  deleted_at_ms: number;
+  expires_at_ms: number;
+  openclaw_profile: string | null;
+};
+
+export type ProfileDeleteResult = {
+  store: ProfilesStore;
+  // null when deleted permanently.
+  trash: TrashedProfile | null;
+  removed_secrets: string[];
+  warnings: string[];
+};
+
+// Moves the profile to the trash (restorable until expires_at_ms) unless permanent; openclawProfile also
+// removes the profile's own OpenClaw profile.
+export async function profilesDelete(
+  profileId: string,
+  opts: { permanent?: boolean; openclawProfile?: boolean } = {},
+): Promise<ProfileDeleteResult> {
+  return invoke("profiles_delete", {
+    profileId,
+    permanent: opts.permanent ?? null,
+    openclawProfile: opts.openclawProfile ?? null,
+  });
+}
+
+export async function profilesTrashList(): Promise<TrashedProfile[]> {
+  return invoke("profiles_trash_list");
 }
 
+export async function profilesRestore(trashId: string): Promise<ProfilesStore> {
+  return invoke("profiles_restore", { trashId });
+}
+
+// Deletes a trashed profile and its secrets now; returns the secret keys removed.
+export async function profilesTrashPurge(trashId: string): Promise<string[]> {
+  return invoke("profiles_trash_purge", { trashId });
+}
+
+export type ProfileExportResult = {
+  path: string;
+  chats: number;
+  files: number;
+  // Secret keys included, encrypted with the passphrase.
+  secrets: string[];
+};
+
+export type ProfileImportResult = {
+  store: ProfilesStore;
+  profile: Profile;
+  chats: number;
+  files: number;
+  secrets: string[];
+  // What didn't carry over as-is, for the user to review.
+  conflicts: string[];
+};
+
+// Secrets are only exported when a passphrase is given. A profile with encrypted chats needs one;
+// its chats are sealed with it.
+export async function profileExport(profileId: string, dest: string, passphrase?: string): Promise<ProfileExportResult> {
+  return invoke("profile_export", { profileId, dest, passphrase: passphrase ?? null });
+}
+
+// Creates a new profile (with fresh chat and session ids) from a bundle.
+export async function profileImport(src: string, passphrase?: string): Promise<ProfileImportResult> {
+  return invoke("profile_import", { src, passphrase: passphrase ?? null });
+}
+
+export type SecretInfo = {
+  key: string;
+  purpose: string | null;
+  // 0 for secrets stored before metadata was kept.
+  created_at_ms: number;
+  // Last time the value was set (rotated).
+  updated_at_ms: number;
+  last_used_ms: number | null;
+  // Provider whose API key this is (see secretProviders).
+  provider: string | null;
+  // Environment variable the value is exported as to exec and OpenClaw.
+  env: string | null;
+  backend: SecretBackend;
+};
+
+// Where secret values are stored: the OS keychain, or the profile's passphrase-encrypted vault.
+export type SecretBackend = "keychain" | "vault";
+
+export type VaultStatus = { exists: boolean; unlocked: boolean };
+
+export type SecretBackendStatus = {
+  // The profile's setting; null picks the keychain when it is available.
+  configured: SecretBackend | null;
+  effective: SecretBackend;
+  keychain_available: boolean;
+  // Why the keychain isn't available.
+  keychain_error: string | null;
+  vault: VaultStatus;
+};
+
+export type SecretMigrateResult = { backend: SecretBackend; moved: string[]; errors: string[] };
+
+export type SecretMeta = { purpose?: string | null; provider?: string | null; env?: string | null };
+
+export async function secretsList(profileId: string): Promise<SecretInfo[]> {
+  return invoke("secrets_list", { profileId });
+}
+
+// Replaces the secret's purpose and mappings.
+export async function secretSetMeta(profileId: string, key: string, meta: SecretMeta): Promise<SecretInfo> {
+  return invoke("secret_set_meta", { profileId, key, meta });
+}
+
+// [provider, env var] pairs.
+export async function secretProviders(): Promise<[string, string][]> {
+  return invoke("secret_providers");
+}
+
+// Stores a new secret, or rotates an existing one's value.
 export async function secretSet(profileId: string, key: string, value: string): Promise<void> {
   return invoke("secret_set", { profileId, key, value });
 }
@@ -44,15 +201,69 @@ export async function secretDelete(profileId: string, key: string): Promise<void
   return invoke("secret_delete", { profileId, key });
 }
 
+export async function secretsBackendStatus(profileId: string): Promise<SecretBackendStatus> {
+  return invoke("secrets_backend_status", { profileId });
+}
+
+// Moves every secret to `to` and makes it the profile's backend.
+export async function secretsMigrate(profileId: string, to: SecretBackend): Promise<SecretMigrateResult> {
+  return invoke("secrets_migrate", { profileId, to });
+}
+
+// Creates the vault on first unlock.
+export async function secretsVaultUnlock(profileId: string, passphrase: string): Promise<VaultStatus> {
+  return invoke("secrets_vault_unlock", { profileId, passphrase });
+}
+
+export async function secretsVaultLock(profileId: string): Promise<VaultStatus> {
+  return invoke("secrets_vault_lock", { profileId });
+}
+
+export type GatewayInfo = {
+  state: "running" | "stopped" | "unknown";
+  pid: number | null;
+  port: number | null;
+  uptime_secs: number | null;
+  version: string | null;
+  source: "json" | "text";
+};
+
 export type GatewayStatus = {
   exit_code: number;
   stdout: string;
   stderr: string;
+  // Parsed `gateway status` output; null for start/stop/restart.
+  info: GatewayInfo | null;
 };
 
 export type GatewayLogs = {
   out: string;
   err: string;
+  // Directory the logs were read from.
+  dir: string;
+};
+
+export type LogLevel = "trace" | "debug" | "info" | "warn" | "error";
+
+export type LogFilter = {
+  // Minimum level; unlabelled lines count as info (warn in gateway.err.log).
+  level?: LogLevel | null;
+  // Regex matched against each line.
+  pattern?: string | null;
+};
+
+export type LogLine = {
+  stream: "out" | "err";
+  level: LogLevel;
+  text: string;
+};
+
+export type GatewayLogEvent = {
+  follow_id: string;
+  profile_id: string | null;
+  lines: LogLine[];
+  // Set when a log file was rotated or truncated since the last event.
+  reset: "rotated" | "truncated" | null;
 };
 
 export type ProfileSettings = {
@@ -61,8 +272,66 @@ export type ProfileSettings = {
   openclaw_profile?: string | null;
   ollama_base_url?: string | null;
   ollama_model?: string | null;
+  // Context window in tokens; null keeps the model's default.
+  ollama_num_ctx?: number | null;
   dev_full_exec_auto?: boolean | null;
   auto_do_mode?: boolean | null;
+  memory_inject?: boolean | null;
+  system_prompt_template?: string | null;
+  intent?: IntentConfig | null;
+  agent_limits?: AgentLimits | null;
+  api_server?: ApiServerConfig | null;
+  gateway_supervisor?: GatewaySupervisorConfig | null;
+  node_path?: string | null;
+  chat_backend?: ChatBackend | null;
+  secret_backend?: SecretBackend | null;
+};
+
+// "ollama" answers in-process; "openclaw" runs `openclaw agent` on the chat's session.
+export type ChatBackend = "ollama" | "openclaw";
+
+export type GatewaySupervisorConfig = {
+  poll_secs: number;
+  backoff_base_secs: number;
+  backoff_max_secs: number;
+  health_url?: string | null;
+};
+
+// Local OpenAI-compatible API. Clients authenticate with the profile's "api_token" secret.
+export type ApiServerConfig = {
+  port: number;
+  chat?: string | null;
+};
+
+export type ApiServerStatus = {
+  running: boolean;
+  base_url: string | null;
+  config: ApiServerConfig | null;
+  // Set when the server is enabled but failed to start with the app.
+  start_error: string | null;
+};
+
+export type AgentLimits = {
+  max_steps: number;
+  max_wall_secs: number;
+  max_tool_output_bytes: number;
+};
+
+export type IntentRule = {
+  phrase: string;
+  negate?: boolean;
+};
+
+export type IntentConfig = {
+  mode: "keywords" | "model";
+  rules?: IntentRule[];
+  model?: string | null;
+};
+
+export type IntentDecision = {
+  force_tools: boolean;
+  method: string;
+  reason: string;
 };
 
 export async function settingsGet(profileId: string): Promise<ProfileSettings> {
@@ -76,6 +345,56 @@ export async function settingsSetOpenclawPath(
   return invoke("settings_set_openclaw_path", { profileId, openclawPath });
 }
 
+export async function settingsSetNodePath(profileId: string, nodePath: string | null): Promise<ProfileSettings> {
+  return invoke("settings_set_node_path", { profileId, nodePath });
+}
+
+export async function settingsSetChatBackend(profileId: string, backend: ChatBackend | null): Promise<ProfileSettings> {
+  return invoke("settings_set_chat_backend", { profileId, backend });
+}
+
+export type DiscoveryCandidate = {
+  tool: "node" | "openclaw";
+  path: string;
+  // nvm, fnm, volta, asdf, homebrew, npm-prefix, path or pinned.
+  source: string;
+  version: string | null;
+  usable: boolean;
+  error: string | null;
+};
+
+export type DiscoveryReport = {
+  node: DiscoveryCandidate[];
+  openclaw: DiscoveryCandidate[];
+  selected_node: string | null;
+  selected_openclaw: string | null;
+  error: string | null;
+};
+
+export type OpenclawSupport = "supported" | "untested" | "unsupported" | "unknown";
+
+export type OpenclawCompat = {
+  version: string | null;
+  support: OpenclawSupport;
+  // Upgrade guidance or a warning; null when supported.
+  message: string | null;
+  caps: {
+    gateway_status_json: boolean;
+    models_status_json: boolean;
+    agents_non_interactive: boolean;
+    agent_channel: boolean;
+  };
+};
+
+export async function openclawCompat(profileId: string): Promise<OpenclawCompat> {
+  return invoke("openclaw_compat", { profileId });
+}
+
+// Every node/openclaw install found, with versions, and what the profile would use.
+export async function discoveryScan(profileId: string | null = null): Promise<DiscoveryReport> {
+  return invoke("discovery_scan", { profileId });
+}
+
 export async function settingsSetOllamaBaseUrl(
   profileId: string,
   ollamaBaseUrl: string | null,
@@ -90,6 +409,10 @@ export async function settingsSetOllamaModel(
   return invoke("settings_set_ollama_model", { profileId, ollamaModel });
 }
 
+export async function settingsSetOllamaNumCtx(profileId: string, numCtx: number | null): Promise<ProfileSettings> {
+  return invoke("settings_set_ollama_num_ctx", { profileId, numCtx });
+}
+
 export async function settingsSetDevFullExecAuto(
   profileId: string,
   enabled: boolean,
@@ -104,24 +427,237 @@ export async function settingsSetAutoDoMode(
   return invoke("settings_set_auto_do_mode", { profileId, enabled });
 }
 
+export async function settingsSetMemoryInject(profileId: string, enabled: boolean): Promise<ProfileSettings> {
+  return invoke("settings_set_memory_inject", { profileId, enabled });
+}
+
+export async function settingsSetSystemPromptTemplate(
+  profileId: string,
+  template: string | null,
+): Promise<ProfileSettings> {
+  return invoke("settings_set_system_prompt_template", { profileId, template });
+}
+
+export async function settingsSetIntent(profileId: string, intent: IntentConfig | null): Promise<ProfileSettings> {
+  return invoke("settings_set_intent", { profileId, intent });
+}
+
+export async function settingsSetAgentLimits(profileId: string, limits: AgentLimits | null): Promise<ProfileSettings> {
+  return invoke("settings_set_agent_limits", { profileId, limits });
+}
+
+export async function settingsSetApiServer(profileId: string, config: ApiServerConfig | null): Promise<ProfileSettings> {
+  return invoke("settings_set_api_server", { profileId, config });
+}
+
+export async function settingsSetGatewaySupervisor(
+  profileId: string,
+  config: GatewaySupervisorConfig | null,
+): Promise<ProfileSettings> {
+  return invoke("settings_set_gateway_supervisor", { profileId, config });
+}
+
+export async function apiServerStatus(profileId: string): Promise<ApiServerStatus> {
+  return invoke("api_server_status", { profileId });
+}
+
+export async function apiServerStart(profileId: string): Promise<ApiServerStatus> {
+  return invoke("api_server_start", { profileId });
+}
+
+export async function apiServerStop(profileId: string): Promise<ApiServerStatus> {
+  return invoke("api_server_stop", { profileId });
+}
+
+export type PromptPreset = {
+  id: string;
+  name: string;
+  template: string;
+  created_at_ms: number;
+  updated_at_ms: number;
+};
+
+export type PromptLibrary = {
+  version: number;
+  presets: PromptPreset[];
+};
+
+export async function promptsList(profileId: string): Promise<PromptLibrary> {
+  return invoke("prompts_list", { profileId });
+}
+
+export async function promptsSave(
+  profileId: string,
+  presetId: string | null,
+  name: string,
+  template: string,
+): Promise<PromptLibrary> {
+  return invoke("prompts_save", { profileId, presetId, name, template });
+}
+
+export async function promptsDelete(profileId: string, presetId: string): Promise<PromptLibrary> {
+  return invoke("prompts_delete", { profileId, presetId });
+}
+
+export async function promptsPreview(profileId: string, presetId: string | null = null): Promise<string> {
+  return invoke("prompts_preview", { profileId, presetId });
+}
+
+export type MemoryEntry = {
+  id: string;
+  date: string;
+  text: string;
+};
+
+export async function memoryList(profileId: string): Promise<MemoryEntry[]> {
+  return invoke("memory_list", { profileId });
+}
+
+export async function memoryAdd(profileId: string, text: string): Promise<MemoryEntry[]> {
+  return invoke("memory_add", { profileId, text });
+}
+
+export async function memoryUpdate(profileId: string, memoryId: string, text: string): Promise<MemoryEntry[]> {
+  return invoke("memory_update", { profileId, memoryId, text });
+}
+
+export async function memoryDelete(profileId: string, memoryId: string): Promise<MemoryEntry[]> {
+  return invoke("memory_delete", { profileId, memoryId });
+}
+
 export async function gatewayStatus(profileId: string): Promise<GatewayStatus> {
   return invoke("gateway_status", { profileId });
 }
 
-export async function gatewayStart(profileId: string): Promise<GatewayStatus> {
-  return invoke("gateway_start", { profileId });
+// Pass `handle` to cancel with openclawCancel; output lines arrive via onOpenclawOutput.
+export async function gatewayStart(profileId: string, handle?: string): Promise<GatewayStatus> {
+  return invoke("gateway_start", { profileId, handle: handle ?? null });
+}
+
+export async function gatewayStop(profileId: string, handle?: string): Promise<GatewayStatus> {
+  return invoke("gateway_stop", { profileId, handle: handle ?? null });
+}
+
+export async function gatewayRestart(profileId: string, handle?: string): Promise<GatewayStatus> {
+  return invoke("gateway_restart", { profileId, handle: handle ?? null });
 }
 
-export async function gatewayStop(profileId: string): Promise<GatewayStatus> {
-  return invoke("gateway_stop", { profileId });
+export async function gatewayLogs(lines = 200, profileId?: string, filter?: LogFilter): Promise<GatewayLogs> {
+  return invoke("gateway_logs", { profileId: profileId ?? null, lines, filter: filter ?? null });
 }
 
-export async function gatewayRestart(profileId: string): Promise<GatewayStatus> {
-  return invoke("gateway_restart", { profileId });
+// Starts pushing new log lines as `gateway:log` events; returns the id to pass to gatewayLogsUnfollow.
+export async function gatewayLogsFollow(profileId?: string, filter?: LogFilter): Promise<string> {
+  return invoke("gateway_logs_follow", { profileId: profileId ?? null, filter: filter ?? null });
 }
 
-export async function gatewayLogs(lines = 200): Promise<GatewayLogs> {
-  return invoke("gateway_logs", { lines });
+export async function gatewayLogsUnfollow(followId: string): Promise<void> {
+  return invoke("gateway_logs_unfollow", { followId });
+}
+
+// Writes the filtered logs to `path` (default: the profile's log_snapshots dir); returns the path.
+export async function gatewayLogsSave(
+  profileId: string,
+  opts: { path?: string; lines?: number; filter?: LogFilter } = {},
+): Promise<string> {
+  return invoke("gateway_logs_save", {
+    profileId,
+    path: opts.path ?? null,
+    lines: opts.lines ?? null,
+    filter: opts.filter ?? null,
+  });
+}
+
+export async function onGatewayLog(cb: (event: GatewayLogEvent) => void) {
+  return listen<GatewayLogEvent>("gateway:log", (event) => cb(event.payload));
+}
+
+export type OpenclawOutputLine = {
+  handle: string | null;
+  stream: "stdout" | "stderr";
+  line: string;
+};
+
+// Stops an openclaw run started with `handle`; false if none is running.
+export async function openclawCancel(handle: string): Promise<boolean> {
+  return invoke("openclaw_cancel", { handle });
+}
+
+export async function onOpenclawOutput(cb: (line: OpenclawOutputLine) => void) {
+  return listen<OpenclawOutputLine>("openclaw:output", (event) => cb(event.payload));
+}
+
+export type OpenclawAgent = {
+  id: string;
+  name: string | null;
+  workspace: string | null;
+  model: string | null;
+  is_default: boolean;
+};
+
+export async function openclawAgentsList(profileId: string): Promise<OpenclawAgent[]> {
+  return invoke("openclaw_agents_list", { profileId });
+}
+
+// workspace defaults to the profile's minimal workspace, model to the profile's model.
+export async function openclawAgentsCreate(
+  profileId: string,
+  agentId: string,
+  opts: { workspace?: string | null; model?: string | null } = {},
+): Promise<OpenclawAgent[]> {
+  return invoke("openclaw_agents_create", {
+    profileId,
+    agentId,
+    workspace: opts.workspace ?? null,
+    model: opts.model ?? null,
+  });
+}
+
+export async function openclawAgentsUpdate(
+  profileId: string,
+  agentId: string,
+  opts: { workspace?: string | null; model?: string | null },
+): Promise<OpenclawAgent[]> {
+  return invoke("openclaw_agents_update", {
+    profileId,
+    agentId,
+    workspace: opts.workspace ?? null,
+    model: opts.model ?? null,
+  });
+}
+
+// Chats using the agent fall back to the desktop agent.
+export async function openclawAgentsDelete(profileId: string, agentId: string): Promise<OpenclawAgent[]> {
+  return invoke("openclaw_agents_delete", { profileId, agentId });
+}
+
+export type GatewayHealth = "unknown" | "running" | "stopped" | "down" | "restarting";
+
+export type RestartRecord = {
+  at_ms: number;
+  attempt: number;
+  ok: boolean;
+  error: string | null;
+};
+
+export type GatewayState = {
+  profile_id: string;
+  supervised: boolean;
+  health: GatewayHealth;
+  since_ms: number;
+  last_check_ms: number | null;
+  last_error: string | null;
+  next_restart_ms: number | null;
+  restarts: RestartRecord[];
+};
+
+export async function gatewayState(profileId: string): Promise<GatewayState> {
+  return invoke("gateway_state", { profileId });
+}
+
+// Emitted by the gateway supervisor whenever a profile's gateway state changes.
+export async function onGatewayState(cb: (state: GatewayState) => void) {
+  return listen<GatewayState>("gateway:state", (event) => cb(event.payload));
 }
 
 export type Chat = {
@@ -133,6 +669,8 @@ export type Chat = {
   agent_id: string | null;
   thinking: string | null;
   worker?: string | null;
+  prompt_preset_id?: string | null;
+  backend?: ChatBackend | null;
 };
 
 export type ChatIndex = {
@@ -147,6 +685,35 @@ export type ChatMessage = {
   role: ChatRole;
   text: string;
   created_at_ms: number;
+  intent?: IntentDecision;
+  pause?: LoopPause;
+};
+
+export type LoopPause = {
+  limit: "steps" | "wall_time" | "tool_output";
+  detail: string;
+  steps_taken: number;
+};
+
+export type TraceStep = {
+  index: number;
+  prompt_messages: number;
+  prompt_chars: number;
+  model_latency_ms: number;
+  response_chars: number;
+  tool: string | null;
+  result_bytes: number | null;
+  note: string | null;
+};
+
+export type AgentTrace = {
+  message_id: string;
+  started_at_ms: number;
+  finished_at_ms: number | null;
+  outcome: "final" | "paused" | "error" | null;
+  steps: TraceStep[];
+  // Problems that didn't stop the run, e.g. a secret that couldn't be read.
+  warnings?: string[];
 };
 
 export type ChatThread = {
@@ -181,6 +748,123 @@ export async function chatsUpdate(
   });
 }
 
+export async function chatsSetPromptPreset(
+  profileId: string,
+  chatId: string,
+  presetId: string | null,
+): Promise<ChatIndex> {
+  return invoke("chats_set_prompt_preset", { profileId, chatId, presetId });
+}
+
+// New chat continuing an existing OpenClaw session; answered by the OpenClaw backend.
+export async function chatsAttachSession(profileId: string, sessionId: string, title?: string): Promise<Chat> {
+  return invoke("chats_attach_session", { profileId, sessionId, title: title ?? null });
+}
+
+export type OpenclawSession = {
+  session_id: string;
+  agent_id: string;
+  path: string;
+  size: number;
+  modified_ms: number;
+  locked: boolean;
+  lock_stale: boolean;
+  chat_title: string | null;
+  // A desktop session no chat uses any more.
+  orphaned: boolean;
+};
+
+export async function openclawSessionsList(profileId: string): Promise<OpenclawSession[]> {
+  return invoke("openclaw_sessions_list", { profileId });
+}
+
+// Removes orphaned desktop sessions; returns their ids.
+export async function openclawSessionsPrune(profileId: string): Promise<string[]> {
+  return invoke("openclaw_sessions_prune", { profileId });
+}
+
+// Removes stale locks (every lock with force), optionally for one session; returns the lock paths.
+export async function openclawSessionsClearLocks(
+  profileId: string,
+  opts: { sessionId?: string; force?: boolean } = {},
+): Promise<string[]> {
+  return invoke("openclaw_sessions_clear_locks", {
+    profileId,
+    sessionId: opts.sessionId ?? null,
+    force: opts.force ?? null,
+  });
+}
+
+// The agent must exist (see openclawAgentsList); null uses the desktop agent.
+export async function chatsSetAgent(profileId: string, chatId: string, agentId: string | null): Promise<ChatIndex> {
+  return invoke("chats_set_agent", { profileId, chatId, agentId });
+}
+
+// null falls back to the profile's chat_backend.
+export async function chatsSetBackend(profileId: string, chatId: string, backend: ChatBackend | null): Promise<ChatIndex> {
+  return invoke("chats_set_backend", { profileId, chatId, backend });
+}
+
+// Where the chat history key comes from: a random passphrase in the OS keychain, or one the user types.
+export type ChatKeySource = "keychain" | "passphrase";
+
+export type ChatEncryptionStatus = {
+  enabled: boolean;
+  source: ChatKeySource | null;
+  unlocked: boolean;
+  // A key rotation was interrupted; rotating again finishes it.
+  rotating: boolean;
+  // Cleanup that failed without affecting the result.
+  warnings?: string[];
+};
+
+export async function chatsEncryptionStatus(profileId: string): Promise<ChatEncryptionStatus> {
+  return invoke("chats_encryption_status", { profileId });
+}
+
+// Encrypts chats.json and the threads at rest; the passphrase is only for the "passphrase" source.
+export async function chatsEncryptionEnable(
+  profileId: string,
+  source: ChatKeySource,
+  passphrase?: string,
+): Promise<ChatEncryptionStatus> {
+  return invoke("chats_encryption_enable", { profileId, source, passphrase: passphrase ?? null });
+}
+
+export async function chatsEncryptionDisable(profileId: string): Promise<ChatEncryptionStatus> {
+  return invoke("chats_encryption_disable", { profileId });
+}
+
+// Re-encrypts the history under a new key; finishes an interrupted rotation first.
+export async function chatsEncryptionRotate(
+  profileId: string,
+  source: ChatKeySource,
+  passphrase?: string,
+): Promise<ChatEncryptionStatus> {
+  return invoke("chats_encryption_rotate", { profileId, source, passphrase: passphrase ?? null });
+}
+
+export async function chatsEncryptionUnlock(profileId: string, passphrase?: string): Promise<ChatEncryptionStatus> {
+  return invoke("chats_encryption_unlock", { profileId, passphrase: passphrase ?? null });
+}
+
+// Forgets the key; chats can't be read or written until the next unlock.
+export async function chatsEncryptionLock(profileId: string): Promise<ChatEncryptionStatus> {
+  return invoke("chats_encryption_lock", { profileId });
+}
+
+// stderr lines from an OpenClaw-backed reply while it runs; cancel with openclawCancel(messageId).
+export type ChatProgressEvent = {
+  profile_id: string;
+  chat_id: string;
+  message_id: string;
+  line: string;
+};
+
+export async function onChatProgress(cb: (ev: ChatProgressEvent) => void) {
+  return listen<ChatProgressEvent>("chat_progress", (event) => cb(event.payload));
+}
+
 export async function chatsDelete(profileId: string, chatId: string): Promise<ChatIndex> {
   return invoke("chats_delete", { profileId, chatId });
 }
@@ -210,3 +894,11 @@ export async function chatSendStream(
 ): Promise<ChatSendStreamResult> {
   return invoke("chat_send_stream", { profileId, chatId, text });
 }
+
+export async function chatContinue(profileId: string, chatId: string, steps?: number): Promise<ChatSendStreamResult> {
+  return invoke("chat_continue", { profileId, chatId, steps: steps ?? null });
+}
+
+export async function chatTrace(profileId: string, chatId: string, messageId: string): Promise<AgentTrace | null> {
+  return invoke("chat_trace", { profileId, chatId, messageId });
+}